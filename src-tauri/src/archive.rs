@@ -0,0 +1,322 @@
+// ZIP archives of library PDFs (and their sidecars) for sharing outside the app.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Component, Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::importer::{self, invalid_argument};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveResult {
+    pub archive_path: String,
+    pub entry_count: usize,
+    // Uncompressed bytes of everything that went in
+    pub total_bytes: u64,
+    // Paths left out because they were missing, not files, or gone by the
+    // time they were reached
+    pub warnings: Vec<String>,
+}
+
+// Deepest folder containing all of `dirs`, which are absolute
+fn common_ancestor(dirs: &[PathBuf]) -> PathBuf {
+    let mut common = match dirs.first() {
+        Some(first) => first.clone(),
+        None => return PathBuf::new(),
+    };
+    for dir in &dirs[1..] {
+        while !dir.starts_with(&common) {
+            if !common.pop() {
+                return PathBuf::new();
+            }
+        }
+    }
+    common
+}
+
+// ZIP entry names always use "/", whatever the platform
+fn entry_name(relative: &Path) -> String {
+    relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// First free "<stem>_N<extension>" among the names handed out so far, or the
+// name itself
+fn unique_stem(stem: &str, extension: &str, taken: &HashSet<String>) -> String {
+    let is_taken =
+        |candidate: &str| taken.contains(&format!("{}{}", candidate, extension).to_lowercase());
+    if !is_taken(stem) {
+        return stem.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}_{}", stem, n))
+        .find(|candidate| !is_taken(candidate))
+        .unwrap_or_default()
+}
+
+// Entry names for one file and its sidecars. A taken name, which flattened
+// is any repeated basename, moves the whole group to "<stem>_N" so sidecars
+// still match their PDF.
+fn group_entries(
+    path: &Path,
+    sidecars: &[PathBuf],
+    base: &Path,
+    flatten: bool,
+    taken: &mut HashSet<String>,
+) -> Vec<(PathBuf, String)> {
+    let mut group = vec![path.to_path_buf()];
+    group.extend(sidecars.iter().cloned());
+
+    // Sidecars sit in their PDF's folder, so the group shares one
+    let folder = if flatten {
+        String::new()
+    } else {
+        let parent = path.parent().unwrap_or(base);
+        match entry_name(parent.strip_prefix(base).unwrap_or(parent)) {
+            folder if folder.is_empty() => folder,
+            folder => format!("{}/", folder),
+        }
+    };
+    let stem = crate::file_stem_string(path);
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    let new_stem = unique_stem(&format!("{}{}", folder, stem), &extension, taken);
+    let entries = group
+        .into_iter()
+        .map(|member| {
+            let name = member
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let suffix = name.strip_prefix(&stem).unwrap_or(&name).to_string();
+            (member, format!("{}{}", new_stem, suffix))
+        })
+        .collect::<Vec<_>>();
+
+    for (_, name) in &entries {
+        taken.insert(name.to_lowercase());
+    }
+    entries
+}
+
+// The file for each entry and its name, in order. Paths that can't be
+// archived are reported in warnings.
+fn plan_entries(
+    paths: &[String],
+    include_sidecars: bool,
+    flatten: bool,
+    warnings: &mut Vec<String>,
+) -> Vec<(PathBuf, String)> {
+    // The same file named twice goes in once
+    let mut seen = HashSet::new();
+    let mut files = Vec::new();
+    for path in paths {
+        match fs::canonicalize(crate::os_path(Path::new(path))) {
+            Ok(canonical) if canonical.is_file() => {
+                if seen.insert(canonical.clone()) {
+                    files.push(canonical);
+                }
+            }
+            Ok(_) => warnings.push(format!("Not a file, skipped: {}", path)),
+            Err(_) => warnings.push(format!("File does not exist, skipped: {}", path)),
+        }
+    }
+
+    let parents = files
+        .iter()
+        .filter_map(|file| file.parent().map(Path::to_path_buf))
+        .collect::<Vec<_>>();
+    let base = common_ancestor(&parents);
+
+    // A sidecar also listed in `paths` goes in once, with whichever came first
+    let mut placed = HashSet::new();
+    let mut taken = HashSet::new();
+    let mut entries = Vec::new();
+    for file in &files {
+        if !placed.insert(file.clone()) {
+            continue;
+        }
+        let sidecars = if include_sidecars {
+            crate::existing_sidecars(file)
+                .iter()
+                .filter_map(|sidecar| fs::canonicalize(sidecar).ok())
+                .filter(|sidecar| placed.insert(sidecar.clone()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        entries.extend(group_entries(file, &sidecars, &base, flatten, &mut taken));
+    }
+    entries
+}
+
+// Writes `paths` (and with include_sidecars their same-stem sidecars) into a
+// new ZIP at output_path. Entries keep their folders relative to the deepest
+// folder they share, or with flatten all sit at the top, where a repeated
+// name gets a "_N" suffix. compression is "deflate" (default) or "store".
+// Emits "archive-progress" after each entry. The archive is written to
+// "<output_path>.part" and only appears at output_path once complete.
+#[tauri::command]
+pub async fn create_archive(
+    app: AppHandle,
+    paths: Vec<String>,
+    output_path: String,
+    include_sidecars: bool,
+    flatten: bool,
+    compression: Option<String>,
+) -> Result<ArchiveResult, String> {
+    let method = match compression.as_deref().unwrap_or("deflate") {
+        "deflate" => CompressionMethod::Deflated,
+        "store" => CompressionMethod::Stored,
+        other => {
+            return Err(invalid_argument(
+                "invalid_compression",
+                format!("Unsupported compression: {}", other),
+            ))
+        }
+    };
+    if paths.is_empty() {
+        return Err(invalid_argument(
+            "invalid_input",
+            "No files to archive".to_string(),
+        ));
+    }
+
+    let output = crate::os_path(Path::new(&output_path));
+    if !output.is_absolute() {
+        return Err(invalid_argument(
+            "invalid_output_path",
+            format!("Archive path must be absolute: {}", output_path),
+        ));
+    }
+    if output.exists() {
+        return Err(format!("A file already exists at {}", output_path));
+    }
+    if !output.parent().is_some_and(Path::is_dir) {
+        return Err(format!(
+            "Directory does not exist: {}",
+            output.parent().unwrap_or(&output).display()
+        ));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut warnings = Vec::new();
+
+        let entries = plan_entries(&paths, include_sidecars, flatten, &mut warnings);
+
+        let part = importer::part_path(&output);
+        let written = (|| -> Result<(usize, u64), String> {
+            let file =
+                File::create(&part).map_err(|e| format!("Failed to create archive: {}", e))?;
+            let mut zip = ZipWriter::new(BufWriter::new(file));
+            let options = SimpleFileOptions::default()
+                .compression_method(method)
+                .large_file(true);
+
+            let mut entry_count = 0;
+            let mut total_bytes = 0;
+            for (index, (path, name)) in entries.iter().enumerate() {
+                // Opened before the entry starts, so a file that vanished
+                // since the listing leaves no empty entry behind
+                let mut source = match File::open(path) {
+                    Ok(source) => source,
+                    Err(error) => {
+                        warnings.push(format!("Skipped {}: {}", path.display(), error));
+                        continue;
+                    }
+                };
+
+                zip.start_file(name.as_str(), options)
+                    .map_err(|e| format!("Failed to write archive: {}", e))?;
+                total_bytes += io::copy(&mut source, &mut zip)
+                    .map_err(|e| format!("Failed to write archive: {}", e))?;
+                entry_count += 1;
+
+                let _ = app.emit(
+                    "archive-progress",
+                    serde_json::json!({
+                        "archivePath": output_path,
+                        "done": index + 1,
+                        "total": entries.len(),
+                        "entryName": name,
+                        "bytesWritten": total_bytes,
+                    }),
+                );
+            }
+
+            let mut writer = zip
+                .finish()
+                .map_err(|e| format!("Failed to write archive: {}", e))?;
+            io::Write::flush(&mut writer).map_err(|e| format!("Failed to write archive: {}", e))?;
+            writer
+                .get_ref()
+                .sync_all()
+                .map_err(|e| format!("Failed to write archive: {}", e))?;
+            Ok((entry_count, total_bytes))
+        })();
+
+        let (entry_count, total_bytes) = match written {
+            Ok(written) => written,
+            Err(error) => {
+                let _ = fs::remove_file(&part);
+                return Err(error);
+            }
+        };
+        if let Err(error) = fs::rename(&part, &output) {
+            let _ = fs::remove_file(&part);
+            return Err(format!("Failed to write archive: {}", error));
+        }
+
+        Ok(ArchiveResult {
+            archive_path: crate::display_string(&output),
+            entry_count,
+            total_bytes,
+            warnings,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to create archive: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_sidecar_listed_with_its_pdf_goes_in_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let pdf = dir.path().join("a.pdf");
+        let sidecar = dir.path().join("a.metadata.json");
+        fs::write(&pdf, b"%PDF-1.7").unwrap();
+        fs::write(&sidecar, b"{}").unwrap();
+
+        for flatten in [false, true] {
+            for paths in [[&pdf, &sidecar], [&sidecar, &pdf]] {
+                let paths = paths
+                    .iter()
+                    .map(|path| path.to_string_lossy().to_string())
+                    .collect::<Vec<_>>();
+                let mut warnings = Vec::new();
+                let mut names = plan_entries(&paths, true, flatten, &mut warnings)
+                    .into_iter()
+                    .map(|(_, name)| name)
+                    .collect::<Vec<_>>();
+                names.sort();
+                assert_eq!(names, ["a.metadata.json", "a.pdf"], "{:?}", paths);
+                assert!(warnings.is_empty());
+            }
+        }
+    }
+}
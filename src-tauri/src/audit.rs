@@ -0,0 +1,302 @@
+// Consistency checks between library PDFs and their metadata sidecars after
+// files were moved around outside the app, and the repairs for what they find.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use tauri::AppHandle;
+use walkdir::WalkDir;
+
+use crate::sidecar::{self, sidecar_stem};
+use crate::{ArxivImportOptions, ArxivImportResult, LibraryIndex};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnpairedPdf {
+    pub pdf_path: String,
+    // Set when the stem starts with an arXiv id, so the metadata can be
+    // fetched again
+    pub arxiv_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MismatchedSidecar {
+    pub metadata_path: String,
+    pub recorded_pdf_path: String,
+    pub actual_pdf_path: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibraryAudit {
+    // Sidecars with neither a same-stem PDF nor one at their pdf_path
+    pub orphaned_sidecars: Vec<String>,
+    // PDFs named like an import ("<id>_<title>") that have no sidecar
+    pub unpaired_pdfs: Vec<UnpairedPdf>,
+    // Sidecars whose pdf_path names another file than the PDF next to them
+    pub mismatched: Vec<MismatchedSidecar>,
+    // Sidecars that couldn't be read, as "<metadata path>: <error>"
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefetchedPdf {
+    pub pdf_path: String,
+    pub result: Option<ArxivImportResult>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibraryRepair {
+    pub trashed_sidecars: Vec<String>,
+    pub fixed_sidecars: Vec<String>,
+    pub refetched: Vec<RefetchedPdf>,
+    // Paths left alone because they no longer need the repair or it failed,
+    // as "<path>: <reason>"
+    pub errors: Vec<String>,
+}
+
+static ARXIV_STEM_ID: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:[0-9]{4}\.[0-9]{4,5}|[a-z\-]+_[0-9]{7})v[0-9]+(?:_|$)").unwrap()
+});
+static DOI_STEM_ID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^10\.[0-9]{4,9}_[^_]+").unwrap());
+
+// The id at the start of an imported file's stem: arXiv ("2301.12345v2_...",
+// old style "hep-th_9901001v1_..."), bioRxiv or other DOIs ("10.1101_...").
+// The flag is true for arXiv ids.
+pub fn stem_id(stem: &str) -> Option<(String, bool)> {
+    if let Some(found) = ARXIV_STEM_ID.find(stem) {
+        let id = found.as_str().trim_end_matches('_');
+        let id = match id.split_once('_') {
+            Some((archive, number)) => format!("{}/{}", archive, number),
+            None => id.to_string(),
+        };
+        return crate::parse_arxiv_input(&id).map(|(base, _)| (base, true));
+    }
+
+    DOI_STEM_ID
+        .find(stem)
+        .map(|found| (found.as_str().replacen('_', "/", 1), false))
+}
+
+fn has_sidecar(pdf_path: &Path) -> bool {
+    let stem = crate::file_stem_string(pdf_path);
+    let dir = pdf_path.parent().unwrap_or_else(|| Path::new(""));
+    sidecar::metadata_suffixes().any(|suffix| dir.join(format!("{}{}", stem, suffix)).is_file())
+}
+
+fn sibling_pdf(metadata_path: &Path) -> Option<PathBuf> {
+    let stem = sidecar_stem(metadata_path)?;
+    Some(metadata_path.with_file_name(format!("{}.pdf", stem))).filter(|pdf| pdf.is_file())
+}
+
+fn recorded_pdf_path(metadata_path: &Path) -> Result<Option<String>, String> {
+    let fields = sidecar::read(metadata_path)?;
+    Ok(fields
+        .get("pdf_path")
+        .and_then(|value| value.as_str())
+        .map(|value| value.to_string()))
+}
+
+enum SidecarState {
+    Paired,
+    Orphaned,
+    Mismatched(MismatchedSidecar),
+}
+
+// Sidecars without a recorded pdf_path predate it and pair by stem alone
+fn sidecar_state(metadata_path: &Path) -> Result<SidecarState, String> {
+    if !metadata_path.is_file() {
+        return Err("Metadata file does not exist".to_string());
+    }
+    let recorded = recorded_pdf_path(metadata_path)?;
+    match (sibling_pdf(metadata_path), recorded) {
+        (Some(actual), Some(recorded))
+            if !crate::is_same_file(&crate::os_path(Path::new(&recorded)), &actual) =>
+        {
+            Ok(SidecarState::Mismatched(MismatchedSidecar {
+                metadata_path: crate::display_string(metadata_path),
+                recorded_pdf_path: recorded,
+                actual_pdf_path: crate::normalized_path(&actual)
+                    .unwrap_or(actual)
+                    .to_string_lossy()
+                    .to_string(),
+            }))
+        }
+        (Some(_), _) => Ok(SidecarState::Paired),
+        (None, Some(recorded)) if crate::os_path(Path::new(&recorded)).is_file() => {
+            Ok(SidecarState::Paired)
+        }
+        (None, _) => Ok(SidecarState::Orphaned),
+    }
+}
+
+fn unpaired_pdf(pdf_path: &Path) -> Option<UnpairedPdf> {
+    if has_sidecar(pdf_path) {
+        return None;
+    }
+    let (id, is_arxiv) = stem_id(&crate::file_stem_string(pdf_path))?;
+    Some(UnpairedPdf {
+        pdf_path: crate::display_string(pdf_path),
+        arxiv_id: is_arxiv.then_some(id),
+    })
+}
+
+// Pairs the PDFs and sidecars under dir_path (only directly inside it unless
+// recursive) by file stem. Nothing is changed; see repair_library.
+#[tauri::command]
+pub async fn audit_library(dir_path: String, recursive: bool) -> Result<LibraryAudit, String> {
+    let dir = crate::os_path(Path::new(&dir_path));
+    if !dir.is_dir() {
+        return Err(format!("Directory does not exist: {}", dir_path));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut walker = WalkDir::new(crate::walk_root(&dir)).min_depth(1);
+        if !recursive {
+            walker = walker.max_depth(1);
+        }
+
+        let mut audit = LibraryAudit::default();
+        for entry in walker.into_iter().filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let path = entry.path();
+
+            if sidecar_stem(path).is_some() {
+                match sidecar_state(path) {
+                    Ok(SidecarState::Paired) => {}
+                    Ok(SidecarState::Orphaned) => {
+                        audit.orphaned_sidecars.push(crate::display_string(path))
+                    }
+                    Ok(SidecarState::Mismatched(mismatch)) => audit.mismatched.push(mismatch),
+                    Err(error) => {
+                        audit
+                            .errors
+                            .push(format!("{}: {}", crate::display_string(path), error))
+                    }
+                }
+            } else if crate::is_pdf_path(path) {
+                audit.unpaired_pdfs.extend(unpaired_pdf(path));
+            }
+        }
+
+        audit
+    })
+    .await
+    .map_err(|e| format!("Failed to audit library: {}", e))
+}
+
+// Applies the repairs for paths from audit_library, each checked again first
+// so a stale audit can't do damage: trash_sidecars go to the trash if still
+// orphaned and inside a library root, fix_sidecars get pdf_path rewritten to
+// the PDF next to them, and refetch_pdfs with an arXiv id are attached again
+// (mode "attach"), which writes a fresh sidecar and may move the PDF to its
+// canonical name.
+#[tauri::command]
+pub async fn repair_library(
+    app: AppHandle,
+    trash_sidecars: Vec<String>,
+    fix_sidecars: Vec<String>,
+    refetch_pdfs: Option<Vec<String>>,
+) -> Result<LibraryRepair, String> {
+    let mut repair = LibraryRepair::default();
+
+    for metadata_path in trash_sidecars {
+        let path = &crate::os_path(Path::new(&metadata_path));
+        let outcome = match sidecar_state(path) {
+            Ok(SidecarState::Orphaned) => crate::check_inside_library_roots(path, false)
+                .and_then(|_| crate::remove_file_or_trash(path, true)),
+            Ok(_) => Err("no longer orphaned".to_string()),
+            Err(error) => Err(error),
+        };
+        match outcome {
+            Ok(()) => repair.trashed_sidecars.push(metadata_path),
+            Err(error) => repair.errors.push(format!("{}: {}", metadata_path, error)),
+        }
+    }
+
+    for metadata_path in fix_sidecars {
+        let path = &crate::os_path(Path::new(&metadata_path));
+        let outcome = match sidecar_state(path) {
+            Ok(SidecarState::Mismatched(mismatch)) => sidecar::read(path).and_then(|mut fields| {
+                fields.insert(
+                    "pdf_path".to_string(),
+                    serde_json::json!(mismatch.actual_pdf_path),
+                );
+                sidecar::write(path, fields)
+            }),
+            Ok(_) => Err("pdf_path no longer disagrees with the PDF".to_string()),
+            Err(error) => Err(error),
+        };
+        match outcome {
+            Ok(()) => repair.fixed_sidecars.push(metadata_path),
+            Err(error) => repair.errors.push(format!("{}: {}", metadata_path, error)),
+        }
+    }
+
+    // One at a time, spaced out by the arXiv gate like any other import
+    let mut seen = HashSet::new();
+    for pdf_path in refetch_pdfs.unwrap_or_default() {
+        let path = &crate::os_path(Path::new(&pdf_path));
+        let arxiv_id = match path.is_file().then(|| unpaired_pdf(path)).flatten() {
+            Some(UnpairedPdf {
+                arxiv_id: Some(arxiv_id),
+                ..
+            }) => arxiv_id,
+            Some(_) => {
+                repair
+                    .errors
+                    .push(format!("{}: not named after an arXiv id", pdf_path));
+                continue;
+            }
+            None => {
+                repair
+                    .errors
+                    .push(format!("{}: no longer an unpaired PDF", pdf_path));
+                continue;
+            }
+        };
+        if !seen.insert(arxiv_id.clone()) {
+            repair.errors.push(format!(
+                "{}: another PDF of {} came first",
+                pdf_path, arxiv_id
+            ));
+            continue;
+        }
+
+        let target_dir = path.parent().map(crate::display_string).unwrap_or_default();
+        let options = ArxivImportOptions {
+            mode: Some("attach".to_string()),
+            existing_pdf_path: Some(pdf_path.clone()),
+            ..Default::default()
+        };
+        let outcome = crate::import_and_record(
+            &app,
+            arxiv_id,
+            target_dir,
+            "skip".to_string(),
+            options,
+            &LibraryIndex::new(),
+            None,
+        )
+        .await;
+
+        repair.refetched.push(match outcome {
+            Ok(result) => RefetchedPdf {
+                pdf_path,
+                result: Some(result),
+                error: None,
+            },
+            Err(error) => RefetchedPdf {
+                pdf_path,
+                result: None,
+                error: Some(error),
+            },
+        });
+    }
+
+    Ok(repair)
+}
@@ -0,0 +1,335 @@
+use log::{debug, error, warn};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::State;
+use url::Url;
+
+use crate::downloads::DownloadManager;
+use crate::importer::{self, compact_text, non_empty_text};
+use crate::sidecar;
+
+const BIORXIV_API_URL: &str = "https://api.biorxiv.org/details";
+const SERVERS: &[&str] = &["biorxiv", "medrxiv"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BiorxivPaperMetadata {
+    pub doi: String,
+    pub version: u32,
+    // "biorxiv" or "medrxiv"
+    pub server: String,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub summary: String,
+    // Posting date of this version, YYYY-MM-DD
+    pub published: String,
+    pub category: Option<String>,
+    pub license: Option<String>,
+    // Journal DOI once the preprint has been published
+    pub published_doi: Option<String>,
+    pub abs_url: String,
+    pub pdf_url: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BiorxivImportResult {
+    pub status: String,
+    pub reason: Option<String>,
+    pub detail: Option<String>,
+    pub pdf_path: Option<String>,
+    pub pdf_size: Option<u64>,
+    pub metadata_path: Option<String>,
+    pub paper: Option<BiorxivPaperMetadata>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DetailsResponse {
+    #[serde(default)]
+    collection: Vec<DetailsRecord>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DetailsRecord {
+    title: Option<String>,
+    authors: Option<String>,
+    date: Option<String>,
+    version: Option<String>,
+    license: Option<String>,
+    category: Option<String>,
+    #[serde(rename = "abstract")]
+    summary: Option<String>,
+    published: Option<String>,
+}
+
+fn skipped_result(reason: &str, paper: Option<BiorxivPaperMetadata>) -> BiorxivImportResult {
+    BiorxivImportResult {
+        status: "skipped".to_string(),
+        reason: Some(reason.to_string()),
+        paper,
+        ..Default::default()
+    }
+}
+
+// (server if known, DOI, version if given). Accepts content URLs like
+// https://www.biorxiv.org/content/10.1101/2020.01.01.123456v2.full.pdf, DOI
+// links and bare 10.1101/... DOIs; a bare DOI doesn't say which server it
+// belongs to.
+fn parse_biorxiv_input(input: &str) -> Option<(Option<&'static str>, String, Option<u32>)> {
+    let doi_pattern = Regex::new(r"^(10\.1101/(?:\d{4}\.\d{2}\.\d{2}\.)?\d+)(?:v(\d+))?").ok()?;
+    let trimmed = input.trim();
+
+    let (server, rest) = match Url::parse(trimmed) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => {
+            let host = url.host_str()?.to_lowercase();
+            let server = SERVERS.iter().copied().find(|server| {
+                host == format!("{}.org", server) || host.ends_with(&format!(".{}.org", server))
+            });
+            let path = url.path().trim_start_matches('/').to_string();
+            match server {
+                Some(server) => (Some(server), path.strip_prefix("content/")?.to_string()),
+                None if host == "doi.org" || host == "dx.doi.org" => (None, path),
+                None => return None,
+            }
+        }
+        // "doi:10.1101/..." parses as a URL too, so anything that isn't
+        // http(s) is treated as a bare DOI
+        _ => {
+            let rest = match trimmed.get(..4) {
+                Some(prefix) if prefix.eq_ignore_ascii_case("doi:") => trimmed[4..].trim(),
+                _ => trimmed,
+            };
+            (None, rest.to_string())
+        }
+    };
+
+    let captures = doi_pattern.captures(&rest)?;
+    let doi = captures.get(1)?.as_str().to_string();
+    let version = captures
+        .get(2)
+        .and_then(|value| value.as_str().parse::<u32>().ok());
+
+    Some((server, doi, version))
+}
+
+fn paper_from_record(
+    record: DetailsRecord,
+    server: &str,
+    doi: &str,
+    version: u32,
+) -> BiorxivPaperMetadata {
+    let id_with_version = format!("{}v{}", doi, version);
+
+    BiorxivPaperMetadata {
+        doi: doi.to_string(),
+        version,
+        server: server.to_string(),
+        title: record
+            .title
+            .as_deref()
+            .map(compact_text)
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| format!("{} {}", server, id_with_version)),
+        // The API joins authors as "Surname, I.; Surname, J."
+        authors: record
+            .authors
+            .as_deref()
+            .unwrap_or_default()
+            .split(';')
+            .map(compact_text)
+            .filter(|name| !name.is_empty())
+            .collect(),
+        summary: record
+            .summary
+            .as_deref()
+            .map(compact_text)
+            .unwrap_or_default(),
+        published: record.date.unwrap_or_default(),
+        category: non_empty_text(record.category),
+        license: non_empty_text(record.license),
+        published_doi: non_empty_text(record.published).filter(|value| value != "NA"),
+        abs_url: format!("https://www.{}.org/content/{}", server, id_with_version),
+        pdf_url: format!(
+            "https://www.{}.org/content/{}.full.pdf",
+            server, id_with_version
+        ),
+    }
+}
+
+// Every posted version of the preprint on the first server that knows it
+async fn fetch_versions(
+    client: &reqwest::Client,
+    servers: &[&'static str],
+    doi: &str,
+) -> Result<Option<(&'static str, Vec<DetailsRecord>)>, &'static str> {
+    for server in servers {
+        let url = format!("{}/{}/{}", BIORXIV_API_URL, server, doi);
+        debug!("GET {}", url);
+        let response = match client.get(&url).send().await {
+            Ok(response) => response,
+            Err(error) => {
+                warn!("Failed to fetch {} metadata: {:?}", server, error);
+                return Err("network_error");
+            }
+        };
+
+        if !response.status().is_success() {
+            warn!("{} API returned {} for {}", server, response.status(), doi);
+            return Err("network_error");
+        }
+
+        // Unknown DOIs come back as 200 with an empty collection
+        let body = match response.text().await {
+            Ok(body) => body,
+            Err(error) => {
+                warn!("Failed to read {} metadata: {:?}", server, error);
+                return Err("network_error");
+            }
+        };
+        let details = match serde_json::from_str::<DetailsResponse>(&body) {
+            Ok(details) => details,
+            Err(error) => {
+                warn!("Failed to parse {} metadata: {:?}", server, error);
+                continue;
+            }
+        };
+        if !details.collection.is_empty() {
+            return Ok(Some((server, details.collection)));
+        }
+    }
+
+    Ok(None)
+}
+
+#[tauri::command]
+pub async fn import_biorxiv_paper(
+    downloads: State<'_, DownloadManager>,
+    input_url_or_doi: String,
+    target_dir: String,
+    conflict_policy: String,
+) -> Result<BiorxivImportResult, String> {
+    importer::check_conflict_policy(&conflict_policy)?;
+    importer::check_target_dir(&target_dir)?;
+
+    let (server, doi, requested_version) = match parse_biorxiv_input(&input_url_or_doi) {
+        Some(parsed) => parsed,
+        None => return Ok(skipped_result("invalid_link", None)),
+    };
+
+    let target = Path::new(&target_dir);
+    if !importer::ensure_target_dir(target) {
+        return Ok(skipped_result("write_failed", None));
+    }
+
+    let client = match crate::arxiv_client() {
+        Ok(client) => client,
+        Err(error) => {
+            error!("Failed to create reqwest client: {:?}", error);
+            return Ok(skipped_result("network_error", None));
+        }
+    };
+
+    let servers = match server {
+        Some(server) => vec![server],
+        None => SERVERS.to_vec(),
+    };
+    let (server, records) = match fetch_versions(&client, &servers, &doi).await {
+        Ok(Some(found)) => found,
+        Ok(None) => return Ok(skipped_result("paper_not_found", None)),
+        Err(reason) => return Ok(skipped_result(reason, None)),
+    };
+
+    let mut versions = records
+        .into_iter()
+        .filter_map(|record| {
+            let version = record.version.as_deref()?.trim().parse::<u32>().ok()?;
+            Some((version, record))
+        })
+        .collect::<Vec<_>>();
+    versions.sort_by_key(|(version, _)| *version);
+
+    let selected = match requested_version {
+        Some(requested) => versions
+            .into_iter()
+            .find(|(version, _)| *version == requested),
+        None => versions.pop(),
+    };
+    let (version, record) = match selected {
+        Some(selected) => selected,
+        None => return Ok(skipped_result("paper_not_found", None)),
+    };
+
+    let paper = paper_from_record(record, server, &doi, version);
+    let file_stem = importer::paper_file_stem(&format!("{}v{}", doi, version), &paper.title);
+    let pdf_path = target.join(format!("{}.pdf", file_stem));
+    let metadata_path = sidecar::sidecar_for_pdf(&pdf_path, crate::default_sidecar_format());
+
+    if pdf_path.exists() {
+        return Ok(BiorxivImportResult {
+            status: "skipped".to_string(),
+            reason: Some("file_exists".to_string()),
+            pdf_path: Some(pdf_path.to_string_lossy().to_string()),
+            metadata_path: if metadata_path.exists() {
+                Some(metadata_path.to_string_lossy().to_string())
+            } else {
+                None
+            },
+            paper: Some(paper),
+            ..Default::default()
+        });
+    }
+
+    // Staged and renamed into place with the sidecar, as for arXiv
+    let pdf_part = importer::part_path(&pdf_path);
+    let metadata_part = importer::part_path(&metadata_path);
+    importer::remove_stale_part(&pdf_part);
+    importer::remove_stale_part(&metadata_part);
+
+    let job = serde_json::json!({
+        "importer": server,
+        "doi": paper.doi,
+        "kind": "pdf",
+    });
+    let download = importer::queue_pdf_download(
+        &downloads,
+        &paper.pdf_url,
+        &pdf_part,
+        job,
+        None,
+        "pdf_unavailable",
+    );
+    let downloaded = match download.await {
+        Ok(downloaded) => downloaded,
+        Err(failure) => {
+            let mut result = skipped_result(failure.reason, Some(paper));
+            result.detail = failure.detail;
+            return Ok(result);
+        }
+    };
+
+    if let Err(error) = importer::commit_import(
+        Some((&pdf_part, &pdf_path)),
+        &metadata_part,
+        &metadata_path,
+        |part| {
+            importer::write_sidecar(
+                &paper,
+                server,
+                Some(&pdf_path),
+                part,
+                importer::checksum_fields(&downloaded.sha256),
+            )
+        },
+    ) {
+        error!("{}", error);
+        return Ok(skipped_result("write_failed", Some(paper)));
+    }
+
+    Ok(BiorxivImportResult {
+        status: "downloaded".to_string(),
+        pdf_path: Some(pdf_path.to_string_lossy().to_string()),
+        pdf_size: Some(downloaded.size),
+        metadata_path: Some(metadata_path.to_string_lossy().to_string()),
+        paper: Some(paper),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,371 @@
+// SHA-256 checksums recorded in metadata sidecars, the commands that check
+// library PDFs against them, and on-demand hashing for the frontend.
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use walkdir::WalkDir;
+
+use crate::concurrency::{Budget, Concurrency};
+use crate::importer::invalid_argument;
+use crate::sidecar::{self, sidecar_stem};
+
+const HASH_CACHE_FILE: &str = "hash_cache.json";
+// Oldest entries go first once the cache grows past this
+const MAX_HASH_CACHE_ENTRIES: usize = 20_000;
+// Smaller batches finish before a progress bar would show
+const HASH_PROGRESS_MIN_FILES: usize = 5;
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecksumCheck {
+    pub metadata_path: String,
+    pub pdf_path: String,
+    pub matches: bool,
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibraryChecksumReport {
+    pub checked: usize,
+    pub matched: usize,
+    pub mismatches: Vec<ChecksumCheck>,
+    // Sidecars written before checksums were recorded, or without a PDF
+    pub without_checksum: Vec<String>,
+    // Unreadable sidecars and missing PDFs, as "<metadata path>: <error>"
+    pub errors: Vec<String>,
+}
+
+// Ok(None) when the sidecar has no checksum to compare against
+fn check_sidecar(metadata_path: &Path) -> Result<Option<ChecksumCheck>, String> {
+    let sidecar = sidecar::read(metadata_path)?;
+    let expected = match sidecar.get("sha256").and_then(|value| value.as_str()) {
+        Some(expected) => expected.to_lowercase(),
+        None => return Ok(None),
+    };
+
+    // Sidecars from before pdf_path was recorded sit next to "<stem>.pdf"
+    let pdf_path = match sidecar.get("pdf_path").and_then(|value| value.as_str()) {
+        Some(pdf_path) => PathBuf::from(pdf_path),
+        None => {
+            let stem = sidecar_stem(metadata_path).unwrap_or_default();
+            metadata_path.with_file_name(format!("{}.pdf", stem))
+        }
+    };
+    if !crate::os_path(&pdf_path).is_file() {
+        return Err(format!("PDF does not exist: {}", pdf_path.display()));
+    }
+
+    let actual = sha256_file(&crate::os_path(&pdf_path))
+        .map_err(|e| format!("Failed to read PDF for hashing: {}", e))?;
+
+    Ok(Some(ChecksumCheck {
+        metadata_path: crate::display_string(metadata_path),
+        pdf_path: crate::display_string(&pdf_path),
+        matches: actual == expected,
+        expected,
+        actual,
+    }))
+}
+
+#[tauri::command]
+pub async fn verify_pdf_checksum(metadata_path: String) -> Result<ChecksumCheck, String> {
+    // Re-hashes the whole PDF
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = &crate::os_path(Path::new(&metadata_path));
+        if !path.is_file() {
+            return Err(format!("Metadata file does not exist: {}", metadata_path));
+        }
+
+        check_sidecar(path)?
+            .ok_or_else(|| format!("No sha256 recorded in metadata file: {}", metadata_path))
+    })
+    .await
+    .map_err(|e| format!("Failed to verify checksum: {}", e))?
+}
+
+// Re-hashes every PDF with a recorded checksum under the library roots,
+// emitting "checksum-progress" after each sidecar
+#[tauri::command]
+pub async fn verify_library_checksums(
+    app: AppHandle,
+    library_roots: Vec<String>,
+) -> Result<LibraryChecksumReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let sidecars = library_roots
+            .iter()
+            .map(|root| crate::os_path(Path::new(root)))
+            .filter(|root| root.is_dir())
+            .flat_map(|root| {
+                WalkDir::new(crate::walk_root(&root))
+                    .into_iter()
+                    .filter_map(|e| e.ok())
+            })
+            .filter(|entry| entry.file_type().is_file() && sidecar_stem(entry.path()).is_some())
+            .map(|entry| entry.into_path())
+            .collect::<Vec<_>>();
+
+        let mut report = LibraryChecksumReport::default();
+        for (index, metadata_path) in sidecars.iter().enumerate() {
+            match check_sidecar(metadata_path) {
+                Ok(Some(check)) => {
+                    report.checked += 1;
+                    if check.matches {
+                        report.matched += 1;
+                    } else {
+                        report.mismatches.push(check);
+                    }
+                }
+                Ok(None) => report
+                    .without_checksum
+                    .push(metadata_path.to_string_lossy().to_string()),
+                Err(error) => report
+                    .errors
+                    .push(format!("{}: {}", metadata_path.display(), error)),
+            }
+
+            let _ = app.emit(
+                "checksum-progress",
+                serde_json::json!({
+                    "done": index + 1,
+                    "total": sidecars.len(),
+                    "metadataPath": metadata_path.to_string_lossy().to_string(),
+                }),
+            );
+        }
+
+        report
+    })
+    .await
+    .map_err(|e| format!("Failed to verify checksums: {}", e))
+}
+
+fn blake3_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileHash {
+    pub path: String,
+    pub hash: Option<String>,
+    pub bytes: Option<u64>,
+    // Set instead of hash when the file couldn't be read
+    pub error: Option<String>,
+    // As in PdfFile. Equal hashes with equal ids are links to one file, which
+    // take no extra space, rather than copies
+    pub file_id: Option<String>,
+}
+
+// A hash stays valid while the file keeps its size and mtime
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedHash {
+    size: u64,
+    modified_ns: u64,
+    hash: String,
+    // Unix seconds
+    cached_at: u64,
+}
+
+// Keyed by "<algorithm>:<path>"
+type HashCache = HashMap<String, CachedHash>;
+
+fn hash_cache_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(HASH_CACHE_FILE))
+        .map_err(|e| format!("Failed to resolve data directory: {}", e))
+}
+
+// A missing or unreadable cache just means everything gets hashed again
+fn load_hash_cache(path: &Path) -> HashCache {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_hash_cache(path: &Path, mut cache: HashCache) -> Result<(), String> {
+    if cache.len() > MAX_HASH_CACHE_ENTRIES {
+        let mut by_age = cache
+            .iter()
+            .map(|(key, entry)| (entry.cached_at, key.clone()))
+            .collect::<Vec<_>>();
+        by_age.sort();
+        for (_, key) in by_age.iter().take(cache.len() - MAX_HASH_CACHE_ENTRIES) {
+            cache.remove(key);
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create data directory: {}", e))?;
+    }
+    let text = serde_json::to_string(&cache)
+        .map_err(|e| format!("Failed to serialize hash cache: {}", e))?;
+    crate::atomic_write(path, text.as_bytes())
+        .map_err(|e| format!("Failed to write hash cache: {}", e))
+}
+
+// The result, and whether the file was hashed rather than found in the cache
+fn hash_one(path: &str, algorithm: &str, cache: &Mutex<HashCache>) -> (FileHash, bool) {
+    let mut result = FileHash {
+        path: path.to_string(),
+        hash: None,
+        bytes: None,
+        error: None,
+        file_id: None,
+    };
+
+    let os_path = crate::os_path(Path::new(path));
+    let metadata = match fs::metadata(&os_path) {
+        Ok(metadata) if metadata.is_file() => metadata,
+        Ok(_) => {
+            result.error = Some(format!("Not a file: {}", path));
+            return (result, false);
+        }
+        Err(error) => {
+            result.error = Some(format!("Failed to read file: {}", error));
+            return (result, false);
+        }
+    };
+    let size = metadata.len();
+    let modified_ns = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or(0);
+    result.bytes = Some(size);
+    result.file_id = crate::file_id(&os_path, &metadata);
+
+    let key = format!("{}:{}", algorithm, path);
+    if let Some(cached) = cache.lock().unwrap().get(&key) {
+        if cached.size == size && cached.modified_ns == modified_ns {
+            result.hash = Some(cached.hash.clone());
+            return (result, false);
+        }
+    }
+
+    let hashed = match algorithm {
+        "blake3" => blake3_file(&os_path),
+        _ => sha256_file(&os_path),
+    };
+    match hashed {
+        Ok(hash) => {
+            cache.lock().unwrap().insert(
+                key,
+                CachedHash {
+                    size,
+                    modified_ns,
+                    hash: hash.clone(),
+                    cached_at: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or(0),
+                },
+            );
+            result.hash = Some(hash);
+            (result, true)
+        }
+        Err(error) => {
+            result.error = Some(format!("Failed to hash file: {}", error));
+            (result, false)
+        }
+    }
+}
+
+// Hashes with "sha256" or "blake3" on as many blocking threads as the local
+// concurrency budget has free. Results come back in input order, with
+// per-file errors instead of failing the batch; unchanged files are answered
+// from the cache in the app data dir. Batches of more than
+// HASH_PROGRESS_MIN_FILES emit "hash-progress" per file.
+#[tauri::command]
+pub async fn hash_files(
+    app: AppHandle,
+    paths: Vec<String>,
+    algorithm: String,
+) -> Result<Vec<FileHash>, String> {
+    let algorithm = algorithm.trim().to_lowercase();
+    if !matches!(algorithm.as_str(), "sha256" | "blake3") {
+        return Err(invalid_argument(
+            "invalid_algorithm",
+            format!("Unsupported hash algorithm: {}", algorithm),
+        ));
+    }
+    let cache_path = hash_cache_path(&app)?;
+    let permits = app
+        .state::<Concurrency>()
+        .acquire_up_to(Budget::Local, paths.len())
+        .await;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let cache = Mutex::new(load_hash_cache(&cache_path));
+        // Files actually hashed this time; without any the cache is unchanged
+        let fresh = AtomicUsize::new(0);
+        let next = AtomicUsize::new(0);
+        let done = AtomicUsize::new(0);
+        let results = Mutex::new(Vec::with_capacity(paths.len()));
+
+        std::thread::scope(|scope| {
+            for _ in 0..permits.num_permits().min(paths.len()) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(path) = paths.get(index) else {
+                        break;
+                    };
+                    let hashed = hash_one(path, &algorithm, &cache);
+                    if hashed.1 {
+                        fresh.fetch_add(1, Ordering::SeqCst);
+                    }
+                    results.lock().unwrap().push((index, hashed.0));
+
+                    let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+                    if paths.len() > HASH_PROGRESS_MIN_FILES {
+                        let _ = app.emit(
+                            "hash-progress",
+                            serde_json::json!({
+                                "done": done,
+                                "total": paths.len(),
+                                "path": path,
+                            }),
+                        );
+                    }
+                });
+            }
+        });
+
+        if fresh.load(Ordering::SeqCst) > 0 {
+            if let Err(error) = save_hash_cache(&cache_path, cache.into_inner().unwrap()) {
+                warn!("{}", error);
+            }
+        }
+
+        let mut results = results.into_inner().unwrap();
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, hashed)| hashed).collect()
+    })
+    .await
+    .map_err(|e| format!("Failed to hash files: {}", e))
+}
@@ -0,0 +1,200 @@
+// Shared budgets for work that fans out, so batch commands running at the
+// same time split the machine between them instead of each taking its own
+// share. "local" covers disk and CPU work (hashing, metadata reads),
+// "network" the imports that fetch from remote services.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::importer::invalid_argument;
+
+const MAX_CONCURRENCY: usize = 64;
+// Used when the CPU count can't be determined
+const FALLBACK_LOCAL_CONCURRENCY: usize = 4;
+// Remote services rate-limit well before a desktop runs out of sockets
+const DEFAULT_NETWORK_CONCURRENCY: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Budget {
+    Local,
+    Network,
+}
+
+impl Budget {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "local" => Ok(Self::Local),
+            "network" => Ok(Self::Network),
+            _ => Err(invalid_argument(
+                "invalid_input",
+                format!("Unknown concurrency budget: {}", value),
+            )),
+        }
+    }
+}
+
+// A new limit resizes the semaphore, so work already running counts against
+// it as well; see Pool::resize
+struct Pool {
+    limit: usize,
+    semaphore: Arc<Semaphore>,
+}
+
+impl Pool {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            semaphore: Arc::new(Semaphore::new(limit)),
+        }
+    }
+
+    // Growing adds permits right away. Shrinking takes the free ones out and
+    // waits for the rest to come back from the work holding them; the wait
+    // is queued ahead of later acquires, so nothing new starts above the new
+    // limit.
+    fn resize(&mut self, limit: usize) {
+        if limit > self.limit {
+            self.semaphore.add_permits(limit - self.limit);
+        } else if limit < self.limit {
+            let excess = self.limit - limit;
+            let outstanding = excess - self.semaphore.forget_permits(excess);
+            if outstanding > 0 {
+                let semaphore = self.semaphore.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Ok(permits) = semaphore.acquire_many_owned(outstanding as u32).await {
+                        permits.forget();
+                    }
+                });
+            }
+        }
+        self.limit = limit;
+    }
+}
+
+// Managed as app state; see Concurrency::acquire
+pub struct Concurrency {
+    local: Mutex<Pool>,
+    network: Mutex<Pool>,
+}
+
+impl Default for Concurrency {
+    fn default() -> Self {
+        let cpus = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(FALLBACK_LOCAL_CONCURRENCY);
+        Self {
+            local: Mutex::new(Pool::new(cpus.min(MAX_CONCURRENCY))),
+            network: Mutex::new(Pool::new(DEFAULT_NETWORK_CONCURRENCY)),
+        }
+    }
+}
+
+impl Concurrency {
+    fn pool(&self, budget: Budget) -> &Mutex<Pool> {
+        match budget {
+            Budget::Local => &self.local,
+            Budget::Network => &self.network,
+        }
+    }
+
+    fn semaphore(&self, budget: Budget) -> Arc<Semaphore> {
+        self.pool(budget).lock().unwrap().semaphore.clone()
+    }
+
+    // One slot of the budget, waiting until one is free. The slot is given
+    // back when the permit is dropped.
+    pub async fn acquire(&self, budget: Budget) -> OwnedSemaphorePermit {
+        self.semaphore(budget)
+            .acquire_owned()
+            .await
+            .expect("concurrency semaphores are never closed")
+    }
+
+    // Between 1 and `wanted` slots: waits for the first, then takes whatever
+    // else is free right now. num_permits() on the result is how many workers
+    // the caller may run.
+    pub async fn acquire_up_to(&self, budget: Budget, wanted: usize) -> OwnedSemaphorePermit {
+        let semaphore = self.semaphore(budget);
+        let mut permits = semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("concurrency semaphores are never closed");
+        while permits.num_permits() < wanted {
+            match semaphore.clone().try_acquire_owned() {
+                Ok(permit) => permits.merge(permit),
+                Err(_) => break,
+            }
+        }
+        permits
+    }
+
+    fn limits(&self) -> ConcurrencyLimits {
+        ConcurrencyLimits {
+            local: self.local.lock().unwrap().limit,
+            network: self.network.lock().unwrap().limit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConcurrencyLimits {
+    pub local: usize,
+    pub network: usize,
+}
+
+#[tauri::command]
+pub fn get_max_concurrency(concurrency: State<'_, Concurrency>) -> ConcurrencyLimits {
+    concurrency.limits()
+}
+
+// Sets how many operations of one budget ("local", the default, or
+// "network") may run at once across all commands. Work already running
+// counts against the new limit: after lowering it, nothing else starts until
+// enough of that work is done.
+#[tauri::command]
+pub fn set_max_concurrency(
+    concurrency: State<'_, Concurrency>,
+    n: usize,
+    kind: Option<String>,
+) -> Result<ConcurrencyLimits, String> {
+    let budget = Budget::parse(kind.as_deref().unwrap_or("local"))?;
+    if !(1..=MAX_CONCURRENCY).contains(&n) {
+        return Err(invalid_argument(
+            "invalid_input",
+            format!("Concurrency must be between 1 and {}", MAX_CONCURRENCY),
+        ));
+    }
+
+    concurrency.pool(budget).lock().unwrap().resize(n);
+    Ok(concurrency.limits())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn lowering_the_limit_waits_for_running_work() {
+        let mut pool = Pool::new(3);
+        let running = pool.semaphore.clone().acquire_many_owned(2).await.unwrap();
+
+        pool.resize(1);
+        assert_eq!(pool.semaphore.available_permits(), 0);
+        drop(running);
+        for _ in 0..100 {
+            if pool.semaphore.available_permits() == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(pool.semaphore.available_permits(), 1);
+
+        pool.resize(4);
+        assert_eq!(pool.semaphore.available_permits(), 4);
+        assert_eq!(pool.limit, 4);
+    }
+}
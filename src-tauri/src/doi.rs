@@ -0,0 +1,475 @@
+use log::{debug, error, warn};
+use regex::Regex;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::State;
+use url::Url;
+
+use crate::downloads::DownloadManager;
+use crate::importer::{self, compact_text, non_empty_text};
+use crate::sidecar;
+
+const CROSSREF_WORKS_URL: &str = "https://api.crossref.org/works";
+const UNPAYWALL_URL: &str = "https://api.unpaywall.org/v2";
+const DOI_HOSTS: &[&str] = &["doi.org", "dx.doi.org", "www.doi.org"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoiPaperMetadata {
+    pub doi: String,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub summary: String,
+    pub year: Option<i32>,
+    pub journal: Option<String>,
+    pub volume: Option<String>,
+    pub issue: Option<String>,
+    pub pages: Option<String>,
+    pub publisher: Option<String>,
+    pub abs_url: String,
+    // Open-access copy found through Unpaywall, if any
+    pub pdf_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DoiImportResult {
+    // "downloaded", "metadata_only" (cataloged without a PDF) or "skipped"
+    pub status: String,
+    pub reason: Option<String>,
+    pub detail: Option<String>,
+    pub pdf_path: Option<String>,
+    pub pdf_size: Option<u64>,
+    pub metadata_path: Option<String>,
+    // Why an open-access PDF that Unpaywall listed could not be fetched
+    pub warnings: Vec<String>,
+    pub paper: Option<DoiPaperMetadata>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrossrefResponse {
+    message: CrossrefWork,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrossrefWork {
+    #[serde(default)]
+    title: Vec<String>,
+    #[serde(default)]
+    author: Vec<CrossrefAuthor>,
+    issued: Option<CrossrefDate>,
+    #[serde(rename = "container-title", default)]
+    container_title: Vec<String>,
+    volume: Option<String>,
+    issue: Option<String>,
+    page: Option<String>,
+    publisher: Option<String>,
+    #[serde(rename = "abstract")]
+    summary: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrossrefAuthor {
+    given: Option<String>,
+    family: Option<String>,
+    // Organisations and consortia have a single name instead
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrossrefDate {
+    #[serde(rename = "date-parts", default)]
+    date_parts: Vec<Vec<Option<i64>>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UnpaywallResponse {
+    best_oa_location: Option<UnpaywallLocation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UnpaywallLocation {
+    url_for_pdf: Option<String>,
+}
+
+fn skipped_result(reason: &str, paper: Option<DoiPaperMetadata>) -> DoiImportResult {
+    DoiImportResult {
+        status: "skipped".to_string(),
+        reason: Some(reason.to_string()),
+        paper,
+        ..Default::default()
+    }
+}
+
+// DOIs are case-insensitive, so the lowercase form is what files and sidecars
+// use. Accepts "10.x/y", "doi:10.x/y" and doi.org links.
+fn normalize_doi(input: &str) -> Option<String> {
+    let trimmed = input.trim();
+
+    // "doi:10.x/y" parses as a URL as well, hence the scheme check
+    let doi = match Url::parse(trimmed) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => {
+            let host = url.host_str()?.to_lowercase();
+            if !DOI_HOSTS.contains(&host.as_str()) {
+                return None;
+            }
+            url.path().trim_start_matches('/').to_string()
+        }
+        _ => match trimmed.get(..4) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("doi:") => trimmed[4..].trim().to_string(),
+            _ => trimmed.to_string(),
+        },
+    };
+
+    let doi = doi.to_lowercase();
+    let (prefix, suffix) = doi.split_once('/')?;
+    if !prefix.starts_with("10.") || suffix.is_empty() || doi.contains(char::is_whitespace) {
+        return None;
+    }
+
+    Some(doi)
+}
+
+// Keeps the DOI's slashes as path separators and percent-encodes everything
+// else that isn't URL-safe
+fn doi_url(base: &str, doi: &str) -> Option<Url> {
+    let mut url = Url::parse(base).ok()?;
+    url.path_segments_mut().ok()?.extend(doi.split('/'));
+    Some(url)
+}
+
+fn crossref_author_name(author: CrossrefAuthor) -> Option<String> {
+    let full_name = match (author.given, author.family) {
+        (Some(given), Some(family)) => format!("{} {}", given, family),
+        (None, Some(family)) => family,
+        (given, None) => author.name.or(given)?,
+    };
+    non_empty_text(Some(full_name))
+}
+
+fn paper_from_work(work: CrossrefWork, doi: &str) -> DoiPaperMetadata {
+    // Crossref abstracts are JATS fragments ("<jats:p>...</jats:p>")
+    let summary = work
+        .summary
+        .as_deref()
+        .map(|summary| match Regex::new(r"<[^>]+>") {
+            Ok(tags) => compact_text(&tags.replace_all(summary, " ")),
+            Err(_) => compact_text(summary),
+        })
+        .unwrap_or_default();
+
+    DoiPaperMetadata {
+        doi: doi.to_string(),
+        title: work
+            .title
+            .first()
+            .map(|title| compact_text(title))
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| format!("DOI {}", doi)),
+        authors: work
+            .author
+            .into_iter()
+            .filter_map(crossref_author_name)
+            .collect(),
+        summary,
+        year: work
+            .issued
+            .and_then(|issued| issued.date_parts.first()?.first().copied().flatten())
+            .map(|year| year as i32),
+        journal: non_empty_text(work.container_title.into_iter().next()),
+        volume: non_empty_text(work.volume),
+        issue: non_empty_text(work.issue),
+        pages: non_empty_text(work.page),
+        publisher: non_empty_text(work.publisher),
+        abs_url: format!("https://doi.org/{}", doi),
+        pdf_url: None,
+    }
+}
+
+// Errors come back as the skip reason reported to the frontend
+async fn fetch_crossref_work(
+    client: &Client,
+    doi: &str,
+    email: Option<&str>,
+) -> Result<CrossrefWork, &'static str> {
+    fetch_crossref_work_at(client, CROSSREF_WORKS_URL, doi, email).await
+}
+
+async fn fetch_crossref_work_at(
+    client: &Client,
+    works_url: &str,
+    doi: &str,
+    email: Option<&str>,
+) -> Result<CrossrefWork, &'static str> {
+    let mut url = doi_url(works_url, doi).ok_or("invalid_link")?;
+    // Identified requests go to Crossref's "polite" pool
+    if let Some(email) = email {
+        url.query_pairs_mut().append_pair("mailto", email);
+    }
+
+    // The URL carries the contact email, which doesn't belong in logs
+    debug!("Querying Crossref for {}", doi);
+    let response = match client.get(url).send().await {
+        Ok(response) => response,
+        Err(error) => {
+            warn!("Failed to fetch Crossref metadata: {:?}", error);
+            return Err("network_error");
+        }
+    };
+
+    if response.status().as_u16() == 404 {
+        return Err("paper_not_found");
+    }
+    if !response.status().is_success() {
+        warn!("Crossref returned {} for {}", response.status(), doi);
+        return Err("network_error");
+    }
+
+    let body = match response.text().await {
+        Ok(body) => body,
+        Err(error) => {
+            warn!("Failed to read Crossref metadata: {:?}", error);
+            return Err("network_error");
+        }
+    };
+
+    match serde_json::from_str::<CrossrefResponse>(&body) {
+        Ok(parsed) => Ok(parsed.message),
+        Err(error) => {
+            warn!("Failed to parse Crossref metadata: {:?}", error);
+            Err("paper_not_found")
+        }
+    }
+}
+
+// Best open-access PDF location, if Unpaywall knows one. Any failure just means
+// the paper gets cataloged without a PDF.
+async fn find_open_access_pdf(client: &Client, doi: &str, email: &str) -> Option<String> {
+    let mut url = doi_url(UNPAYWALL_URL, doi)?;
+    url.query_pairs_mut().append_pair("email", email);
+
+    debug!("Querying Unpaywall for {}", doi);
+    let response = match client.get(url).send().await {
+        Ok(response) => response,
+        Err(error) => {
+            warn!("Failed to query Unpaywall: {:?}", error);
+            return None;
+        }
+    };
+    if !response.status().is_success() {
+        return None;
+    }
+
+    let body = response.text().await.ok()?;
+    serde_json::from_str::<UnpaywallResponse>(&body)
+        .ok()?
+        .best_oa_location?
+        .url_for_pdf
+        .and_then(|url| non_empty_text(Some(url)))
+}
+
+#[tauri::command]
+pub async fn import_doi(
+    downloads: State<'_, DownloadManager>,
+    doi_or_url: String,
+    target_dir: String,
+    conflict_policy: String,
+    email_for_unpaywall: Option<String>,
+) -> Result<DoiImportResult, String> {
+    importer::check_conflict_policy(&conflict_policy)?;
+    importer::check_target_dir(&target_dir)?;
+
+    let doi = match normalize_doi(&doi_or_url) {
+        Some(doi) => doi,
+        None => return Ok(skipped_result("invalid_link", None)),
+    };
+
+    let target = Path::new(&target_dir);
+    if !importer::ensure_target_dir(target) {
+        return Ok(skipped_result("write_failed", None));
+    }
+
+    let client = match crate::arxiv_client() {
+        Ok(client) => client,
+        Err(error) => {
+            error!("Failed to create reqwest client: {:?}", error);
+            return Ok(skipped_result("network_error", None));
+        }
+    };
+
+    let email = email_for_unpaywall
+        .as_deref()
+        .map(str::trim)
+        .filter(|email| !email.is_empty());
+
+    let work = match fetch_crossref_work(&client, &doi, email).await {
+        Ok(work) => work,
+        Err(reason) => return Ok(skipped_result(reason, None)),
+    };
+    let mut paper = paper_from_work(work, &doi);
+
+    let file_stem = importer::paper_file_stem(&doi, &paper.title);
+    let pdf_path = target.join(format!("{}.pdf", file_stem));
+    let metadata_path = sidecar::sidecar_for_pdf(&pdf_path, crate::default_sidecar_format());
+
+    if pdf_path.exists() || metadata_path.exists() {
+        return Ok(DoiImportResult {
+            status: "skipped".to_string(),
+            reason: Some("file_exists".to_string()),
+            pdf_path: if pdf_path.exists() {
+                Some(pdf_path.to_string_lossy().to_string())
+            } else {
+                None
+            },
+            metadata_path: if metadata_path.exists() {
+                Some(metadata_path.to_string_lossy().to_string())
+            } else {
+                None
+            },
+            paper: Some(paper),
+            ..Default::default()
+        });
+    }
+
+    // Unpaywall requires an email on every request
+    paper.pdf_url = match email {
+        Some(email) => find_open_access_pdf(&client, &doi, email).await,
+        None => None,
+    };
+
+    // Staged and renamed into place with the sidecar, as for arXiv
+    let pdf_part = importer::part_path(&pdf_path);
+    let metadata_part = importer::part_path(&metadata_path);
+    importer::remove_stale_part(&pdf_part);
+    importer::remove_stale_part(&metadata_part);
+
+    let mut warnings = Vec::new();
+    let downloaded = match paper.pdf_url.as_deref() {
+        Some(pdf_url) => {
+            let job = serde_json::json!({ "importer": "doi", "doi": doi, "kind": "pdf" });
+            let download = importer::queue_pdf_download(
+                &downloads,
+                pdf_url,
+                &pdf_part,
+                job,
+                None,
+                "pdf_unavailable",
+            );
+            match download.await {
+                Ok(downloaded) => Some(downloaded),
+                Err(failure) => {
+                    warnings.push(failure.reason.to_string());
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    let written_pdf = downloaded.as_ref().map(|_| pdf_path.as_path());
+    let extra = downloaded
+        .as_ref()
+        .map(|downloaded| importer::checksum_fields(&downloaded.sha256))
+        .unwrap_or_default();
+    let staged_pdf = written_pdf.map(|pdf_path| (pdf_part.as_path(), pdf_path));
+    if let Err(error) =
+        importer::commit_import(staged_pdf, &metadata_part, &metadata_path, |part| {
+            importer::write_sidecar(&paper, "doi", written_pdf, part, extra)
+        })
+    {
+        error!("{}", error);
+        return Ok(skipped_result("write_failed", Some(paper)));
+    }
+
+    Ok(DoiImportResult {
+        status: if downloaded.is_some() {
+            "downloaded".to_string()
+        } else {
+            "metadata_only".to_string()
+        },
+        pdf_path: written_pdf.map(|path| path.to_string_lossy().to_string()),
+        pdf_size: downloaded.as_ref().map(|downloaded| downloaded.size),
+        metadata_path: Some(metadata_path.to_string_lossy().to_string()),
+        warnings,
+        paper: Some(paper),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::testing;
+
+    #[test]
+    fn normalize_doi_accepts_bare_prefixed_and_linked_dois() {
+        let accepted = [
+            ("10.1038/nphys1170", "10.1038/nphys1170"),
+            ("  10.1038/NPHYS1170 ", "10.1038/nphys1170"),
+            ("doi:10.1145/3290605.3300233", "10.1145/3290605.3300233"),
+            ("DOI: 10.1145/3290605.3300233", "10.1145/3290605.3300233"),
+            ("https://doi.org/10.1000/182", "10.1000/182"),
+            ("http://dx.doi.org/10.1000/182", "10.1000/182"),
+            (
+                "https://www.doi.org/10.1002/(SICI)1097-4571",
+                "10.1002/(sici)1097-4571",
+            ),
+        ];
+        for (input, doi) in accepted {
+            assert_eq!(normalize_doi(input).as_deref(), Some(doi), "{}", input);
+        }
+
+        let rejected = [
+            "",
+            "nphys1170",
+            "11.1038/nphys1170",
+            "10.1038/",
+            "10.1038/has space",
+            "https://example.com/10.1038/nphys1170",
+            "ftp://doi.org/10.1000/182",
+        ];
+        for input in rejected {
+            assert_eq!(normalize_doi(input), None, "{}", input);
+        }
+    }
+
+    #[tokio::test]
+    async fn crossref_404_is_paper_not_found() {
+        let (base, requests) = testing::serve(vec![
+            (404, "text/plain", "Resource not found.".to_string()),
+            (503, "text/plain", "Service unavailable".to_string()),
+            (
+                200,
+                "application/json",
+                serde_json::json!({
+                    "message": {
+                        "title": ["Ultrafast  electron\ndynamics"],
+                        "author": [{"given": "Ada", "family": "Lovelace"}],
+                        "issued": {"date-parts": [[2008, 12]]},
+                    }
+                })
+                .to_string(),
+            ),
+        ]);
+        let works_url = format!("{}/works", base);
+        let client = Client::new();
+
+        let missing = fetch_crossref_work_at(&client, &works_url, "10.1000/missing", None).await;
+        assert_eq!(missing.err(), Some("paper_not_found"));
+        assert_eq!(
+            requests.recv().unwrap(),
+            "GET /works/10.1000/missing HTTP/1.1"
+        );
+
+        let down = fetch_crossref_work_at(&client, &works_url, "10.1000/182", None).await;
+        assert_eq!(down.err(), Some("network_error"));
+        requests.recv().unwrap();
+
+        let work = fetch_crossref_work_at(&client, &works_url, "10.1000/182", None)
+            .await
+            .unwrap();
+        let paper = paper_from_work(work, "10.1000/182");
+        assert_eq!(paper.title, "Ultrafast electron dynamics");
+        assert_eq!(paper.authors, vec!["Ada Lovelace".to_string()]);
+        assert_eq!(paper.year, Some(2008));
+    }
+}
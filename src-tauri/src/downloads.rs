@@ -0,0 +1,718 @@
+// One queue for the HTTP downloads of every importer. Jobs run in FIFO order
+// (priority jobs go to the front) on a small worker pool, requests to the same
+// host are spaced out, and each state change is emitted as "download-state" so
+// the frontend can show them all in one list. A paused job keeps its ".part"
+// file and asks for the rest with a Range request once resumed.
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::{oneshot, Notify};
+use url::Url;
+
+use crate::importer::{self, invalid_argument};
+
+const WORKER_COUNT: usize = 3;
+// Gap between requests to the same host; arXiv hosts go through the stricter
+// importer-wide gate instead (see wait_for_arxiv_slot)
+const HOST_REQUEST_INTERVAL: Duration = Duration::from_millis(500);
+// Finished jobs kept for list_downloads before the oldest are dropped
+const MAX_FINISHED_JOBS: usize = 200;
+// Progress events are sent at most this often per job
+const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadState {
+    Queued,
+    Active,
+    Paused,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl DownloadState {
+    fn is_finished(self) -> bool {
+        matches!(self, Self::Done | Self::Failed | Self::Cancelled)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadInfo {
+    pub id: String,
+    pub url: String,
+    pub destination: String,
+    // Who asked for the file and why, e.g. the importer and the paper id
+    pub provenance: Option<serde_json::Value>,
+    pub state: DownloadState,
+    pub bytes_downloaded: u64,
+    // From Content-Length, when the server sent one
+    pub total_bytes: Option<u64>,
+    pub http_status: Option<u16>,
+    // Where the request ended up after redirects
+    pub resolved_url: Option<String>,
+    pub content_type: Option<String>,
+    // Raw Content-Disposition header, for callers that name the file after it
+    pub content_disposition: Option<String>,
+    // With state failed: "network_error", "too_many_redirects", "http_error",
+    // "not_a_pdf" (see DownloadRequest::expect_pdf) or "write_failed"
+    pub reason: Option<String>,
+    pub error: Option<String>,
+    // SHA-256 of the body, hashed chunk by chunk as it arrived; set once done
+    pub sha256: Option<String>,
+}
+
+pub struct DownloadRequest {
+    pub url: String,
+    // Written as "<destination>.part" and renamed once complete
+    pub destination: PathBuf,
+    pub provenance: Option<serde_json::Value>,
+    // Lets the caller stop the job as well, e.g. for cancel_import
+    pub cancel: Option<Arc<AtomicBool>>,
+    // Fail with "not_a_pdf" before writing anything when the server answers
+    // with HTML or a body that doesn't start with %PDF-
+    pub expect_pdf: bool,
+}
+
+struct Job {
+    info: DownloadInfo,
+    cancel: Arc<AtomicBool>,
+    pause: Arc<AtomicBool>,
+    // Paused mid-transfer, so its ".part" holds the start of the body
+    resumable: bool,
+    expect_pdf: bool,
+    waiters: Vec<oneshot::Sender<DownloadInfo>>,
+}
+
+#[derive(Default)]
+struct Jobs {
+    // Enqueue order, for list_downloads
+    order: Vec<String>,
+    by_id: HashMap<String, Job>,
+    queue: VecDeque<String>,
+}
+
+struct Failure {
+    reason: &'static str,
+    error: String,
+}
+
+impl Failure {
+    fn new(reason: &'static str, error: impl ToString) -> Self {
+        Self {
+            reason,
+            error: error.to_string(),
+        }
+    }
+}
+
+// How a running transfer is told to stop
+struct Signals {
+    cancel: Arc<AtomicBool>,
+    pause: Arc<AtomicBool>,
+}
+
+impl Signals {
+    fn check(&self) -> Result<(), Failure> {
+        if self.cancel.load(Ordering::SeqCst) {
+            Err(Failure::new("cancelled", "Cancelled"))
+        } else if self.pause.load(Ordering::SeqCst) {
+            Err(Failure::new("paused", "Paused"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+struct Shared {
+    app: AppHandle,
+    jobs: Mutex<Jobs>,
+    wake: Notify,
+    // Earliest time the next request to each host may start
+    host_slots: Mutex<HashMap<String, Instant>>,
+}
+
+// Cheap to clone; every clone drives the same queue
+#[derive(Clone)]
+pub struct DownloadManager(Arc<Shared>);
+
+impl DownloadManager {
+    // Spawns the workers; call once from setup and manage the result
+    pub fn start(app: AppHandle) -> Self {
+        let manager = Self(Arc::new(Shared {
+            app,
+            jobs: Mutex::new(Jobs::default()),
+            wake: Notify::new(),
+            host_slots: Mutex::new(HashMap::new()),
+        }));
+
+        for _ in 0..WORKER_COUNT {
+            let worker = manager.clone();
+            tauri::async_runtime::spawn(async move { worker.work().await });
+        }
+
+        manager
+    }
+
+    pub fn enqueue(
+        &self,
+        request: DownloadRequest,
+        priority: bool,
+    ) -> (String, oneshot::Receiver<DownloadInfo>) {
+        let id = uuid::Uuid::new_v4().to_string();
+        let (sender, receiver) = oneshot::channel();
+        let info = DownloadInfo {
+            id: id.clone(),
+            url: request.url,
+            destination: request.destination.to_string_lossy().to_string(),
+            provenance: request.provenance,
+            state: DownloadState::Queued,
+            bytes_downloaded: 0,
+            total_bytes: None,
+            http_status: None,
+            resolved_url: None,
+            content_type: None,
+            content_disposition: None,
+            reason: None,
+            error: None,
+            sha256: None,
+        };
+
+        {
+            let mut jobs = self.0.jobs.lock().unwrap();
+            jobs.order.push(id.clone());
+            if priority {
+                jobs.queue.push_front(id.clone());
+            } else {
+                jobs.queue.push_back(id.clone());
+            }
+            jobs.by_id.insert(
+                id.clone(),
+                Job {
+                    info: info.clone(),
+                    cancel: request.cancel.unwrap_or_default(),
+                    pause: Arc::default(),
+                    resumable: false,
+                    expect_pdf: request.expect_pdf,
+                    waiters: vec![sender],
+                },
+            );
+        }
+
+        self.emit(&info);
+        self.0.wake.notify_one();
+        (id, receiver)
+    }
+
+    // Enqueues and waits for the job to finish, however it ends
+    pub async fn download(&self, request: DownloadRequest) -> DownloadInfo {
+        let url = request.url.clone();
+        let destination = request.destination.to_string_lossy().to_string();
+        let (id, receiver) = self.enqueue(request, false);
+
+        receiver.await.unwrap_or_else(|_| DownloadInfo {
+            id,
+            url,
+            destination,
+            provenance: None,
+            state: DownloadState::Failed,
+            bytes_downloaded: 0,
+            total_bytes: None,
+            http_status: None,
+            resolved_url: None,
+            content_type: None,
+            content_disposition: None,
+            reason: Some("network_error".to_string()),
+            error: Some("Download manager stopped".to_string()),
+            sha256: None,
+        })
+    }
+
+    // Queued and paused jobs are dropped right away; active ones stop at the
+    // next chunk. false if the job is unknown or already finished.
+    pub fn cancel(&self, id: &str) -> bool {
+        let waiting = {
+            let mut jobs = self.0.jobs.lock().unwrap();
+            let Some(job) = jobs.by_id.get(id) else {
+                return false;
+            };
+            match job.info.state {
+                DownloadState::Queued => {
+                    jobs.queue.retain(|queued| queued != id);
+                    None
+                }
+                DownloadState::Paused => {
+                    Some(importer::part_path(Path::new(&job.info.destination)))
+                }
+                DownloadState::Active => {
+                    job.cancel.store(true, Ordering::SeqCst);
+                    return true;
+                }
+                _ => return false,
+            }
+        };
+
+        if let Some(part) = waiting {
+            let _ = std::fs::remove_file(part);
+        }
+        self.finish(id, Err(Failure::new("cancelled", "Cancelled")));
+        true
+    }
+
+    // Queued jobs leave the queue right away; active ones stop at the next
+    // chunk. Whoever waits on the job keeps waiting until it is resumed or
+    // cancelled. false if the job is unknown or not queued or active.
+    pub fn pause(&self, id: &str) -> bool {
+        let info = {
+            let mut jobs = self.0.jobs.lock().unwrap();
+            let Some(job) = jobs.by_id.get_mut(id) else {
+                return false;
+            };
+            match job.info.state {
+                DownloadState::Queued => {
+                    job.info.state = DownloadState::Paused;
+                    let info = job.info.clone();
+                    jobs.queue.retain(|queued| queued != id);
+                    info
+                }
+                DownloadState::Active => {
+                    job.pause.store(true, Ordering::SeqCst);
+                    return true;
+                }
+                _ => return false,
+            }
+        };
+
+        self.emit(&info);
+        true
+    }
+
+    // Puts a paused job back at the end of the queue. false if it isn't paused.
+    pub fn resume(&self, id: &str) -> bool {
+        let info = {
+            let mut jobs = self.0.jobs.lock().unwrap();
+            let Some(job) = jobs.by_id.get_mut(id) else {
+                return false;
+            };
+            if job.info.state != DownloadState::Paused {
+                return false;
+            }
+            job.pause.store(false, Ordering::SeqCst);
+            job.info.state = DownloadState::Queued;
+            let info = job.info.clone();
+            jobs.queue.push_back(id.to_string());
+            info
+        };
+
+        self.emit(&info);
+        self.0.wake.notify_one();
+        true
+    }
+
+    pub fn list(&self) -> Vec<DownloadInfo> {
+        let jobs = self.0.jobs.lock().unwrap();
+        jobs.order
+            .iter()
+            .filter_map(|id| jobs.by_id.get(id))
+            .map(|job| job.info.clone())
+            .collect()
+    }
+
+    fn emit(&self, info: &DownloadInfo) {
+        let _ = self.0.app.emit("download-state", info);
+    }
+
+    fn update(&self, id: &str, change: impl FnOnce(&mut DownloadInfo)) -> Option<DownloadInfo> {
+        let mut jobs = self.0.jobs.lock().unwrap();
+        let job = jobs.by_id.get_mut(id)?;
+        change(&mut job.info);
+        Some(job.info.clone())
+    }
+
+    fn mark_paused(&self, id: &str) {
+        let info = {
+            let mut jobs = self.0.jobs.lock().unwrap();
+            let Some(job) = jobs.by_id.get_mut(id) else {
+                return;
+            };
+            job.info.state = DownloadState::Paused;
+            job.resumable = true;
+            job.info.clone()
+        };
+        self.emit(&info);
+    }
+
+    fn finish(&self, id: &str, outcome: Result<(), Failure>) {
+        let (info, waiters) = {
+            let mut jobs = self.0.jobs.lock().unwrap();
+            let Some(job) = jobs.by_id.get_mut(id) else {
+                return;
+            };
+            match outcome {
+                Ok(()) => job.info.state = DownloadState::Done,
+                Err(failure) => {
+                    job.info.state = if failure.reason == "cancelled" {
+                        DownloadState::Cancelled
+                    } else {
+                        DownloadState::Failed
+                    };
+                    job.info.reason = Some(failure.reason.to_string());
+                    job.info.error = Some(failure.error);
+                }
+            }
+            let finished = (job.info.clone(), std::mem::take(&mut job.waiters));
+            jobs.prune();
+            finished
+        };
+
+        self.emit(&info);
+        for waiter in waiters {
+            let _ = waiter.send(info.clone());
+        }
+    }
+
+    async fn work(self) {
+        loop {
+            let next = {
+                let mut jobs = self.0.jobs.lock().unwrap();
+                jobs.queue.pop_front().and_then(|id| {
+                    let job = jobs.by_id.get_mut(&id)?;
+                    job.info.state = DownloadState::Active;
+                    Some((
+                        id,
+                        job.info.url.clone(),
+                        PathBuf::from(&job.info.destination),
+                        Signals {
+                            cancel: job.cancel.clone(),
+                            pause: job.pause.clone(),
+                        },
+                        job.expect_pdf,
+                        job.resumable,
+                    ))
+                })
+            };
+
+            let Some((id, url, destination, signals, expect_pdf, resumable)) = next else {
+                self.0.wake.notified().await;
+                continue;
+            };
+
+            if let Some(info) = self.update(&id, |_| {}) {
+                self.emit(&info);
+            }
+            let outcome = self
+                .transfer(&id, &url, &destination, &signals, expect_pdf, resumable)
+                .await;
+            if matches!(&outcome, Err(failure) if failure.reason == "paused") {
+                // Cancelled while it was winding down: finish it as cancelled
+                if signals.cancel.load(Ordering::SeqCst) {
+                    let _ = tokio::fs::remove_file(importer::part_path(&destination)).await;
+                    self.finish(&id, Err(Failure::new("cancelled", "Cancelled")));
+                } else {
+                    self.mark_paused(&id);
+                }
+                continue;
+            }
+            if outcome.is_err() {
+                let _ = tokio::fs::remove_file(importer::part_path(&destination)).await;
+            }
+            self.finish(&id, outcome);
+        }
+    }
+
+    async fn wait_for_host(&self, url: &Url) {
+        let host = url.host_str().unwrap_or_default().to_lowercase();
+        if host == "arxiv.org" || host.ends_with(".arxiv.org") {
+            crate::wait_for_arxiv_slot().await;
+            return;
+        }
+
+        let wait = {
+            let mut slots = self.0.host_slots.lock().unwrap();
+            let now = Instant::now();
+            let slot = slots.get(&host).map(|next| (*next).max(now)).unwrap_or(now);
+            slots.insert(host, slot + HOST_REQUEST_INTERVAL);
+            slot - now
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    async fn transfer(
+        &self,
+        id: &str,
+        url: &str,
+        destination: &Path,
+        signals: &Signals,
+        expect_pdf: bool,
+        resumable: bool,
+    ) -> Result<(), Failure> {
+        let parsed = Url::parse(url).map_err(|e| Failure::new("network_error", e))?;
+        signals.check()?;
+        self.wait_for_host(&parsed).await;
+        signals.check()?;
+
+        // Left behind when this job was paused; anything else there is stale
+        let part = importer::part_path(destination);
+        let resume_from = if resumable {
+            tokio::fs::metadata(&part)
+                .await
+                .map(|metadata| metadata.len())
+                .unwrap_or(0)
+        } else {
+            0
+        };
+
+        let client = crate::arxiv_client().map_err(|e| Failure::new("network_error", e))?;
+        debug!("GET {}", url);
+        let mut request = client.get(parsed);
+        if resume_from > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", resume_from));
+        }
+        let mut response = request.send().await.map_err(|e| {
+            if e.is_redirect() {
+                let url = e.url().map(|url| url.to_string()).unwrap_or_default();
+                Failure::new("too_many_redirects", url)
+            } else {
+                Failure::new("network_error", e)
+            }
+        })?;
+
+        let status = response.status();
+        // A server that ignores Range sends the whole body again
+        let resumed = resume_from > 0 && status == reqwest::StatusCode::PARTIAL_CONTENT;
+        let offset = if resumed { resume_from } else { 0 };
+        let resolved_url = response.url().to_string();
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+        let content_type = header(reqwest::header::CONTENT_TYPE);
+        let content_disposition = header(reqwest::header::CONTENT_DISPOSITION);
+        let is_html = importer::is_html_content_type(content_type.as_deref());
+        let total_bytes = response.content_length().map(|length| length + offset);
+        if let Some(info) = self.update(id, |info| {
+            info.http_status = Some(status.as_u16());
+            info.resolved_url = Some(resolved_url);
+            info.content_type = content_type;
+            info.content_disposition = content_disposition;
+            info.total_bytes = total_bytes;
+        }) {
+            self.emit(&info);
+        }
+
+        if !status.is_success() {
+            return Err(Failure::new("http_error", format!("HTTP {}", status)));
+        }
+
+        // Enough of the body to see the PDF header, held back until it checks
+        // out. A resumed body was checked the first time round.
+        let mut head = Vec::new();
+        if expect_pdf && !resumed {
+            while head.len() < importer::PDF_MAGIC.len() {
+                match response
+                    .chunk()
+                    .await
+                    .map_err(|e| Failure::new("network_error", e))?
+                {
+                    Some(chunk) => head.extend_from_slice(&chunk),
+                    None => break,
+                }
+            }
+            if is_html || !head.starts_with(importer::PDF_MAGIC) {
+                return Err(Failure::new("not_a_pdf", importer::body_preview(&head)));
+            }
+        }
+
+        let mut hasher = Sha256::new();
+        let mut file = if resumed {
+            // Hashed again so the checksum covers the whole file
+            let mut file = tokio::fs::OpenOptions::new()
+                .read(true)
+                .append(true)
+                .open(&part)
+                .await
+                .map_err(|e| Failure::new("write_failed", e))?;
+            let mut buffer = vec![0; 64 * 1024];
+            loop {
+                let read = file
+                    .read(&mut buffer)
+                    .await
+                    .map_err(|e| Failure::new("write_failed", e))?;
+                if read == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..read]);
+            }
+            file
+        } else {
+            tokio::fs::File::create(&part)
+                .await
+                .map_err(|e| Failure::new("write_failed", e))?
+        };
+        file.write_all(&head)
+            .await
+            .map_err(|e| Failure::new("write_failed", e))?;
+        hasher.update(&head);
+
+        let mut downloaded = offset + head.len() as u64;
+        let mut last_event = Instant::now();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| Failure::new("network_error", e))?
+        {
+            if let Err(stop) = signals.check() {
+                // What arrived so far stays in the part for a resume
+                let _ = file.flush().await;
+                self.update(id, |info| info.bytes_downloaded = downloaded);
+                return Err(stop);
+            }
+
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| Failure::new("write_failed", e))?;
+            hasher.update(&chunk);
+            downloaded += chunk.len() as u64;
+
+            if last_event.elapsed() >= PROGRESS_EVENT_INTERVAL {
+                last_event = Instant::now();
+                if let Some(info) = self.update(id, |info| info.bytes_downloaded = downloaded) {
+                    self.emit(&info);
+                }
+            }
+        }
+
+        file.sync_all()
+            .await
+            .map_err(|e| Failure::new("write_failed", e))?;
+        drop(file);
+        let sha256 = format!("{:x}", hasher.finalize());
+        self.update(id, |info| {
+            info.bytes_downloaded = downloaded;
+            info.sha256 = Some(sha256);
+        });
+
+        tokio::fs::rename(&part, destination).await.map_err(|e| {
+            warn!("Failed to move download into place: {:?}", e);
+            Failure::new("write_failed", e)
+        })
+    }
+}
+
+impl Jobs {
+    fn prune(&mut self) {
+        let finished = self
+            .order
+            .iter()
+            .filter(|id| {
+                self.by_id
+                    .get(*id)
+                    .map(|job| job.info.state.is_finished())
+                    .unwrap_or(true)
+            })
+            .count();
+        let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+        if excess == 0 {
+            return;
+        }
+
+        let by_id = &mut self.by_id;
+        self.order.retain(|id| {
+            let finished = by_id
+                .get(id)
+                .map(|job| job.info.state.is_finished())
+                .unwrap_or(true);
+            if finished && excess > 0 {
+                excess -= 1;
+                by_id.remove(id);
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+// Queues a download to an absolute path that doesn't exist yet and returns
+// its id; progress arrives as "download-state" events
+#[tauri::command]
+pub fn enqueue_download(
+    downloads: State<'_, DownloadManager>,
+    url: String,
+    destination: String,
+    provenance: Option<serde_json::Value>,
+    priority: Option<bool>,
+) -> Result<String, String> {
+    match Url::parse(url.trim()) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+        _ => {
+            return Err(invalid_argument(
+                "invalid_link",
+                format!("Not an HTTP(S) URL: {}", url),
+            ))
+        }
+    }
+
+    let destination = PathBuf::from(destination);
+    if !destination.is_absolute() {
+        return Err(invalid_argument(
+            "invalid_target_dir",
+            format!(
+                "Destination must be an absolute path: {}",
+                destination.display()
+            ),
+        ));
+    }
+    if !destination.parent().map(Path::is_dir).unwrap_or(false) {
+        return Err(format!(
+            "Destination directory does not exist: {}",
+            destination.display()
+        ));
+    }
+    if destination.exists() {
+        return Err(format!("File already exists: {}", destination.display()));
+    }
+
+    let request = DownloadRequest {
+        url: url.trim().to_string(),
+        destination,
+        provenance,
+        cancel: None,
+        expect_pdf: false,
+    };
+    let (id, _) = downloads.enqueue(request, priority.unwrap_or(false));
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn cancel_download(downloads: State<'_, DownloadManager>, id: String) -> bool {
+    downloads.cancel(&id)
+}
+
+#[tauri::command]
+pub fn pause_download(downloads: State<'_, DownloadManager>, id: String) -> bool {
+    downloads.pause(&id)
+}
+
+#[tauri::command]
+pub fn resume_download(downloads: State<'_, DownloadManager>, id: String) -> bool {
+    downloads.resume(&id)
+}
+
+#[tauri::command]
+pub fn list_downloads(downloads: State<'_, DownloadManager>) -> Vec<DownloadInfo> {
+    downloads.list()
+}
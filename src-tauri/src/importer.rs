@@ -0,0 +1,657 @@
+// Pieces shared by the paper importers (arXiv, bioRxiv/medRxiv, ...) so they
+// agree on file naming, argument checks, PDF validation and sidecar layout.
+
+use log::{error, info, warn};
+use sanitize_filename::sanitize;
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use crate::downloads::{DownloadManager, DownloadRequest, DownloadState};
+use crate::sidecar;
+use crate::{unix_timestamp_string, SidecarFields};
+
+const MIN_PDF_BYTES: u64 = 1024;
+
+pub fn compact_text(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub fn sanitize_title_for_filename(title: &str) -> String {
+    let compact = compact_text(title);
+    let underscored = compact.replace(['/', '\\'], " ");
+    let joined = underscored.split_whitespace().collect::<Vec<_>>().join("_");
+    let truncated = joined.chars().take(96).collect::<String>();
+    let cleaned = correct_filename(
+        &sanitize(&truncated),
+        crate::max_filename_bytes().saturating_sub(STEM_SUFFIX_RESERVE_BYTES),
+    );
+    if cleaned.is_empty() {
+        "paper".to_string()
+    } else {
+        cleaned
+    }
+}
+
+// ext4, APFS and NTFS all stop at 255 bytes (UTF-16 units on NTFS)
+pub const DEFAULT_MAX_FILENAME_BYTES: usize = 255;
+// Room a generated stem leaves for the longest sidecar suffix plus ".part"
+const STEM_SUFFIX_RESERVE_BYTES: usize = 20;
+// Illegal on Windows; '/' is the separator everywhere else
+const ILLEGAL_FILENAME_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+// Windows device names, reserved with any extension ("aux.pdf" too)
+const RESERVED_FILENAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FilenameViolation {
+    // empty_name, invalid_character, reserved_name, trailing_dot_or_space or
+    // name_too_long
+    pub code: String,
+    pub message: String,
+}
+
+fn is_reserved_filename(name: &str) -> bool {
+    let base = name.split('.').next().unwrap_or(name).trim_end();
+    RESERVED_FILENAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(base))
+}
+
+// The first rule `name` breaks for a file name that has to work on every
+// platform the library might be synced to, or None if it's fine
+pub fn filename_violation(name: &str, max_bytes: usize) -> Option<FilenameViolation> {
+    let violation = |code: &str, message: String| {
+        Some(FilenameViolation {
+            code: code.to_string(),
+            message,
+        })
+    };
+
+    if name.trim().is_empty() {
+        return violation("empty_name", "File name is empty".to_string());
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| c.is_control() || ILLEGAL_FILENAME_CHARS.contains(c))
+    {
+        return violation(
+            "invalid_character",
+            format!("File names cannot contain {:?}", c),
+        );
+    }
+    if name == "." || name == ".." || is_reserved_filename(name) {
+        return violation(
+            "reserved_name",
+            format!("'{}' is a reserved name on Windows", name),
+        );
+    }
+    if name.ends_with(['.', ' ']) {
+        return violation(
+            "trailing_dot_or_space",
+            "File names cannot end with a dot or a space".to_string(),
+        );
+    }
+    if name.len() > max_bytes {
+        return violation(
+            "name_too_long",
+            format!(
+                "File name is {} bytes, the limit is {}",
+                name.len(),
+                max_bytes
+            ),
+        );
+    }
+    None
+}
+
+// The closest name filename_violation accepts: illegal characters become
+// "_", a reserved name gets a "_" after it, trailing dots and spaces go, and
+// an overlong name is cut at a character boundary, keeping a short extension.
+// Empty if nothing usable is left.
+pub fn correct_filename(name: &str, max_bytes: usize) -> String {
+    let mapped = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_control() || ILLEGAL_FILENAME_CHARS.contains(&c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect::<String>();
+    let mut corrected = mapped.trim_end_matches(['.', ' ']).to_string();
+
+    if corrected.len() > max_bytes {
+        let extension = corrected
+            .rfind('.')
+            .filter(|dot| *dot > 0 && corrected.len() - dot <= 16)
+            .map(|dot| corrected[dot..].to_string())
+            .unwrap_or_default();
+        let mut cut = max_bytes.saturating_sub(extension.len());
+        while !corrected.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        let stem = corrected[..cut].trim_end_matches(['.', ' ']);
+        corrected = if extension.len() <= max_bytes {
+            format!("{}{}", stem, extension)
+        } else {
+            String::new()
+        };
+    }
+
+    // Last, since trimming can uncover one ("con." becomes "con")
+    if is_reserved_filename(&corrected) {
+        let base_len = corrected.find('.').unwrap_or(corrected.len());
+        corrected.insert(base_len, '_');
+    }
+    corrected
+}
+
+pub fn non_empty_text(value: Option<String>) -> Option<String> {
+    value
+        .as_deref()
+        .map(compact_text)
+        .filter(|value| !value.is_empty())
+}
+
+// "{id}_{Title_Words}", with any slashes in the id (old-style arXiv ids, DOIs)
+// turned into underscores
+pub fn paper_file_stem(id: &str, title: &str) -> String {
+    let stem = format!(
+        "{}_{}",
+        id.replace('/', "_"),
+        sanitize_title_for_filename(title)
+    );
+    correct_filename(
+        &stem,
+        crate::max_filename_bytes().saturating_sub(STEM_SUFFIX_RESERVE_BYTES),
+    )
+}
+
+// Expands "{name}" placeholders in a user-supplied filename template, e.g.
+// "{first_author}{year}_{title}", then sanitizes the whole stem. A "{" with no
+// closing "}" is kept literally; a placeholder not in `values` is a caller
+// error.
+pub fn expand_filename_template(template: &str, values: &[(&str, &str)]) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 1..start + length];
+        let value = values
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.replace('/', "_"))
+            .ok_or_else(|| {
+                invalid_argument(
+                    "invalid_input",
+                    format!("Unknown filename template placeholder: {{{}}}", name),
+                )
+            })?;
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(&value);
+        rest = &rest[start + length + 1..];
+    }
+    expanded.push_str(rest);
+
+    Ok(sanitize_title_for_filename(&expanded))
+}
+
+// "<code>: <message>", so the frontend can branch on the code before the colon
+pub fn invalid_argument(code: &str, message: String) -> String {
+    format!("{}: {}", code, message)
+}
+
+pub fn check_conflict_policy(conflict_policy: &str) -> Result<(), String> {
+    check_conflict_policy_in(conflict_policy, &["skip"])
+}
+
+// For importers that support more than "skip"
+pub fn check_conflict_policy_in(conflict_policy: &str, supported: &[&str]) -> Result<(), String> {
+    if !supported.contains(&conflict_policy) {
+        return Err(invalid_argument(
+            "invalid_conflict_policy",
+            format!("Unsupported conflict policy: {}", conflict_policy),
+        ));
+    }
+    Ok(())
+}
+
+pub fn check_target_dir(target_dir: &str) -> Result<(), String> {
+    if target_dir.trim().is_empty() {
+        return Err(invalid_argument(
+            "invalid_target_dir",
+            "Target directory is empty".to_string(),
+        ));
+    }
+
+    if !Path::new(target_dir).is_absolute() {
+        return Err(invalid_argument(
+            "invalid_target_dir",
+            format!("Target directory must be an absolute path: {}", target_dir),
+        ));
+    }
+
+    Ok(())
+}
+
+// Creates the target if needed; false means the import should be skipped
+// with "write_failed"
+pub fn ensure_target_dir(target: &Path) -> bool {
+    if !target.exists() {
+        if let Err(error) = fs::create_dir_all(target) {
+            error!("Failed to create target directory: {:?}", error);
+            return false;
+        }
+    }
+
+    target.is_dir()
+}
+
+pub const PDF_MAGIC: &[u8] = b"%PDF-";
+
+pub fn is_html_content_type(content_type: Option<&str>) -> bool {
+    content_type
+        .map(|value| value.to_lowercase().contains("html"))
+        .unwrap_or(false)
+}
+
+pub fn body_preview(body: &[u8]) -> String {
+    String::from_utf8_lossy(&body[..body.len().min(512)])
+        .chars()
+        .take(200)
+        .collect()
+}
+
+// Why a download didn't produce a PDF: the skip reason for the frontend plus,
+// for non-PDF bodies, the start of what the server sent instead
+#[derive(Debug)]
+pub struct DownloadFailure {
+    pub reason: &'static str,
+    pub detail: Option<String>,
+    // HTTP status when the server answered with an error
+    pub status: Option<u16>,
+    // Final URL after redirects, once the server answered
+    pub resolved_url: Option<String>,
+}
+
+impl DownloadFailure {
+    fn new(reason: &'static str) -> Self {
+        Self {
+            reason,
+            detail: None,
+            status: None,
+            resolved_url: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DownloadedPdf {
+    pub size: u64,
+    pub sha256: String,
+    pub resolved_url: Option<String>,
+}
+
+// Fetches a PDF through the shared download queue; `provenance` is shown in
+// list_downloads, and setting `cancel` stops the download. The file is
+// removed again if it fails validation. Bodies that aren't a PDF at all (an
+// HTML error page during an outage) are refused before anything is written
+// and come back as `not_pdf_reason`.
+pub async fn queue_pdf_download(
+    downloads: &DownloadManager,
+    url: &str,
+    pdf_path: &Path,
+    provenance: serde_json::Value,
+    cancel: Option<Arc<AtomicBool>>,
+    not_pdf_reason: &'static str,
+) -> Result<DownloadedPdf, DownloadFailure> {
+    let info = downloads
+        .download(DownloadRequest {
+            url: url.to_string(),
+            destination: pdf_path.to_path_buf(),
+            provenance: Some(provenance),
+            cancel,
+            expect_pdf: true,
+        })
+        .await;
+
+    match (info.state, info.reason.as_deref()) {
+        (DownloadState::Done, _) => {}
+        (DownloadState::Cancelled, _) => return Err(DownloadFailure::new("cancelled")),
+        (_, Some("write_failed")) => {
+            error!("Failed to write downloaded PDF: {:?}", info.error);
+            return Err(DownloadFailure::new("write_failed"));
+        }
+        (_, Some("not_a_pdf")) => {
+            warn!(
+                "Server returned a non-PDF body (content type {:?})",
+                info.content_type
+            );
+            return Err(DownloadFailure {
+                detail: info.error,
+                status: info.http_status,
+                resolved_url: info.resolved_url,
+                ..DownloadFailure::new(not_pdf_reason)
+            });
+        }
+        (_, Some("http_error")) => {
+            let reason = if info.http_status == Some(404) {
+                "paper_not_found"
+            } else {
+                "network_error"
+            };
+            return Err(DownloadFailure {
+                status: info.http_status,
+                resolved_url: info.resolved_url,
+                ..DownloadFailure::new(reason)
+            });
+        }
+        _ => {
+            warn!("Failed to download PDF: {:?}", info.error);
+            return Err(DownloadFailure::new("network_error"));
+        }
+    }
+
+    // The header was checked as the body came in and the checksum computed
+    // along the way, so only the end of the file is read back
+    // A connection dropped mid-body can still look like a PDF up front
+    let complete = match crate::pdf::file_has_eof_marker(pdf_path) {
+        Ok(complete) => complete && info.bytes_downloaded >= MIN_PDF_BYTES,
+        Err(error) => {
+            error!("Failed to read downloaded PDF: {:?}", error);
+            return Err(DownloadFailure::new("write_failed"));
+        }
+    };
+    let sha256 = match info.sha256 {
+        Some(sha256) if complete => sha256,
+        _ => {
+            warn!(
+                "Downloaded PDF is truncated or has no %%EOF marker ({} bytes)",
+                info.bytes_downloaded
+            );
+            let _ = fs::remove_file(pdf_path);
+            return Err(DownloadFailure {
+                resolved_url: info.resolved_url,
+                ..DownloadFailure::new("corrupt_download")
+            });
+        }
+    };
+
+    Ok(DownloadedPdf {
+        size: info.bytes_downloaded,
+        sha256,
+        resolved_url: info.resolved_url,
+    })
+}
+
+// Sidecar entry recording the downloaded PDF's checksum
+pub fn checksum_fields(sha256: &str) -> SidecarFields {
+    let mut fields = SidecarFields::new();
+    fields.insert("sha256".to_string(), serde_json::json!(sha256));
+    fields
+}
+
+// "<name>.part" next to `path`, where a file is staged until the whole import
+// can be committed
+pub fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+// A .part left at this point is from an import that crashed midway
+pub fn remove_stale_part(part: &Path) {
+    if part.exists() {
+        info!("Removing stale partial file: {:?}", part);
+        let _ = fs::remove_file(part);
+    }
+}
+
+pub fn discard_parts(parts: &[&Path]) {
+    for part in parts {
+        let _ = fs::remove_file(part);
+    }
+}
+
+// Renames each (part, target) pair into place. If one rename fails, targets
+// already placed are removed again and the remaining parts are discarded, so
+// only the last target may be one that existed before.
+pub fn commit_parts(files: &[(&Path, &Path)]) -> io::Result<()> {
+    for (index, (part, target)) in files.iter().enumerate() {
+        if let Err(error) = fs::rename(part, target) {
+            for (_, placed) in &files[..index] {
+                let _ = fs::remove_file(placed);
+            }
+            for (part, _) in &files[index..] {
+                let _ = fs::remove_file(part);
+            }
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+// Writes the sidecar to metadata_part with `write_sidecar`, then renames it
+// and the staged PDF (None when the PDF is already in place) into position.
+// Whatever fails, every part is discarded first, so the PDF never shows up
+// without its sidecar.
+pub fn commit_import(
+    pdf: Option<(&Path, &Path)>,
+    metadata_part: &Path,
+    metadata_path: &Path,
+    write_sidecar: impl FnOnce(&Path) -> Result<(), String>,
+) -> Result<(), String> {
+    if let Err(error) = write_sidecar(metadata_part) {
+        let pdf_part = pdf.map(|(part, _)| part);
+        discard_parts(
+            &pdf_part
+                .into_iter()
+                .chain([metadata_part])
+                .collect::<Vec<_>>(),
+        );
+        return Err(format!("Failed to write metadata file: {}", error));
+    }
+
+    let files = pdf
+        .into_iter()
+        .chain([(metadata_part, metadata_path)])
+        .collect::<Vec<_>>();
+    commit_parts(&files).map_err(|e| format!("Failed to move imported files into place: {}", e))
+}
+
+// The sidecar layout every importer writes: the paper's own fields, then
+// `extra`, then the bookkeeping keys (source, downloaded_at, pdf_path)
+pub fn sidecar_fields<P: Serialize>(
+    paper: &P,
+    source: &str,
+    pdf_path: Option<&Path>,
+    extra: SidecarFields,
+) -> SidecarFields {
+    let mut fields = match serde_json::to_value(paper) {
+        Ok(serde_json::Value::Object(fields)) => fields,
+        _ => SidecarFields::new(),
+    };
+    fields.extend(extra);
+    fields.insert("source".to_string(), serde_json::json!(source));
+    fields.insert(
+        "downloaded_at".to_string(),
+        serde_json::json!(unix_timestamp_string()),
+    );
+    if let Some(pdf_path) = pdf_path {
+        fields.insert(
+            "pdf_path".to_string(),
+            serde_json::json!(pdf_path.to_string_lossy().to_string()),
+        );
+    }
+    fields
+}
+
+// The format follows from metadata_path's suffix (see sidecar::metadata_path)
+pub fn write_sidecar<P: Serialize>(
+    paper: &P,
+    source: &str,
+    pdf_path: Option<&Path>,
+    metadata_path: &Path,
+    extra: SidecarFields,
+) -> Result<(), String> {
+    sidecar::write(
+        metadata_path,
+        sidecar_fields(paper, source, pdf_path, extra),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn staged_import(dir: &Path) -> (PathBuf, PathBuf, PathBuf, PathBuf) {
+        let pdf_path = dir.join("2301.01234v1_Paper.pdf");
+        let metadata_path = dir.join("2301.01234v1_Paper.metadata.json");
+        let pdf_part = part_path(&pdf_path);
+        fs::write(&pdf_part, b"%PDF-1.7 downloaded").unwrap();
+        (pdf_part, pdf_path, part_path(&metadata_path), metadata_path)
+    }
+
+    fn write_test_sidecar(part: &Path) -> Result<(), String> {
+        sidecar::write(part, SidecarFields::new())
+    }
+
+    #[test]
+    fn sidecar_write_failure_discards_the_staged_pdf() {
+        let dir = tempfile::tempdir().unwrap();
+        let (pdf_part, pdf_path, metadata_part, metadata_path) = staged_import(dir.path());
+
+        let result = commit_import(
+            Some((&pdf_part, &pdf_path)),
+            &metadata_part,
+            &metadata_path,
+            |_| Err("disk full".to_string()),
+        );
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sidecar_write_failure_in_read_only_dir_leaves_no_pdf() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let (pdf_part, pdf_path, metadata_part, metadata_path) = staged_import(dir.path());
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o555)).unwrap();
+        // Root ignores directory permissions, so there is nothing to test
+        let enforced = fs::File::create(dir.path().join("probe")).is_err();
+
+        let result = enforced.then(|| {
+            commit_import(
+                Some((&pdf_part, &pdf_path)),
+                &metadata_part,
+                &metadata_path,
+                write_test_sidecar,
+            )
+        });
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o755)).unwrap();
+
+        if let Some(result) = result {
+            assert!(result.is_err());
+            assert!(!pdf_path.exists());
+            assert!(!metadata_path.exists());
+        }
+    }
+
+    #[test]
+    fn failed_sidecar_rename_takes_the_placed_pdf_back_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let (pdf_part, pdf_path, metadata_part, metadata_path) = staged_import(dir.path());
+        // Something the sidecar can't be renamed over
+        fs::create_dir(&metadata_path).unwrap();
+        fs::write(metadata_path.join("keep"), b"").unwrap();
+
+        let result = commit_import(
+            Some((&pdf_part, &pdf_path)),
+            &metadata_part,
+            &metadata_path,
+            write_test_sidecar,
+        );
+
+        assert!(result.is_err());
+        assert!(!pdf_path.exists());
+        assert!(!pdf_part.exists());
+        assert!(!metadata_part.exists());
+    }
+
+    #[test]
+    fn committed_import_places_both_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let (pdf_part, pdf_path, metadata_part, metadata_path) = staged_import(dir.path());
+
+        commit_import(
+            Some((&pdf_part, &pdf_path)),
+            &metadata_part,
+            &metadata_path,
+            write_test_sidecar,
+        )
+        .unwrap();
+
+        assert_eq!(fs::read(&pdf_path).unwrap(), b"%PDF-1.7 downloaded");
+        assert!(metadata_path.is_file());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+}
+
+// A local HTTP server for importer tests that answers each request with the
+// next (status, content type, body) in turn and then stops. Returns its
+// base URL and the request lines it saw.
+#[cfg(test)]
+pub mod testing {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    pub fn serve(responses: Vec<(u16, &'static str, String)>) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let (requests, received) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            for (status, content_type, body) in responses {
+                let Ok((stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut reader = BufReader::new(stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+                let _ = requests.send(request_line.trim_end().to_string());
+
+                let mut stream = reader.into_inner();
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Test\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+                     Connection: close\r\n\r\n{}",
+                    status,
+                    content_type,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        (base, received)
+    }
+}
@@ -1,23 +1,63 @@
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime};
+use flate2::read::GzDecoder;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use log::{debug, error, warn};
+use notify::event::{ModifyKind, RenameMode};
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use quick_xml::de::from_str;
 use regex::Regex;
 use reqwest::Client;
-use sanitize_filename::sanitize;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
-use std::sync::Mutex;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use tauri::{AppHandle, Emitter};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_log::{RotationStrategy, Target, TargetKind};
+use unicode_normalization::UnicodeNormalization;
 use url::Url;
 use walkdir::WalkDir;
 
+mod archive;
+mod audit;
+mod biorxiv;
+mod checksum;
+mod concurrency;
+mod doi;
+mod downloads;
+mod importer;
+mod openreview;
+mod orcid;
+mod pdf;
+mod semantic_scholar;
+mod sidecar;
+mod thumbnails;
+mod url_import;
+
+use concurrency::{Budget, Concurrency};
+use downloads::{DownloadManager, DownloadRequest, DownloadState};
+use importer::{compact_text, invalid_argument, non_empty_text};
+use semantic_scholar::Enrichment;
+use sidecar::{sidecar_stem, SidecarFormat};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PdfFile {
     pub name: String,
     pub path: String,
     pub size: u64,
+    // A symlink; path is the link itself and link_target the file it points
+    // to, so a link next to its target is still an entry of its own
+    #[serde(default)]
+    pub is_symlink: bool,
+    #[serde(default)]
+    pub link_target: Option<String>,
+    // Equal for every hard link (and symlink) to the same file; see file_id
+    #[serde(default)]
+    pub file_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +66,10 @@ pub struct ScanResult {
     pub total_count: usize,
     pub error_count: usize,
     pub errors: Vec<String>,
+    pub has_more: bool,
+    // The scan ran out of time and files is what it found until then
+    #[serde(default)]
+    pub timed_out: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,24 +77,168 @@ pub struct ArxivPaperMetadata {
     pub arxiv_id: String,
     pub version: u32,
     pub title: String,
+    // Display names, kept flat for older readers of the sidecar. Sidecars
+    // that store author objects here load as well.
+    #[serde(deserialize_with = "deserialize_author_names")]
     pub authors: Vec<String>,
+    // Same authors with what the feed knows about them; absent from sidecars
+    // written before it existed
+    #[serde(default)]
+    pub authors_detailed: Vec<ArxivAuthor>,
     pub summary: String,
     pub published: String,
     pub updated: String,
+    // Calendar parts of `published`, None when arXiv sent something unparseable
+    #[serde(default)]
+    pub published_year: Option<u32>,
+    #[serde(default)]
+    pub published_month: Option<u32>,
     pub abs_url: String,
     pub pdf_url: String,
+    pub primary_category: Option<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    pub doi: Option<String>,
+    pub journal_ref: Option<String>,
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub withdrawn: bool,
+    // The abstract with its paragraph breaks, which `summary` collapses. Only
+    // known right after a fetch; never written to the sidecar.
+    #[serde(skip)]
+    pub summary_paragraphs: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArxivAuthor {
+    pub name: String,
+    #[serde(default)]
+    pub affiliation: Option<String>,
+    // Bare iD ("0000-0002-1825-0097"), from the feed or the optional ORCID
+    // lookup (see orcid.rs). Only lookups set orcid_confidence, in 0..=1.
+    #[serde(default)]
+    pub orcid: Option<String>,
+    #[serde(default)]
+    pub orcid_confidence: Option<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AuthorEntry {
+    Name(String),
+    Detailed(ArxivAuthor),
+}
+
+fn deserialize_author_names<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let entries = Vec::<AuthorEntry>::deserialize(deserializer)?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| match entry {
+            AuthorEntry::Name(name) => name,
+            AuthorEntry::Detailed(author) => author.name,
+        })
+        .collect())
+}
+
+// Optional knobs for import_arxiv_paper; every field has a default so the
+// frontend only sends what it wants to change.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArxivImportOptions {
+    // "full" (download the PDF), "metadata_only" (sidecar only) or "attach"
+    // (adopt existing_pdf_path under the canonical name)
+    pub mode: Option<String>,
+    pub existing_pdf_path: Option<String>,
+    // Also fetch the LaTeX e-print next to the PDF
+    pub include_source: bool,
+    // HTTPS bases for mirrors, e.g. "https://export.arxiv.org" for the API
+    // and "https://arxiv.org" for PDFs and sources
+    pub api_base: Option<String>,
+    pub pdf_base: Option<String>,
+    // Treat input that isn't an arXiv URL or ID as a caller error instead of
+    // an "invalid_link" skip, for callers that validated it beforehand
+    pub strict: bool,
+    // Folders searched for an existing sidecar with the same arXiv id before
+    // anything is fetched
+    pub library_roots: Vec<String>,
+    // Render a {file_stem}.md note with the title, authors and abstract
+    pub write_readme: bool,
+    // Write the abstract on its own to {file_stem}.abstract.md; None uses
+    // the write_abstract importer setting
+    pub write_abstract: Option<bool>,
+    // Shorthand for mode "metadata_only": sidecar and .bib, no PDF
+    pub metadata_only: bool,
+    // File stem pattern with {id}, {version}, {title}, {first_author} and
+    // {year}; defaults to "{id}v{version}_{title}"
+    pub filename_template: Option<String>,
+    // Overrides the sidecar_format importer setting for this import
+    pub sidecar_format: Option<String>,
+    // Look up ORCID iDs for the authors (best effort, see orcid.rs)
+    pub enrich_orcid: bool,
+    // Add citation counts and venue from Semantic Scholar (best effort)
+    pub enrich: bool,
+    // "pdf" (default) or "source": fetch only the LaTeX e-print, saved as
+    // {file_stem}.tar.gz (.tex.gz or .tar for other archive kinds), with the
+    // sidecar but no PDF
+    pub format: Option<String>,
+}
+
+const FILENAME_PLACEHOLDERS: &[&str] = &["id", "version", "title", "first_author", "year"];
+
+// Outcome of an import that was attempted. Arguments the caller got wrong
+// (unknown conflict policy or mode, attach without a file, empty or relative
+// target dir, unparseable input in strict mode) never get here: the command
+// returns Err("<code>: <message>") with codes invalid_conflict_policy,
+// invalid_mode, invalid_attachment, invalid_target_dir, invalid_link,
+// invalid_input (unknown filename template placeholder) and
+// invalid_sidecar_format.
+// Everything that depends on the outside world (paper_not_found,
+// network_error, pdf_unavailable, corrupt_download, file_exists, write_failed,
+// cancelled (cancel_import or cancel_download), source_unavailable for format
+// "source", ...) comes back as status "skipped" with that reason.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ArxivImportResult {
     pub status: String,
     pub reason: Option<String>,
+    pub detail: Option<String>,
     pub pdf_path: Option<String>,
+    // With conflict policy "rename": the taken path this import was going to
+    // use before it moved to pdf_path (or metadata_path for metadata_only)
+    pub renamed_from: Option<String>,
     pub pdf_size: Option<u64>,
+    // Where the PDF request ended up after redirects, once arXiv answered
+    pub resolved_pdf_url: Option<String>,
+    // Status of a PDF request that failed with an HTTP error
+    pub http_status: Option<u16>,
+    // Hex SHA-256 of the PDF, also stored as "sha256" in the sidecar
+    pub sha256: Option<String>,
     pub metadata_path: Option<String>,
+    pub bibtex_path: Option<String>,
+    pub source_path: Option<String>,
+    pub source_size: Option<u64>,
+    pub readme_path: Option<String>,
+    pub abstract_path: Option<String>,
+    // With options.enrich, unless the lookup failed
+    pub enrichment: Option<Enrichment>,
+    // Problems with optional extras that didn't stop the import
+    pub warnings: Vec<String>,
+    // With reason "version_not_found": the versions that do exist
+    pub available_versions: Option<Vec<u32>>,
     pub paper: Option<ArxivPaperMetadata>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArxivVersion {
+    pub version: u32,
+    // RFC 3339, when arXiv said
+    pub submitted: Option<String>,
+    // As shown on the abs page, e.g. "1,234 KB"
+    pub size: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct ArxivApiFeed {
     #[serde(rename = "entry", default)]
@@ -68,11 +256,36 @@ struct ArxivApiEntry {
     author: Vec<ArxivApiAuthor>,
     #[serde(rename = "link", default)]
     link: Vec<ArxivApiLink>,
+    // arxiv:* elements are matched by local name, the namespace prefix is dropped
+    primary_category: Option<ArxivApiCategory>,
+    #[serde(rename = "category", default)]
+    category: Vec<ArxivApiCategory>,
+    doi: Option<String>,
+    journal_ref: Option<String>,
+    comment: Option<String>,
+}
+
+impl ArxivApiEntry {
+    // Unknown or malformed ids still get a 200, with one entry titled "Error"
+    // whose id points at http://arxiv.org/api/errors and whose summary says
+    // what was wrong
+    fn is_error_marker(&self) -> bool {
+        self.title.as_deref().map(str::trim) == Some("Error")
+            || self
+                .id
+                .as_deref()
+                .is_some_and(|id| id.contains("api/errors"))
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct ArxivApiAuthor {
     name: Option<String>,
+    #[serde(rename = "affiliation", default)]
+    affiliation: Vec<String>,
+    // Where present, an orcid.org profile link or an arxiv:orcid element
+    uri: Option<String>,
+    orcid: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -81,35 +294,194 @@ struct ArxivApiLink {
     href: Option<String>,
 }
 
-fn parse_plain_arxiv_id(value: &str) -> Option<(String, Option<u32>)> {
-    let pattern = Regex::new(
+#[derive(Debug, Deserialize)]
+struct ArxivApiCategory {
+    #[serde(rename = "@term")]
+    term: Option<String>,
+}
+
+static PLAIN_ARXIV_ID: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
         r"^(?P<base>(?:[A-Za-z\.\-]+/[0-9]{7}|[0-9]{4}\.[0-9]{4,5}))(?:v(?P<version>[0-9]+))?$",
     )
-    .ok()?;
-    let captures = pattern.captures(value.trim())?;
+    .unwrap()
+});
+
+fn parse_plain_arxiv_id(value: &str) -> Option<(String, Option<u32>)> {
+    let captures = PLAIN_ARXIV_ID.captures(value.trim())?;
     let base = captures.name("base")?.as_str().to_lowercase();
+    // Old-style ids are often cited with the subject class ("math.GT/0309136")
+    // while arXiv itself uses the bare archive ("math/0309136")
+    let base = match base.split_once('/') {
+        Some((archive, number)) => {
+            let archive = archive.split('.').next().unwrap_or(archive);
+            format!("{}/{}", archive, number)
+        }
+        None => base,
+    };
     let version = captures
         .name("version")
         .and_then(|m| m.as_str().parse::<u32>().ok());
     Some((base, version))
 }
 
+// Archives of pre-2007 ids ("hep-th/9901001"). Matching against the list
+// keeps "tel/5551234" and the like from passing for one.
+const OLD_ARXIV_ARCHIVES: &[&str] = &[
+    "acc-phys", "adap-org", "alg-geom", "ao-sci", "astro-ph", "atom-ph", "bayes-an", "chao-dyn",
+    "chem-ph", "cmp-lg", "comp-gas", "cond-mat", "cs", "dg-ga", "funct-an", "gr-qc", "hep-ex",
+    "hep-lat", "hep-ph", "hep-th", "math", "math-ph", "mtrl-th", "nlin", "nucl-ex", "nucl-th",
+    "patt-sol", "physics", "plasm-ph", "q-alg", "q-bio", "q-fin", "quant-ph", "solv-int", "stat",
+    "supr-con",
+];
+
+// Whether a YYMM (plus the number's digit count) could be a real id: new-style
+// ids started in April 2007 and went to five digits in 2015, old-style ones
+// ran from 1991 to March 2007
+fn plausible_arxiv_date(yymm: &str, number_digits: usize, old_style: bool) -> bool {
+    let (Ok(year), Ok(month)) = (yymm[..2].parse::<u32>(), yymm[2..].parse::<u32>()) else {
+        return false;
+    };
+    if !(1..=12).contains(&month) {
+        return false;
+    }
+    if old_style {
+        return !(7..91).contains(&year) || (year == 7 && month <= 3);
+    }
+    let since_2007 = year > 7 || (year == 7 && month >= 4);
+    since_2007 && (number_digits == 5) == (year >= 15)
+}
+
+static ARXIV_ID_IN_TEXT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(?P<archive>[a-z][a-z\-]*)(?:\.[a-z]{2})?/(?P<old>[0-9]{7})(?:v(?P<old_version>[0-9]+))?|(?P<new>(?P<yymm>[0-9]{4})\.(?P<number>[0-9]{4,5}))(?:v(?P<new_version>[0-9]+))?",
+    )
+    .unwrap()
+});
+// What precedes the id in arXiv's DOIs ("10.48550/arXiv.<id>")
+const DOI_ID_PREFIX: &[u8] = b"arxiv.";
+
+// Every distinct arXiv id in free text (reference lists, citations, links),
+// in order of appearance and with the version when one is given. Bare
+// numbers only count when they sit on their own and their YYMM could be an
+// arXiv date, so years, phone numbers and IP addresses are left alone.
+#[tauri::command]
+fn extract_arxiv_ids(text: String) -> Vec<String> {
+    let bytes = text.as_bytes();
+    let mut seen = HashSet::new();
+    let mut ids = Vec::new();
+    for captures in ARXIV_ID_IN_TEXT.captures_iter(&text) {
+        let whole = captures.get(0).unwrap();
+        let before = whole.start().checked_sub(1).map(|i| bytes[i]);
+        let after = bytes.get(whole.end()).copied();
+        let after_next = bytes.get(whole.end() + 1).copied();
+        let continues = after.is_some_and(|c| c.is_ascii_alphanumeric())
+            || (after == Some(b'.') && after_next.is_some_and(|c| c.is_ascii_digit()));
+
+        let plausible = if let Some(old) = captures.name("old") {
+            let archive = captures["archive"].to_lowercase();
+            let starts_clean = !before.is_some_and(|c| c.is_ascii_alphanumeric() || c == b'-');
+            starts_clean
+                && OLD_ARXIV_ARCHIVES.contains(&archive.as_str())
+                && plausible_arxiv_date(&old.as_str()[..4], 3, true)
+        } else {
+            // A dot in front is only fine in arXiv's DOIs ("10.48550/arXiv.<id>")
+            let after_doi_prefix = bytes[..whole.start()]
+                .get(whole.start().saturating_sub(DOI_ID_PREFIX.len())..)
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(DOI_ID_PREFIX));
+            let starts_clean = match before {
+                Some(b'.') => after_doi_prefix,
+                Some(c) => !c.is_ascii_digit(),
+                None => true,
+            };
+            starts_clean && plausible_arxiv_date(&captures["yymm"], captures["number"].len(), false)
+        };
+        if !plausible || continues {
+            continue;
+        }
+
+        if let Some((base, version)) = parse_plain_arxiv_id(whole.as_str()) {
+            let id = match version {
+                Some(version) => format!("{}v{}", base, version),
+                None => base,
+            };
+            if seen.insert(id.clone()) {
+                ids.push(id);
+            }
+        }
+    }
+    ids
+}
+
+const ARXIV_HOSTS: &[&str] = &[
+    "arxiv.org",
+    "www.arxiv.org",
+    "export.arxiv.org",
+    "browse.arxiv.org",
+];
+
+// arXiv mints a DataCite DOI for every paper: 10.48550/arXiv.<id>
+fn strip_arxiv_doi(value: &str) -> Option<&str> {
+    const DOI_PREFIX: &str = "10.48550/arxiv.";
+    let value = value.strip_prefix("doi:").unwrap_or(value);
+    let has_prefix = value
+        .get(..DOI_PREFIX.len())
+        .map(|prefix| prefix.eq_ignore_ascii_case(DOI_PREFIX))
+        .unwrap_or(false);
+    if has_prefix {
+        Some(&value[DOI_PREFIX.len()..])
+    } else {
+        None
+    }
+}
+
 fn parse_arxiv_input(value: &str) -> Option<(String, Option<u32>)> {
     let trimmed = value.trim();
     if trimmed.is_empty() {
         return None;
     }
 
-    if let Ok(url) = Url::parse(trimmed) {
+    if let Some(candidate) = strip_arxiv_doi(trimmed) {
+        return parse_plain_arxiv_id(candidate);
+    }
+
+    let has_arxiv_prefix = trimmed
+        .get(.."arxiv:".len())
+        .map(|prefix| prefix.eq_ignore_ascii_case("arxiv:"))
+        .unwrap_or(false);
+    if has_arxiv_prefix {
+        return parse_plain_arxiv_id(&trimmed["arxiv:".len()..]);
+    }
+
+    let lowered = trimmed.to_lowercase();
+
+    // "arxiv.org/abs/..." pasted without a scheme
+    let with_scheme = if !trimmed.contains("://")
+        && ARXIV_HOSTS
+            .iter()
+            .any(|host| lowered.starts_with(&format!("{}/", host)))
+    {
+        format!("https://{}", trimmed)
+    } else {
+        trimmed.to_string()
+    };
+
+    if let Ok(url) = Url::parse(&with_scheme) {
         let host = url.host_str()?.to_lowercase();
-        if host != "arxiv.org" && host != "www.arxiv.org" {
+        let path = url.path().trim_matches('/');
+
+        if host == "doi.org" || host == "dx.doi.org" {
+            return strip_arxiv_doi(path).and_then(parse_plain_arxiv_id);
+        }
+
+        if !ARXIV_HOSTS.contains(&host.as_str()) {
             return None;
         }
 
-        let path = url.path().trim_matches('/');
         if let Some(candidate) = path.strip_prefix("abs/") {
             return parse_plain_arxiv_id(candidate);
         }
+        // Newer PDF links drop the .pdf suffix
         if let Some(candidate) = path.strip_prefix("pdf/") {
             let without_ext = candidate.strip_suffix(".pdf").unwrap_or(candidate);
             return parse_plain_arxiv_id(without_ext);
@@ -120,23 +492,6 @@ fn parse_arxiv_input(value: &str) -> Option<(String, Option<u32>)> {
     parse_plain_arxiv_id(trimmed)
 }
 
-fn compact_text(value: &str) -> String {
-    value.split_whitespace().collect::<Vec<_>>().join(" ")
-}
-
-fn sanitize_title_for_filename(title: &str) -> String {
-    let compact = compact_text(title);
-    let underscored = compact.replace('/', " ").replace('\\', " ");
-    let joined = underscored.split_whitespace().collect::<Vec<_>>().join("_");
-    let truncated = joined.chars().take(96).collect::<String>();
-    let cleaned = sanitize(&truncated);
-    if cleaned.is_empty() {
-        "paper".to_string()
-    } else {
-        cleaned
-    }
-}
-
 fn unix_timestamp_string() -> String {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -144,347 +499,894 @@ fn unix_timestamp_string() -> String {
         .unwrap_or_else(|_| "0".to_string())
 }
 
-fn skipped_result(reason: &str, paper: Option<ArxivPaperMetadata>) -> ArxivImportResult {
-    ArxivImportResult {
-        status: "skipped".to_string(),
-        reason: Some(reason.to_string()),
-        pdf_path: None,
-        pdf_size: None,
-        metadata_path: None,
-        paper,
-    }
+// Readers only ever see the old content or the complete new content: the bytes
+// go to a temp file in the same directory, which is then renamed over `path`.
+fn atomic_write(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    atomic_write_with(path, |file| file.write_all(bytes))
 }
 
-// Store active watchers
-static WATCHERS: Mutex<Option<HashMap<String, RecommendedWatcher>>> = Mutex::new(None);
+// atomic_write with the temp file filled in by `write`
+fn atomic_write_with(
+    path: &Path,
+    write: impl FnOnce(&mut fs::File) -> io::Result<()>,
+) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let temp_path = path.with_file_name(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        uuid::Uuid::new_v4()
+    ));
 
-#[tauri::command]
-fn scan_directory_for_pdfs(
-    dir_path: String,
-    recursive: bool,
-    max_depth: usize,
-) -> Result<ScanResult, String> {
-    let path = Path::new(&dir_path);
+    let result = fs::File::create(&temp_path)
+        .and_then(|mut file| {
+            write(&mut file)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, path));
 
-    if !path.exists() {
-        return Err(format!("Directory does not exist: {}", dir_path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
     }
+    result
+}
 
-    if !path.is_dir() {
-        return Err(format!("Path is not a directory: {}", dir_path));
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
     }
+}
 
-    let mut files = Vec::new();
-    let mut errors = Vec::new();
-    let mut error_count = 0;
+// "<device>:<inode>" on Unix and "<volume serial>:<file index>" on Windows,
+// equal for every hard link to the file and for symlinks that resolve to it
+fn file_id(path: &Path, metadata: &fs::Metadata) -> Option<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let _ = path;
+        Some(format!("{}:{}", metadata.dev(), metadata.ino()))
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt;
+        use std::os::windows::io::AsRawHandle;
+        use windows_sys::Win32::Storage::FileSystem::{
+            GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_FLAG_BACKUP_SEMANTICS,
+        };
 
-    let walker = if recursive {
-        WalkDir::new(path).max_depth(max_depth)
-    } else {
-        WalkDir::new(path).max_depth(1)
-    };
+        let _ = metadata;
+        // No read or write access asked for, so a PDF another program holds
+        // open without sharing still answers
+        let file = fs::OpenOptions::new()
+            .access_mode(0)
+            .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+            .open(os_path(path))
+            .ok()?;
+        let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+        // SAFETY: the handle stays open for the call and info is a valid out
+        // pointer of the right type
+        if unsafe { GetFileInformationByHandle(file.as_raw_handle(), &mut info) } == 0 {
+            return None;
+        }
+        let index = (u64::from(info.nFileIndexHigh) << 32) | u64::from(info.nFileIndexLow);
+        Some(format!("{}:{}", info.dwVolumeSerialNumber, index))
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = (path, metadata);
+        None
+    }
+}
 
-    for entry in walker.into_iter().filter_map(|e| e.ok()) {
-        let entry_path = entry.path();
+// Falls back to copy + delete when a plain rename can't cross filesystems
+// (EXDEV). The destination is never replaced.
+fn relocate_file(from: &Path, to: &Path) -> io::Result<()> {
+    if to.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", to.display()),
+        ));
+    }
 
-        if entry_path.is_file() {
-            if let Some(extension) = entry_path.extension() {
-                if extension.to_string_lossy().to_lowercase() == "pdf" {
-                    match entry_path.metadata() {
-                        Ok(metadata) => {
-                            files.push(PdfFile {
-                                name: entry_path
-                                    .file_name()
-                                    .map(|n| n.to_string_lossy().to_string())
-                                    .unwrap_or_default(),
-                                path: entry_path.to_string_lossy().to_string(),
-                                size: metadata.len(),
-                            });
-                        }
-                        Err(e) => {
-                            error_count += 1;
-                            errors.push(format!(
-                                "Failed to read metadata for {}: {}",
-                                entry_path.display(),
-                                e
-                            ));
-                        }
-                    }
-                }
-            }
-        }
+    match fs::rename(from, to) {
+        Ok(()) => return Ok(()),
+        Err(error) if error.kind() != io::ErrorKind::CrossesDevices => return Err(error),
+        Err(_) => {}
     }
 
-    // Sort files by name
-    files.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    // The original only goes once the copy is known to be on disk
+    let copied = fs::copy(from, to).and_then(|_| fs::File::open(to)?.sync_all());
+    if let Err(error) = copied {
+        let _ = fs::remove_file(to);
+        return Err(error);
+    }
+    fs::remove_file(from)
+}
 
-    Ok(ScanResult {
-        total_count: files.len(),
-        error_count,
-        errors,
-        files,
-    })
+// Windows ERROR_SHARING_VIOLATION and ERROR_LOCK_VIOLATION, what a file open
+// in another program (typically a PDF viewer) fails with
+#[cfg(windows)]
+const LOCKED_FILE_OS_ERRORS: &[i32] = &[32, 33];
+#[cfg(not(windows))]
+const LOCKED_FILE_OS_ERRORS: &[i32] = &[];
+
+fn is_locked_file_error(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::ResourceBusy
+        || error
+            .raw_os_error()
+            .is_some_and(|code| LOCKED_FILE_OS_ERRORS.contains(&code))
 }
 
-#[tauri::command]
-async fn start_watch_folder(
-    app: AppHandle,
-    folder_path: String,
-    recursive: bool,
-) -> Result<String, String> {
-    let path = Path::new(&folder_path);
+// Failures of a file operation the user can do something about come back as
+// "file_locked: ..." and "read_only: ..." so the frontend can offer a retry
+// (the latter with clear_readonly); anything else as "Failed to <action>: ..."
+fn file_operation_error(action: &str, path: &Path, error: io::Error) -> String {
+    if is_locked_file_error(&error) {
+        invalid_argument(
+            "file_locked",
+            format!(
+                "{} is open in another program; close it there and try again",
+                path.display()
+            ),
+        )
+    } else if error.kind() == io::ErrorKind::PermissionDenied {
+        invalid_argument(
+            "read_only",
+            format!(
+                "{} is read-only or in a folder that can't be changed; retry with clear_readonly to remove the read-only attribute",
+                path.display()
+            ),
+        )
+    } else {
+        format!("Failed to {}: {}", action, error)
+    }
+}
 
-    if !path.exists() {
-        return Err(format!("Directory does not exist: {}", folder_path));
+// Makes the file writable for its owner. Nothing else is touched, so a file
+// that is locked or sits in a read-only folder still fails afterwards.
+fn clear_readonly(path: &Path) -> io::Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    if !permissions.readonly() {
+        return Ok(());
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        permissions.set_mode(permissions.mode() | 0o200);
+    }
+    #[cfg(not(unix))]
+    {
+        permissions.set_readonly(false);
     }
+    fs::set_permissions(path, permissions)
+}
 
-    if !path.is_dir() {
-        return Err(format!("Path is not a directory: {}", folder_path));
+// clear_readonly for a paper and the sidecars that go along with it
+fn clear_readonly_with_sidecars(path: &Path) -> Result<(), String> {
+    for file in std::iter::once(path.to_path_buf()).chain(existing_sidecars(path)) {
+        clear_readonly(&file)
+            .map_err(|e| file_operation_error("clear the read-only attribute", &file, e))?;
     }
+    Ok(())
+}
 
-    // Generate a unique ID for this watcher
-    let watch_id = uuid::Uuid::new_v4().to_string();
-    let watch_id_clone = watch_id.clone();
-    let folder_path_clone = folder_path.clone();
+fn skipped_result(reason: &str, paper: Option<ArxivPaperMetadata>) -> ArxivImportResult {
+    ArxivImportResult {
+        status: "skipped".to_string(),
+        reason: Some(reason.to_string()),
+        paper,
+        ..Default::default()
+    }
+}
 
-    let app_handle = app.clone();
-    let mode = if recursive {
-        RecursiveMode::Recursive
-    } else {
-        RecursiveMode::NonRecursive
-    };
+fn escape_bibtex(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        if matches!(ch, '{' | '}' | '%' | '&' | '_' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
 
-    let mut watcher = RecommendedWatcher::new(
-        move |res: Result<Event, notify::Error>| {
-            match res {
-                Ok(event) => {
-                    // Check if this is a file creation event
-                    if matches!(event.kind, EventKind::Create(_)) {
-                        for path in &event.paths {
-                            if path.extension()
-                                .map(|ext| ext.to_string_lossy().to_lowercase() == "pdf")
-                                .unwrap_or(false)
-                            {
-                                let _ = app_handle.emit(
-                                    "folder-changed",
-                                    serde_json::json!({
-                                        "watchId": watch_id_clone,
-                                        "folderPath": folder_path_clone,
-                                        "eventType": "created",
-                                        "filePath": path.to_string_lossy().to_string(),
-                                    }),
-                                );
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Watch error: {:?}", e);
-                }
+// BibTeX styles lowercase titles, so any word carrying capitals is braced to
+// keep acronyms and proper nouns intact.
+fn bibtex_title(title: &str) -> String {
+    compact_text(title)
+        .split(' ')
+        .map(|word| {
+            let escaped = escape_bibtex(word);
+            if word.chars().any(|ch| ch.is_uppercase()) {
+                format!("{{{}}}", escaped)
+            } else {
+                escaped
             }
-        },
-        Config::default(),
-    )
-    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-    watcher
-        .watch(path, mode)
-        .map_err(|e| format!("Failed to start watching: {}", e))?;
+fn bibtex_key_part(value: &str) -> String {
+    value
+        .chars()
+        .filter(|ch| ch.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
 
-    // Store the watcher
-    let mut watchers = WATCHERS.lock().unwrap();
-    if watchers.is_none() {
-        *watchers = Some(HashMap::new());
-    }
-    watchers
-        .as_mut()
-        .unwrap()
-        .insert(watch_id.clone(), watcher);
+fn bibtex_citation_key(paper: &ArxivPaperMetadata) -> String {
+    let last_name = paper
+        .authors
+        .first()
+        .and_then(|author| author.split_whitespace().last())
+        .map(bibtex_key_part)
+        .unwrap_or_default();
+    let year = paper.published.get(..4).unwrap_or_default();
+    let title_word = paper
+        .title
+        .split_whitespace()
+        .map(bibtex_key_part)
+        .find(|word| word.len() > 3)
+        .unwrap_or_default();
 
-    Ok(watch_id)
+    let key = format!("{}{}{}", last_name, year, title_word);
+    if key.is_empty() {
+        bibtex_key_part(&paper.arxiv_id)
+    } else {
+        key
+    }
 }
 
-#[tauri::command]
-fn stop_watch_folder(watch_id: String) -> Result<(), String> {
-    let mut watchers = WATCHERS.lock().unwrap();
+fn arxiv_bibtex_entry(paper: &ArxivPaperMetadata) -> String {
+    let entry_type = if paper.journal_ref.is_some() {
+        "article"
+    } else {
+        "misc"
+    };
 
-    if let Some(watchers_map) = watchers.as_mut() {
-        if watchers_map.remove(&watch_id).is_some() {
-            return Ok(());
-        }
+    let mut fields = vec![
+        ("title", bibtex_title(&paper.title)),
+        (
+            "author",
+            paper
+                .authors
+                .iter()
+                .map(|author| escape_bibtex(author))
+                .collect::<Vec<_>>()
+                .join(" and "),
+        ),
+    ];
+    if let Some(year) = paper.published.get(..4) {
+        fields.push(("year", year.to_string()));
+    }
+    fields.push(("eprint", paper.arxiv_id.clone()));
+    fields.push(("archivePrefix", "arXiv".to_string()));
+    if let Some(primary_category) = &paper.primary_category {
+        fields.push(("primaryClass", escape_bibtex(primary_category)));
+    }
+    if let Some(doi) = &paper.doi {
+        fields.push(("doi", escape_bibtex(doi)));
+    }
+    if let Some(journal_ref) = &paper.journal_ref {
+        fields.push(("journal", escape_bibtex(journal_ref)));
     }
+    fields.push(("url", paper.abs_url.clone()));
 
-    Err(format!("Watcher with ID {} not found", watch_id))
+    let body = fields
+        .iter()
+        .map(|(name, value)| format!("  {} = {{{}}}", name, value))
+        .collect::<Vec<_>>()
+        .join(",\n");
+
+    format!(
+        "@{}{{{},\n{}\n}}\n",
+        entry_type,
+        bibtex_citation_key(paper),
+        body
+    )
 }
 
-#[tauri::command]
-fn get_file_metadata(file_path: String) -> Result<FileMetadata, String> {
-    let path = Path::new(&file_path);
+// Quick-reference note next to the PDF; every field goes through compact_text
+// so stray newlines from the feed can't break the markdown structure
+fn arxiv_readme_markdown(paper: &ArxivPaperMetadata) -> String {
+    let id_with_version = format!("{}v{}", paper.arxiv_id, paper.version);
+    let mut lines = vec![format!("# {}", compact_text(&paper.title)), String::new()];
 
-    if !path.exists() {
-        return Err(format!("File does not exist: {}", file_path));
+    if !paper.authors.is_empty() {
+        let authors = paper
+            .authors
+            .iter()
+            .map(|author| compact_text(author))
+            .collect::<Vec<_>>()
+            .join(", ");
+        lines.push(format!("- **Authors:** {}", authors));
+    }
+    lines.push(format!(
+        "- **arXiv:** [{}]({}) ([PDF]({}))",
+        id_with_version, paper.abs_url, paper.pdf_url
+    ));
+    if let Some(published) = paper.published.get(..10) {
+        lines.push(format!("- **Published:** {}", published));
+    }
+    if let Some(updated) = paper.updated.get(..10) {
+        lines.push(format!("- **Updated:** {}", updated));
+    }
+    if !paper.categories.is_empty() {
+        lines.push(format!("- **Categories:** {}", paper.categories.join(", ")));
+    }
+    if let Some(doi) = &paper.doi {
+        lines.push(format!(
+            "- **DOI:** [{}](https://doi.org/{})",
+            compact_text(doi),
+            compact_text(doi)
+        ));
+    }
+    if let Some(journal_ref) = &paper.journal_ref {
+        lines.push(format!("- **Journal:** {}", compact_text(journal_ref)));
+    }
+    if let Some(comment) = &paper.comment {
+        lines.push(format!("- **Comment:** {}", compact_text(comment)));
     }
 
-    let metadata = path
-        .metadata()
-        .map_err(|e| format!("Failed to read file metadata: {}", e))?;
+    lines.push(String::new());
+    lines.push("## Abstract".to_string());
+    lines.push(String::new());
+    lines.push(compact_text(&paper.summary));
 
-    let name = path
-        .file_name()
-        .map(|n| n.to_string_lossy().to_string())
-        .unwrap_or_default();
+    lines.join("\n") + "\n"
+}
 
-    Ok(FileMetadata {
-        name,
-        path: file_path,
-        size: metadata.len(),
-        modified: metadata
-            .modified()
-            .ok()
-            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-            .map(|d| d.as_secs() as i64),
-    })
+// Like the .bib, the note is a convenience copy and never fails an import
+fn write_arxiv_readme(
+    paper: &ArxivPaperMetadata,
+    readme_path: &Path,
+    warnings: &mut Vec<String>,
+) -> Option<String> {
+    match atomic_write(readme_path, arxiv_readme_markdown(paper).as_bytes()) {
+        Ok(()) => Some(readme_path.to_string_lossy().to_string()),
+        Err(error) => {
+            error!("Failed to write readme file: {:?}", error);
+            warnings.push("readme_write_failed".to_string());
+            None
+        }
+    }
 }
 
-#[tauri::command]
-fn verify_files_exist(file_paths: Vec<String>) -> Vec<(String, bool)> {
-    file_paths
-        .iter()
-        .map(|path| {
-            let exists = Path::new(path).exists();
-            (path.clone(), exists)
-        })
+const ABSTRACT_WRAP_COLUMNS: usize = 100;
+
+// arXiv indents every line of the abstract and separates paragraphs with a
+// blank line; each paragraph comes back on one line
+fn abstract_paragraphs(summary: &str) -> Vec<String> {
+    summary
+        .split('\n')
+        .collect::<Vec<_>>()
+        .split(|line| line.trim().is_empty())
+        .map(|lines| compact_text(&lines.join(" ")))
+        .filter(|paragraph| !paragraph.is_empty())
         .collect()
 }
 
-#[tauri::command]
-fn rename_file(old_path: String, new_name: String) -> Result<String, String> {
-    let path = Path::new(&old_path);
+fn wrap_words(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
 
-    // Verify file exists
-    if !path.exists() {
-        return Err(format!("File does not exist: {}", old_path));
+fn arxiv_abstract_markdown(paper: &ArxivPaperMetadata) -> String {
+    let mut lines = vec![format!("# {}", compact_text(&paper.title)), String::new()];
+    if !paper.authors.is_empty() {
+        lines.push(paper.authors.join(", "));
+        lines.push(String::new());
     }
+    lines.push(paper.abs_url.clone());
 
-    if !path.is_file() {
-        return Err(format!("Path is not a file: {}", old_path));
+    // Papers loaded from a sidecar only have the collapsed summary
+    let paragraphs = if paper.summary_paragraphs.is_empty() {
+        vec![paper.summary.clone()]
+    } else {
+        paper.summary_paragraphs.clone()
+    };
+    for paragraph in paragraphs.iter().filter(|p| !p.is_empty()) {
+        lines.push(String::new());
+        lines.extend(wrap_words(paragraph, ABSTRACT_WRAP_COLUMNS));
     }
 
-    // Get parent directory and construct new path
-    let parent = path
-        .parent()
-        .ok_or_else(|| "Could not determine parent directory".to_string())?;
+    lines.join("\n") + "\n"
+}
 
-    // Get the file extension (preserve it)
-    let extension = path
-        .extension()
-        .map(|ext| ext.to_string_lossy().to_string());
+fn write_arxiv_abstract(
+    paper: &ArxivPaperMetadata,
+    abstract_path: &Path,
+    warnings: &mut Vec<String>,
+) -> Option<String> {
+    match atomic_write(abstract_path, arxiv_abstract_markdown(paper).as_bytes()) {
+        Ok(()) => Some(abstract_path.to_string_lossy().to_string()),
+        Err(error) => {
+            error!("Failed to write abstract file: {:?}", error);
+            warnings.push("abstract_write_failed".to_string());
+            None
+        }
+    }
+}
 
-    // Construct new filename with extension
-    let new_filename = if let Some(ext) = extension {
-        format!("{}.{}", new_name, ext)
-    } else {
-        new_name.clone()
+const ARXIV_API_BASE: &str = "https://export.arxiv.org";
+const ARXIV_BASE_URL: &str = "https://arxiv.org";
+// Both hosts serve the same API; when one is down the other usually isn't
+const ARXIV_API_HOSTS: &[&str] = &[ARXIV_API_BASE, ARXIV_BASE_URL];
+const ARXIV_SEARCH_MAX_RESULTS: u8 = 50;
+const ARXIV_ID_LIST_CHUNK: usize = 20;
+// arXiv asks API clients to leave about three seconds between requests
+const DEFAULT_ARXIV_REQUEST_INTERVAL_MS: u64 = 3000;
+
+// Mirrors and caching proxies must be plain HTTPS bases; anything else is
+// ignored in favour of the canonical host.
+fn resolve_base_url(value: Option<&str>, default: &str) -> String {
+    let candidate = match value.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(candidate) => candidate,
+        None => return default.to_string(),
     };
 
-    let new_path = parent.join(&new_filename);
+    parse_base_url(candidate).unwrap_or_else(|| {
+        warn!("Ignoring invalid arXiv base URL: {}", candidate);
+        default.to_string()
+    })
+}
 
-    // Check if new path already exists
-    if new_path.exists() {
-        return Err(format!(
-            "A file named '{}' already exists in this location",
-            new_filename
-        ));
+fn parse_base_url(candidate: &str) -> Option<String> {
+    match Url::parse(candidate) {
+        Ok(url)
+            if url.scheme() == "https"
+                && url.host_str().is_some()
+                && url.query().is_none()
+                && url.fragment().is_none() =>
+        {
+            Some(url.as_str().trim_end_matches('/').to_string())
+        }
+        _ => None,
     }
+}
 
-    // Perform the rename
-    std::fs::rename(&path, &new_path)
-        .map_err(|e| format!("Failed to rename file: {}", e))?;
+// When the last request went to arXiv. Held across the wait, so concurrent
+// imports queue up behind each other instead of all firing once it's free.
+static ARXIV_GATE: tokio::sync::Mutex<Option<Instant>> = tokio::sync::Mutex::const_new(None);
+// Requests queued on (or sleeping in) the gate, and imports in progress
+static ARXIV_WAITING: AtomicUsize = AtomicUsize::new(0);
+static ACTIVE_IMPORTS: AtomicUsize = AtomicUsize::new(0);
 
-    Ok(new_path.to_string_lossy().to_string())
+// Holds a counter up while alive, so it also comes back down when the
+// command's future is dropped halfway
+struct CounterGuard(&'static AtomicUsize);
+
+impl CounterGuard {
+    fn enter(counter: &'static AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Self(counter)
+    }
 }
 
-#[tauri::command]
-async fn import_arxiv_paper(
-    input_url_or_id: String,
-    target_dir: String,
-    conflict_policy: String,
-) -> Result<ArxivImportResult, String> {
-    if conflict_policy != "skip" {
-        return Ok(skipped_result("invalid_conflict_policy", None));
+impl Drop for CounterGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
     }
+}
 
-    let (base_id, requested_version) = match parse_arxiv_input(&input_url_or_id) {
-        Some(parsed) => parsed,
-        None => return Ok(skipped_result("invalid_link", None)),
-    };
+// Cancellation flags of the imports started with an import_id
+static IMPORT_CANCELS: Mutex<Option<HashMap<String, Arc<AtomicBool>>>> = Mutex::new(None);
 
-    let target = Path::new(&target_dir);
-    if target_dir.trim().is_empty() {
-        return Ok(skipped_result("write_failed", None));
+// An import's entry in IMPORT_CANCELS, removed again when the import ends.
+// Imports without an id get a flag nobody else can reach.
+struct ImportRegistration {
+    import_id: Option<String>,
+    flag: Arc<AtomicBool>,
+}
+
+impl ImportRegistration {
+    fn register(import_id: Option<String>) -> Result<Self, String> {
+        let flag = Arc::new(AtomicBool::new(false));
+        if let Some(id) = &import_id {
+            let mut imports = IMPORT_CANCELS.lock().unwrap();
+            let imports = imports.get_or_insert_with(HashMap::new);
+            if imports.contains_key(id) {
+                return Err(invalid_argument(
+                    "invalid_input",
+                    format!("Import id already in use: {}", id),
+                ));
+            }
+            imports.insert(id.clone(), flag.clone());
+        }
+        Ok(Self { import_id, flag })
     }
+}
 
-    if !target.exists() {
-        if let Err(error) = fs::create_dir_all(target) {
-            eprintln!("Failed to create target directory: {:?}", error);
-            return Ok(skipped_result("write_failed", None));
+impl Drop for ImportRegistration {
+    fn drop(&mut self) {
+        if let Some(id) = &self.import_id {
+            if let Some(imports) = IMPORT_CANCELS.lock().unwrap().as_mut() {
+                imports.remove(id);
+            }
         }
     }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImporterStatus {
+    pub waiting_requests: usize,
+    pub active_imports: usize,
+    pub arxiv_request_interval_ms: u64,
+}
+
+const IMPORTER_SETTINGS_FILE: &str = "importer_settings.json";
+const DEFAULT_USER_AGENT: &str = "DocFlow/0.1 arXiv importer";
+
+// HTTP settings for the importer, persisted in the app config dir. Missing
+// fields fall back to the defaults so older files keep loading.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImporterSettings {
+    // Proxy for every scheme. Without it reqwest picks up HTTPS_PROXY,
+    // HTTP_PROXY, ALL_PROXY and NO_PROXY from the environment.
+    pub proxy_url: Option<String>,
+    pub connect_timeout_secs: u64,
+    pub timeout_secs: u64,
+    pub user_agent: Option<String>,
+    // Minimum gap between any two requests to arXiv (API, PDFs, sources)
+    pub arxiv_request_interval_ms: u64,
+    // How new sidecars are written: "json", "yaml", "toml" or
+    // "markdown_frontmatter"
+    pub sidecar_format: String,
+    // HTTPS bases for the metadata API and for PDFs and sources; None means
+    // export.arxiv.org and arxiv.org. ArxivImportOptions can still override
+    // them per import.
+    pub arxiv_api_base: Option<String>,
+    pub arxiv_pdf_base: Option<String>,
+    // Default for ArxivImportOptions::write_abstract
+    pub write_abstract: bool,
+    // Folders delete_file_to_trash and delete_file_permanently may touch;
+    // with none configured they refuse everything
+    pub library_roots: Vec<String>,
+    // Longest file name, in UTF-8 bytes, that rename_file accepts and
+    // generated names are cut to
+    pub max_filename_bytes: usize,
+}
 
-    if !target.is_dir() {
-        return Ok(skipped_result("write_failed", None));
+impl Default for ImporterSettings {
+    fn default() -> Self {
+        Self {
+            proxy_url: None,
+            connect_timeout_secs: 15,
+            timeout_secs: 45,
+            user_agent: None,
+            arxiv_request_interval_ms: DEFAULT_ARXIV_REQUEST_INTERVAL_MS,
+            sidecar_format: "json".to_string(),
+            arxiv_api_base: None,
+            arxiv_pdf_base: None,
+            write_abstract: false,
+            library_roots: Vec::new(),
+            max_filename_bytes: importer::DEFAULT_MAX_FILENAME_BYTES,
+        }
     }
+}
+
+// One client per settings revision, so imports share its connection pool
+static IMPORTER: Mutex<Option<(ImporterSettings, Client)>> = Mutex::new(None);
+
+fn build_arxiv_client(settings: &ImporterSettings) -> reqwest::Result<Client> {
+    let user_agent = settings
+        .user_agent
+        .as_deref()
+        .map(str::trim)
+        .filter(|agent| !agent.is_empty())
+        .unwrap_or(DEFAULT_USER_AGENT);
+
+    let mut builder = Client::builder()
+        .connect_timeout(Duration::from_secs(settings.connect_timeout_secs))
+        .timeout(Duration::from_secs(settings.timeout_secs))
+        .user_agent(user_agent);
 
-    let client = match Client::builder()
-        .timeout(Duration::from_secs(45))
-        .user_agent("DocFlow/0.1 arXiv importer")
-        .build()
+    if let Some(proxy_url) = settings
+        .proxy_url
+        .as_deref()
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
     {
-        Ok(client) => client,
-        Err(error) => {
-            eprintln!("Failed to create reqwest client: {:?}", error);
-            return Ok(skipped_result("network_error", None));
+        builder = builder.proxy(reqwest::Proxy::all(proxy_url)?);
+    }
+
+    builder.build()
+}
+
+fn arxiv_client() -> reqwest::Result<Client> {
+    let mut importer = IMPORTER.lock().unwrap();
+    if let Some((_, client)) = importer.as_ref() {
+        return Ok(client.clone());
+    }
+
+    let settings = ImporterSettings::default();
+    let client = build_arxiv_client(&settings)?;
+    *importer = Some((settings, client.clone()));
+    Ok(client)
+}
+
+fn arxiv_request_interval_ms() -> u64 {
+    IMPORTER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|(settings, _)| settings.arxiv_request_interval_ms)
+        .unwrap_or(DEFAULT_ARXIV_REQUEST_INTERVAL_MS)
+}
+
+fn configured_base_url(select: fn(&ImporterSettings) -> Option<&String>, default: &str) -> String {
+    let configured = IMPORTER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|(settings, _)| select(settings).cloned());
+    resolve_base_url(configured.as_deref(), default)
+}
+
+fn arxiv_api_base() -> String {
+    configured_base_url(|settings| settings.arxiv_api_base.as_ref(), ARXIV_API_BASE)
+}
+
+fn arxiv_pdf_base() -> String {
+    configured_base_url(|settings| settings.arxiv_pdf_base.as_ref(), ARXIV_BASE_URL)
+}
+
+fn default_write_abstract() -> bool {
+    IMPORTER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|(settings, _)| settings.write_abstract)
+        .unwrap_or(false)
+}
+
+fn max_filename_bytes() -> usize {
+    IMPORTER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|(settings, _)| settings.max_filename_bytes)
+        .unwrap_or(importer::DEFAULT_MAX_FILENAME_BYTES)
+}
+
+fn configured_library_roots() -> Vec<PathBuf> {
+    IMPORTER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|(settings, _)| {
+            settings
+                .library_roots
+                .iter()
+                .map(|root| root.trim())
+                .filter(|root| !root.is_empty())
+                .filter_map(|root| fs::canonicalize(root).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn default_sidecar_format() -> SidecarFormat {
+    IMPORTER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|(settings, _)| SidecarFormat::parse(&settings.sidecar_format).ok())
+        .unwrap_or(SidecarFormat::Json)
+}
+
+async fn wait_for_arxiv_slot() {
+    let interval = Duration::from_millis(arxiv_request_interval_ms());
+
+    let _waiting = CounterGuard::enter(&ARXIV_WAITING);
+    let mut last_request = ARXIV_GATE.lock().await;
+    if let Some(elapsed) = last_request.map(|last| last.elapsed()) {
+        if elapsed < interval {
+            tokio::time::sleep(interval - elapsed).await;
         }
-    };
+    }
+    *last_request = Some(Instant::now());
+}
+
+const MIN_FILENAME_BYTES: usize = 64;
+
+fn apply_importer_settings(settings: ImporterSettings) -> Result<(), String> {
+    let client = check_importer_settings(&settings)?;
+    *IMPORTER.lock().unwrap() = Some((settings, client));
+    Ok(())
+}
+
+// Rejects settings that can't be used, and builds the client they describe
+fn check_importer_settings(settings: &ImporterSettings) -> Result<Client, String> {
+    if settings.connect_timeout_secs == 0 || settings.timeout_secs == 0 {
+        return Err("Timeouts must be at least one second".to_string());
+    }
+    // Generated names need room for an id and a title after the sidecar
+    // suffix reserve
+    if !(MIN_FILENAME_BYTES..=importer::DEFAULT_MAX_FILENAME_BYTES)
+        .contains(&settings.max_filename_bytes)
+    {
+        return Err(format!(
+            "max_filename_bytes must be between {} and {}",
+            MIN_FILENAME_BYTES,
+            importer::DEFAULT_MAX_FILENAME_BYTES
+        ));
+    }
+    SidecarFormat::parse(&settings.sidecar_format)?;
+    // Unlike per-import overrides, a saved base that resolve_base_url would
+    // quietly ignore is rejected
+    if let Some(base) = [&settings.arxiv_api_base, &settings.arxiv_pdf_base]
+        .into_iter()
+        .flatten()
+        .map(|base| base.trim())
+        .find(|base| !base.is_empty() && parse_base_url(base).is_none())
+    {
+        return Err(format!(
+            "arXiv base URLs must be plain HTTPS URLs: {}",
+            base
+        ));
+    }
+
+    build_arxiv_client(settings).map_err(|e| format!("Invalid importer settings: {}", e))
+}
+
+fn importer_settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(IMPORTER_SETTINGS_FILE))
+        .map_err(|e| format!("Failed to resolve config directory: {}", e))
+}
+
+fn load_importer_settings(app: &AppHandle) {
+    let settings = importer_settings_path(app)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(
+            |text| match serde_json::from_str::<ImporterSettings>(&text) {
+                Ok(settings) => Some(settings),
+                Err(error) => {
+                    warn!("Ignoring unreadable importer settings: {:?}", error);
+                    None
+                }
+            },
+        )
+        .unwrap_or_default();
+
+    if let Err(error) = apply_importer_settings(settings) {
+        warn!("Falling back to default importer settings: {}", error);
+    }
+}
 
-    let api_url = format!("https://export.arxiv.org/api/query?id_list={}", base_id);
-    let api_response = match client.get(api_url).send().await {
+struct FeedFailure {
+    reason: &'static str,
+    // The host couldn't be reached or answered 5xx, so another one might work
+    retryable: bool,
+}
+
+impl FeedFailure {
+    fn new(reason: &'static str, retryable: bool) -> Self {
+        Self { reason, retryable }
+    }
+}
+
+async fn fetch_arxiv_feed_at(client: &Client, url: &Url) -> Result<ArxivApiFeed, FeedFailure> {
+    wait_for_arxiv_slot().await;
+    debug!("GET {}", url);
+    let api_response = match client.get(url.clone()).send().await {
         Ok(response) => response,
         Err(error) => {
-            eprintln!("Failed to fetch arXiv metadata: {:?}", error);
-            return Ok(skipped_result("network_error", None));
+            warn!("Failed to fetch arXiv metadata: {:?}", error);
+            return Err(FeedFailure::new("network_error", true));
         }
     };
 
     if !api_response.status().is_success() {
-        eprintln!(
+        warn!(
             "arXiv metadata API returned non-success status: {}",
             api_response.status()
         );
-        return Ok(skipped_result("network_error", None));
+        return Err(FeedFailure::new(
+            "network_error",
+            api_response.status().is_server_error(),
+        ));
     }
 
     let feed_xml = match api_response.text().await {
         Ok(text) => text,
         Err(error) => {
-            eprintln!("Failed to read arXiv metadata response: {:?}", error);
-            return Ok(skipped_result("network_error", None));
+            warn!("Failed to read arXiv metadata response: {:?}", error);
+            return Err(FeedFailure::new("network_error", false));
         }
     };
 
-    let feed = match from_str::<ArxivApiFeed>(&feed_xml) {
-        Ok(parsed) => parsed,
-        Err(error) => {
-            eprintln!("Failed to parse arXiv metadata feed: {:?}", error);
-            return Ok(skipped_result("paper_not_found", None));
+    let mut feed = from_str::<ArxivApiFeed>(&feed_xml).map_err(|error| {
+        warn!("Failed to parse arXiv metadata feed: {:?}", error);
+        FeedFailure::new("paper_not_found", false)
+    })?;
+
+    // Dropped here so every caller sees the paper as missing rather than
+    // building metadata out of the error text
+    feed.entry.retain(|entry| {
+        if entry.is_error_marker() {
+            warn!(
+                "arXiv API reported an error: {}",
+                entry.summary.as_deref().unwrap_or("").trim()
+            );
         }
-    };
+        !entry.is_error_marker()
+    });
+    Ok(feed)
+}
 
-    let entry = match feed.entry.into_iter().next() {
-        Some(item) => item,
-        None => return Ok(skipped_result("paper_not_found", None)),
+// Queries "<api_base>/api/query". If that host is one of arXiv's own and
+// can't be reached or answers 5xx, the other one is tried once. Ok carries
+// the endpoint that answered; errors come back as the skip reason reported to
+// the frontend.
+async fn fetch_arxiv_feed(
+    client: &Client,
+    api_base: &str,
+    params: &[(&str, String)],
+) -> Result<(ArxivApiFeed, String), &'static str> {
+    let alternate = if ARXIV_API_HOSTS.contains(&api_base) {
+        ARXIV_API_HOSTS
+            .iter()
+            .copied()
+            .find(|host| *host != api_base)
+    } else {
+        None
     };
 
+    let mut last_reason = "network_error";
+    for base in std::iter::once(api_base).chain(alternate) {
+        let endpoint = format!("{}/api/query", base);
+        let url = Url::parse_with_params(&endpoint, params).map_err(|error| {
+            warn!("Failed to build arXiv API URL: {:?}", error);
+            "network_error"
+        })?;
+
+        match fetch_arxiv_feed_at(client, &url).await {
+            Ok(feed) => return Ok((feed, endpoint)),
+            Err(failure) if failure.retryable => {
+                warn!("arXiv API at {} is unavailable", base);
+                last_reason = failure.reason;
+            }
+            Err(failure) => return Err(failure.reason),
+        }
+    }
+    Err(last_reason)
+}
+
+// The one entry for base_id; the answer to an unknown id, or to one the API
+// rejected with an error entry, is paper_not_found
+async fn fetch_arxiv_entry(
+    client: &Client,
+    api_base: &str,
+    base_id: &str,
+) -> Result<(ArxivApiEntry, String), &'static str> {
+    let (feed, endpoint) =
+        fetch_arxiv_feed(client, api_base, &[("id_list", base_id.to_string())]).await?;
+    let entry = feed.entry.into_iter().next().ok_or("paper_not_found")?;
+    Ok((entry, endpoint))
+}
+
+fn entry_latest_version(entry: &ArxivApiEntry, base_id: &str) -> u32 {
     let mut latest_version = 1u32;
     if let Some(entry_id) = entry.id.as_deref() {
         if let Some((entry_base_id, entry_version)) = parse_arxiv_input(entry_id) {
@@ -509,157 +1411,6547 @@ async fn import_arxiv_paper(
             }
         }
     }
+    latest_version
+}
 
-    let version = requested_version.unwrap_or(latest_version.max(1));
-    let id_with_version = format!("{}v{}", base_id, version);
-    let abs_url = format!("https://arxiv.org/abs/{}", id_with_version);
-    let pdf_url = format!("https://arxiv.org/pdf/{}.pdf", id_with_version);
+// The API sends RFC 3339 ("2017-06-12T17:57:34Z"); a bare leading date is
+// still good enough for the year and month
+fn parse_arxiv_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.date_naive())
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok())
+}
 
-    let title = entry
-        .title
-        .as_deref()
-        .map(compact_text)
-        .filter(|value| !value.is_empty())
-        .unwrap_or_else(|| format!("arXiv {}", id_with_version));
-    let summary = entry
-        .summary
-        .as_deref()
-        .map(compact_text)
-        .unwrap_or_default();
-    let published = entry.published.unwrap_or_default();
-    let updated = entry.updated.unwrap_or_default();
-    let authors = entry
+const NAME_SUFFIXES: &[&str] = &["jr", "jr.", "sr", "sr.", "ii", "iii", "iv"];
+
+// The feed uses "Given Family", but a few entries come as "Family, Given";
+// those are turned around so the same person compares equal across papers.
+// Generational suffixes ("John Smith, Jr.") stay where they are.
+fn normalize_author_name(name: &str) -> String {
+    match name.split_once(", ") {
+        Some((family, given))
+            if !given.contains(',') && !NAME_SUFFIXES.contains(&given.to_lowercase().as_str()) =>
+        {
+            format!("{} {}", given, family)
+        }
+        _ => name.to_string(),
+    }
+}
+
+fn paper_from_entry(
+    entry: ArxivApiEntry,
+    base_id: &str,
+    version: u32,
+    pdf_base: &str,
+) -> ArxivPaperMetadata {
+    let version = version.max(1);
+    let id_with_version = format!("{}v{}", base_id, version);
+    let published = entry.published.as_deref().and_then(parse_arxiv_date);
+    // The feed describes the latest version, so only that one is withdrawn;
+    // earlier versions stay downloadable
+    let is_latest = version >= entry_latest_version(&entry, base_id);
+    let authors_detailed = entry
         .author
         .into_iter()
-        .filter_map(|author| author.name.map(|name| compact_text(&name)))
-        .filter(|name| !name.is_empty())
+        .filter_map(|author| {
+            let name = normalize_author_name(&non_empty_text(author.name)?);
+            let orcid = [&author.orcid, &author.uri]
+                .into_iter()
+                .flatten()
+                .find_map(|value| orcid::parse_orcid_id(value));
+            let affiliations = author
+                .affiliation
+                .into_iter()
+                .filter_map(|affiliation| non_empty_text(Some(affiliation)))
+                .collect::<Vec<_>>();
+            Some(ArxivAuthor {
+                name,
+                affiliation: Some(affiliations.join("; ")).filter(|value| !value.is_empty()),
+                orcid,
+                orcid_confidence: None,
+            })
+        })
         .collect::<Vec<_>>();
 
-    let paper = ArxivPaperMetadata {
-        arxiv_id: base_id.clone(),
+    ArxivPaperMetadata {
+        arxiv_id: base_id.to_string(),
         version,
-        title: title.clone(),
-        authors: authors.clone(),
-        summary: summary.clone(),
-        published: published.clone(),
-        updated: updated.clone(),
-        abs_url: abs_url.clone(),
-        pdf_url: pdf_url.clone(),
-    };
-
-    let safe_id = id_with_version.replace('/', "_");
-    let file_stem = format!("{}_{}", safe_id, sanitize_title_for_filename(&title));
-    let pdf_path = target.join(format!("{}.pdf", file_stem));
-    let metadata_path = target.join(format!("{}.metadata.json", file_stem));
+        title: entry
+            .title
+            .as_deref()
+            .map(compact_text)
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| format!("arXiv {}", id_with_version)),
+        authors: authors_detailed
+            .iter()
+            .map(|author| author.name.clone())
+            .collect(),
+        authors_detailed,
+        summary: entry
+            .summary
+            .as_deref()
+            .map(compact_text)
+            .unwrap_or_default(),
+        published_year: published.map(|date| date.year() as u32),
+        published_month: published.map(|date| date.month()),
+        published: entry.published.unwrap_or_default(),
+        updated: entry.updated.unwrap_or_default(),
+        abs_url: format!("{}/abs/{}", ARXIV_BASE_URL, id_with_version),
+        pdf_url: format!("{}/pdf/{}.pdf", pdf_base, id_with_version),
+        primary_category: entry
+            .primary_category
+            .and_then(|category| non_empty_text(category.term)),
+        categories: entry
+            .category
+            .into_iter()
+            .filter_map(|category| non_empty_text(category.term))
+            .collect(),
+        doi: non_empty_text(entry.doi),
+        journal_ref: non_empty_text(entry.journal_ref),
+        withdrawn: is_latest
+            && entry
+                .comment
+                .as_deref()
+                .map(is_withdrawal_comment)
+                .unwrap_or(false),
+        comment: non_empty_text(entry.comment),
+        summary_paragraphs: entry
+            .summary
+            .as_deref()
+            .map(abstract_paragraphs)
+            .unwrap_or_default(),
+    }
+}
 
-    if conflict_policy == "skip" && pdf_path.exists() {
-        return Ok(ArxivImportResult {
-            status: "skipped".to_string(),
-            reason: Some("file_exists".to_string()),
-            pdf_path: Some(pdf_path.to_string_lossy().to_string()),
-            pdf_size: None,
-            metadata_path: if metadata_path.exists() {
-                Some(metadata_path.to_string_lossy().to_string())
-            } else {
-                None
-            },
-            paper: Some(paper),
-        });
+async fn enrich_arxiv_authors(
+    client: &Client,
+    paper: &mut ArxivPaperMetadata,
+    warnings: &mut Vec<String>,
+) {
+    if !orcid::enrich_authors(client, &mut paper.authors_detailed, &paper.title).await {
+        warnings.push("orcid_lookup_failed".to_string());
     }
+}
 
-    let pdf_response = match client.get(&pdf_url).send().await {
-        Ok(response) => response,
+async fn enrich_arxiv_paper(
+    client: &Client,
+    paper: &ArxivPaperMetadata,
+    warnings: &mut Vec<String>,
+) -> Option<Enrichment> {
+    match semantic_scholar::fetch_enrichment(client, &paper.arxiv_id).await {
+        Ok(enrichment) => Some(enrichment),
         Err(error) => {
-            eprintln!("Failed to download arXiv PDF: {:?}", error);
-            return Ok(skipped_result("network_error", Some(paper)));
+            warn!(
+                "Semantic Scholar lookup for {} failed: {}",
+                paper.arxiv_id, error
+            );
+            warnings.push("enrichment_failed".to_string());
+            None
         }
-    };
-
-    if !pdf_response.status().is_success() {
-        let reason = if pdf_response.status().as_u16() == 404 {
-            "paper_not_found"
-        } else {
-            "network_error"
-        };
-        return Ok(skipped_result(reason, Some(paper)));
     }
+}
 
-    let pdf_bytes = match pdf_response.bytes().await {
-        Ok(bytes) => bytes,
-        Err(error) => {
-            eprintln!("Failed to read downloaded PDF bytes: {:?}", error);
-            return Ok(skipped_result("network_error", Some(paper)));
+// The feed has no withdrawn flag; the withdrawing version's comment says so,
+// e.g. "This paper has been withdrawn by the author due to ..." or
+// "This submission has been withdrawn by arXiv administrators ...". A comment
+// that merely mentions the word ("compares withdrawn and approved drugs")
+// doesn't count.
+static WITHDRAWAL_COMMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)^\W*(?:(?:this|the) (?:paper|submission|article|manuscript|preprint|version) (?:has been|is|was) withdrawn|(?:the )?authors? (?:has|have) withdrawn|(?:paper |submission )?withdrawn\b)",
+    )
+    .unwrap()
+});
+
+fn is_withdrawal_comment(comment: &str) -> bool {
+    WITHDRAWAL_COMMENT.is_match(comment)
+}
+
+// Withdrawn papers still get a sidecar so the record exists, just no PDF
+fn withdrawn_result(
+    paper: ArxivPaperMetadata,
+    metadata_path: &Path,
+    extra: SidecarFields,
+) -> ArxivImportResult {
+    let mut result = skipped_result("withdrawn", None);
+    if !metadata_path.exists() {
+        if let Err(error) = write_arxiv_sidecar(&paper, None, metadata_path, extra) {
+            error!("Failed to write metadata file: {:?}", error);
         }
-    };
+    }
+    if metadata_path.exists() {
+        result.metadata_path = Some(metadata_path.to_string_lossy().to_string());
+    }
+    result.paper = Some(paper);
+    result
+}
 
-    if let Err(error) = fs::write(&pdf_path, &pdf_bytes) {
-        eprintln!("Failed to write downloaded PDF: {:?}", error);
-        return Ok(skipped_result("write_failed", Some(paper)));
+type SidecarFields = serde_json::Map<String, serde_json::Value>;
+
+// Enough of an e-print's start for source_archive_suffix
+const SOURCE_SNIFF_BYTES: u64 = 64 * 1024;
+
+// Classifies an arXiv e-print body by magic bytes. Multi-file submissions come
+// as gzipped tarballs, single-file ones as a gzipped .tex, and PDF-only
+// submissions have no source at all (the PDF itself is served).
+fn source_archive_suffix(body: &[u8]) -> Option<&'static str> {
+    let is_tar = |header: &[u8]| header.len() >= 262 && &header[257..262] == b"ustar";
+
+    if body.starts_with(&[0x1f, 0x8b]) {
+        let mut header = Vec::with_capacity(512);
+        let _ = GzDecoder::new(body).take(512).read_to_end(&mut header);
+        if is_tar(&header) {
+            Some(".source.tar.gz")
+        } else {
+            Some(".source.tex.gz")
+        }
+    } else if is_tar(body) {
+        Some(".source.tar")
+    } else {
+        None
     }
+}
 
-    let metadata_json = serde_json::json!({
-        "source": "arxiv",
-        "arxiv_id": base_id,
-        "version": version,
-        "title": title,
-        "authors": authors,
-        "summary": summary,
-        "published": published,
-        "updated": updated,
-        "abs_url": abs_url,
-        "pdf_url": pdf_url,
-        "downloaded_at": unix_timestamp_string(),
-        "pdf_path": pdf_path.to_string_lossy().to_string()
-    });
+// What a primary download (format "source") is saved as, by archive kind:
+// source_archive_suffix without the ".source" infix
+const PRIMARY_SOURCE_SUFFIXES: &[&str] = &[".tar.gz", ".tex.gz", ".tar"];
 
-    if let Ok(metadata_text) = serde_json::to_string_pretty(&metadata_json) {
-        if let Err(error) = fs::write(&metadata_path, metadata_text) {
-            eprintln!("Failed to write metadata file: {:?}", error);
-            return Ok(skipped_result("write_failed", Some(paper)));
+fn existing_primary_source(dir: &Path, file_stem: &str) -> Option<PathBuf> {
+    PRIMARY_SOURCE_SUFFIXES
+        .iter()
+        .map(|suffix| dir.join(format!("{}{}", file_stem, suffix)))
+        .find(|path| path.exists())
+}
+
+// Where download_arxiv_source saves the e-print
+#[derive(Debug, Clone, Copy)]
+enum SourcePlacement {
+    // As "<stem>.source.tar.gz" next to the imported PDF, replacing an older one
+    BesidePdf,
+    // As the import itself ("<stem>.tar.gz"); there is no PDF to tell it apart
+    // from, so it drops the ".source" infix. A file already there is never
+    // replaced.
+    Primary,
+}
+
+// Errors are warning codes; a missing source never fails the import. The body
+// is fetched under a neutral name first since its suffix depends on what
+// arXiv actually serves.
+async fn download_arxiv_source(
+    downloads: &DownloadManager,
+    cancel: &Arc<AtomicBool>,
+    pdf_base: &str,
+    id_with_version: &str,
+    target: &Path,
+    file_stem: &str,
+    placement: SourcePlacement,
+) -> Result<(PathBuf, u64), &'static str> {
+    let staging_path = target.join(format!("{}.source.download", file_stem));
+    let info = downloads
+        .download(DownloadRequest {
+            url: format!("{}/e-print/{}", pdf_base, id_with_version),
+            destination: staging_path.clone(),
+            provenance: Some(serde_json::json!({
+                "importer": "arxiv",
+                "arxiv_id": id_with_version,
+                "kind": "source",
+            })),
+            cancel: Some(cancel.clone()),
+            expect_pdf: false,
+        })
+        .await;
+
+    match (info.state, info.reason.as_deref()) {
+        (DownloadState::Done, _) => {}
+        (DownloadState::Failed, Some("http_error")) => {
+            warn!("arXiv e-print returned status {:?}", info.http_status);
+            return Err("source_unavailable");
+        }
+        (DownloadState::Failed, Some("write_failed")) => {
+            error!("Failed to write arXiv source: {:?}", info.error);
+            return Err("source_write_failed");
         }
+        (DownloadState::Cancelled, _) => return Err("source_cancelled"),
+        _ => {
+            warn!("Failed to download arXiv source: {:?}", info.error);
+            return Err("source_network_error");
+        }
+    }
+
+    let mut header = Vec::new();
+    let sniffed = fs::File::open(&staging_path)
+        .and_then(|file| file.take(SOURCE_SNIFF_BYTES).read_to_end(&mut header));
+    let suffix = match sniffed.ok().and_then(|_| source_archive_suffix(&header)) {
+        Some(suffix) => suffix,
+        None => {
+            let _ = fs::remove_file(&staging_path);
+            return Err("source_unavailable");
+        }
+    };
+
+    let (suffix, replace) = match placement {
+        SourcePlacement::BesidePdf => (suffix, true),
+        SourcePlacement::Primary => (suffix.strip_prefix(".source").unwrap_or(suffix), false),
+    };
+    let source_path = target.join(format!("{}{}", file_stem, suffix));
+    let placed = if replace {
+        fs::rename(&staging_path, &source_path)
     } else {
-        return Ok(skipped_result("write_failed", Some(paper)));
+        relocate_file(&staging_path, &source_path)
+    };
+    if let Err(error) = placed {
+        let _ = fs::remove_file(&staging_path);
+        if error.kind() == io::ErrorKind::AlreadyExists {
+            return Err("source_exists");
+        }
+        error!("Failed to write arXiv source: {:?}", error);
+        return Err("source_write_failed");
     }
 
-    Ok(ArxivImportResult {
-        status: "downloaded".to_string(),
-        reason: None,
-        pdf_path: Some(pdf_path.to_string_lossy().to_string()),
-        pdf_size: Some(pdf_bytes.len() as u64),
-        metadata_path: Some(metadata_path.to_string_lossy().to_string()),
-        paper: Some(paper),
-    })
+    Ok((source_path, info.bytes_downloaded))
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FileMetadata {
-    pub name: String,
-    pub path: String,
-    pub size: u64,
-    pub modified: Option<i64>,
+fn write_arxiv_sidecar(
+    paper: &ArxivPaperMetadata,
+    pdf_path: Option<&Path>,
+    metadata_path: &Path,
+    extra: SidecarFields,
+) -> Result<(), String> {
+    importer::write_sidecar(paper, "arxiv", pdf_path, metadata_path, extra)
 }
 
-#[tauri::command]
-fn greet(name: &str) -> String {
-    format!("Hello, {}! You've been greeted from Rust!", name)
+fn is_pdf_path(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase() == "pdf")
+        .unwrap_or(false)
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_fs::init())
-        .invoke_handler(tauri::generate_handler![
-            greet,
-            scan_directory_for_pdfs,
-            start_watch_folder,
-            stop_watch_folder,
-            get_file_metadata,
-            verify_files_exist,
-            rename_file,
-            import_arxiv_paper
-        ])
+// size and modified (unix seconds) come along so the frontend doesn't need a
+// get_file_metadata round-trip per event; null if the file vanished already.
+// PDFs written with atomic_write arrive by rename from a dot-prefixed .tmp
+// name, so a rewrite is reported as "created" for its final path (the temp
+// file is never a PDF path) and not as "modified", which is left to writes
+// in place; a file renamed within the folder gives a "removed" for the old
+// path and a "created" for the new one.
+fn emit_pdf_created(app: &AppHandle, watch_id: &str, folder_path: &str, path: &Path) {
+    emit_pdf_written(app, watch_id, folder_path, path, "created");
+}
+
+// "created" or "modified", with the same payload
+fn emit_pdf_written(
+    app: &AppHandle,
+    watch_id: &str,
+    folder_path: &str,
+    path: &Path,
+    event_type: &str,
+) {
+    let metadata = fs::metadata(path).ok();
+    let _ = app.emit(
+        "folder-changed",
+        serde_json::json!({
+            "watchId": watch_id,
+            "folderPath": folder_path,
+            "eventType": event_type,
+            "filePath": display_string(path),
+            "size": metadata.as_ref().map(|m| m.len()),
+            "modified": metadata
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64),
+        }),
+    );
+}
+
+// See note_app_removal
+fn emit_pdf_removed(app: &AppHandle, watch_id: &str, folder_path: &str, path: &Path) {
+    let _ = app.emit(
+        "folder-changed",
+        serde_json::json!({
+            "watchId": watch_id,
+            "folderPath": folder_path,
+            "eventType": "removed",
+            "filePath": display_string(path),
+            "initiatedByApp": removed_by_app(path),
+        }),
+    );
+}
+
+fn watch_new_subfolder(app: &AppHandle, watch_id: &str, folder_path: &str, new_dir: &Path) {
+    let new_dir = fs::canonicalize(new_dir).unwrap_or_else(|_| new_dir.to_path_buf());
+    {
+        let mut watchers = WATCHERS.lock().unwrap();
+        let Some(watcher) = watchers
+            .as_mut()
+            .and_then(|watchers| watchers.get_mut(watch_id))
+        else {
+            // Stopped in the meantime
+            return;
+        };
+        // One folder can be reported more than once (created, then renamed
+        // into place); its files were already walked the first time
+        if watcher.subfolders.contains(&new_dir) {
+            return;
+        }
+        // A folder inside one watched here is covered by that recursive watch
+        if !watcher
+            .subfolders
+            .iter()
+            .any(|watched| new_dir.starts_with(watched))
+        {
+            if let Err(e) = watcher.watcher.watch(&new_dir, RecursiveMode::Recursive) {
+                error!("Failed to watch new folder {:?}: {:?}", new_dir, e);
+            }
+        }
+        watcher.subfolders.insert(new_dir.clone());
+    }
+
+    for entry in WalkDir::new(&new_dir).into_iter().filter_map(|e| e.ok()) {
+        if entry.file_type().is_file() && is_pdf_path(entry.path()) {
+            emit_pdf_created(app, watch_id, folder_path, entry.path());
+        }
+    }
+}
+
+// Reports the PDFs already in the folder as "created" events, on another
+// thread so the watch is registered first. The walk starts after the watch
+// does, so nothing added in between is missed, though such a file can come
+// through twice (live and in the snapshot).
+fn emit_initial_snapshot(app: &AppHandle, watch_id: &str, folder_path: &str, recursive: bool) {
+    let app = app.clone();
+    let watch_id = watch_id.to_string();
+    let folder_path = folder_path.to_string();
+    std::thread::spawn(
+        move || match walk_pdfs(&folder_path, recursive, 0, false, None) {
+            Ok(scan) => {
+                for file in &scan.files {
+                    emit_pdf_created(&app, &watch_id, &folder_path, Path::new(&file.path));
+                }
+            }
+            Err(error) => warn!(
+                "Failed to scan {} for the initial snapshot: {}",
+                folder_path, error
+            ),
+        },
+    );
+}
+
+// A created or modified PDF is reported once its size has held still for
+// stable_ms, checked every poll_interval_ms (start_watch_folder can override
+// both)
+const DEFAULT_STABLE_MS: u64 = 1000;
+const DEFAULT_POLL_INTERVAL_MS: u64 = 250;
+// Reported anyway after this, so a stalled copy doesn't hold a thread forever
+const MAX_SETTLE_WAIT: Duration = Duration::from_secs(600);
+
+// Blocks until the file's size stops changing; false if it disappeared
+fn wait_for_stable_size(path: &Path, poll_interval: Duration, stable_for: Duration) -> bool {
+    let started = Instant::now();
+    let mut last_size = None;
+    let mut unchanged_since = Instant::now();
+
+    while started.elapsed() < MAX_SETTLE_WAIT {
+        let size = match fs::metadata(path) {
+            Ok(metadata) => metadata.len(),
+            Err(_) => return false,
+        };
+        if last_size != Some(size) {
+            last_size = Some(size);
+            unchanged_since = Instant::now();
+        } else if unchanged_since.elapsed() >= stable_for {
+            return true;
+        }
+        std::thread::sleep(poll_interval);
+    }
+
+    warn!("{} was still changing, reporting it anyway", path.display());
+    path.exists()
+}
+
+// PDFs of each watch waiting for their size to settle, with the event they
+// will be reported as. A copy in progress fires a stream of modify events;
+// they all fold into the one pending report.
+static SETTLING: Mutex<Option<HashMap<(String, PathBuf), &'static str>>> = Mutex::new(None);
+
+// Reports `path` as `event_type` once wait_for_stable_size says it's done
+fn emit_once_settled(
+    app: &AppHandle,
+    watch_id: &str,
+    folder_path: &str,
+    path: &Path,
+    event_type: &'static str,
+    poll_interval: Duration,
+    stable_for: Duration,
+) {
+    let key = (watch_id.to_string(), path.to_path_buf());
+    {
+        let mut settling = SETTLING.lock().unwrap();
+        let settling = settling.get_or_insert_with(HashMap::new);
+        if let Some(pending) = settling.get_mut(&key) {
+            // Created and then written to is still just created
+            if event_type == "created" {
+                *pending = event_type;
+            }
+            return;
+        }
+        settling.insert(key.clone(), event_type);
+    }
+
+    let app = app.clone();
+    let folder_path = folder_path.to_string();
+    std::thread::spawn(move || {
+        let stable = wait_for_stable_size(&key.1, poll_interval, stable_for);
+        let event_type = SETTLING
+            .lock()
+            .unwrap()
+            .as_mut()
+            .and_then(|settling| settling.remove(&key))
+            .unwrap_or(event_type);
+        if stable {
+            emit_pdf_written(&app, &key.0, &folder_path, &key.1, event_type);
+        }
+    });
+}
+
+// The watch can't recover from these: the folder (or the OS watch on it) is
+// gone, e.g. after the drive was unmounted
+fn is_fatal_watch_error(error: &notify::Error, folder_path: &str) -> bool {
+    matches!(
+        error.kind,
+        notify::ErrorKind::PathNotFound | notify::ErrorKind::WatchNotFound
+    ) || !Path::new(folder_path).is_dir()
+}
+
+fn report_watcher_error(app: &AppHandle, watch_id: &str, folder_path: &str, error: &notify::Error) {
+    let fatal = is_fatal_watch_error(error, folder_path);
+    if fatal {
+        if let Some(watchers) = WATCHERS.lock().unwrap().as_mut() {
+            watchers.remove(watch_id);
+        }
+    }
+
+    let _ = app.emit(
+        "watcher-error",
+        serde_json::json!({
+            "watchId": watch_id,
+            "folderPath": folder_path,
+            "message": error.to_string(),
+            "fatal": fatal,
+        }),
+    );
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatcherInfo {
+    pub watch_id: String,
+    pub folder_path: String,
+    pub recursive: bool,
+    // Set for start_watch_file watchers; folder_path is then its folder
+    pub file_path: Option<String>,
+}
+
+struct ActiveWatcher {
+    watcher: RecommendedWatcher,
+    // Canonical, so different spellings of one folder compare equal
+    folder_path: PathBuf,
+    recursive: bool,
+    // The one file a start_watch_file watcher reports on
+    file_path: Option<PathBuf>,
+    // Canonical subfolders created after a recursive watch started, which
+    // watch_new_subfolder has already handled
+    subfolders: HashSet<PathBuf>,
+}
+
+// A removed subfolder can be created again, and then needs a watch of its own
+fn forget_subfolder(watch_id: &str, removed: &Path) {
+    if let Some(watcher) = WATCHERS
+        .lock()
+        .unwrap()
+        .as_mut()
+        .and_then(|watchers| watchers.get_mut(watch_id))
+    {
+        // Gone, so it can't be canonicalized; its parent still can
+        let removed = removed
+            .parent()
+            .and_then(|parent| fs::canonicalize(parent).ok())
+            .zip(removed.file_name())
+            .map(|(parent, name)| parent.join(name))
+            .unwrap_or_else(|| removed.to_path_buf());
+        watcher
+            .subfolders
+            .retain(|watched| !watched.starts_with(&removed));
+    }
+}
+
+// Store active watchers
+static WATCHERS: Mutex<Option<HashMap<String, ActiveWatcher>>> = Mutex::new(None);
+
+// Full walks kept between scan_directory_paged calls, keyed by request params,
+// with when each was last used
+static SCAN_CACHE: Mutex<Option<HashMap<String, (Instant, ScanResult)>>> = Mutex::new(None);
+// Walks of big libraries are large; the least recently paged one goes first
+const MAX_CACHED_SCANS: usize = 8;
+
+// How deep WalkDir goes: just the folder itself when not recursive, and no
+// limit for max_depth 0
+fn walk_depth(recursive: bool, max_depth: usize) -> usize {
+    match (recursive, max_depth) {
+        (false, _) => 1,
+        (true, 0) => usize::MAX,
+        (true, depth) => depth,
+    }
+}
+
+// Time budget for walk_pdfs, checked between entries. Files found and errors
+// hit so far are mirrored into `found` and `errors` too, for a caller that
+// stops waiting because the walk is stuck inside one call on a dead mount.
+struct ScanDeadline {
+    at: Instant,
+    timeout_secs: u64,
+    found: Mutex<Vec<PdfFile>>,
+    errors: Mutex<Vec<String>>,
+}
+
+// How long past the deadline a scan may take to stop on its own before the
+// caller gives up on it
+const SCAN_TIMEOUT_GRACE: Duration = Duration::from_secs(1);
+
+fn timeout_message(deadline: &ScanDeadline) -> String {
+    format!(
+        "timeout: scan stopped after {}s, results are partial",
+        deadline.timeout_secs
+    )
+}
+
+// Absolute, with "." and ".." resolved and (on Windows) only backslashes, so
+// paths work as stable keys. Symlinks are left alone: a link is an entry of
+// its own, see canonical_path for the file it points to. Like canonical_path
+// it fails when nothing is at `path`.
+fn normalized_path(path: &Path) -> io::Result<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in std::path::absolute(path)?.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    fs::symlink_metadata(os_path(&normalized))?;
+    Ok(nfc_path(display_path(&normalized)))
+}
+
+// normalized_path with symlinks resolved too. dunce drops Windows' \\?\
+// prefix whenever the plain form names the same file; display_path drops it
+// for the long paths where it can't.
+fn canonical_path(path: &Path) -> io::Result<PathBuf> {
+    dunce::canonicalize(path).map(|canonical| nfc_path(display_path(&canonical)))
+}
+
+// Longest path the legacy Win32 API takes (MAX_PATH less the terminating
+// NUL); past it calls fail with "os error 3" unless the path is written in
+// the \\?\ extended-length form
+#[cfg(windows)]
+const LEGACY_MAX_PATH: usize = 259;
+
+// The spelling of `path` to hand to the OS. On Windows an absolute path past
+// LEGACY_MAX_PATH (counted in UTF-16 units, as Windows does) gets the \\?\
+// prefix, see extended_path. Everywhere else it is unchanged.
+fn os_path(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStrExt;
+
+        if path.as_os_str().encode_wide().count() > LEGACY_MAX_PATH {
+            if let Some(extended) = extended_path(path) {
+                return extended;
+            }
+        }
+    }
+    path.to_path_buf()
+}
+
+// The root to walk: files below a short folder can still be past
+// LEGACY_MAX_PATH, so on Windows walks always start from the \\?\ form and
+// every entry inherits it
+fn walk_root(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    if let Some(extended) = extended_path(path) {
+        return extended;
+    }
+    path.to_path_buf()
+}
+
+// `path` with the \\?\ prefix (\\?\UNC\ for network shares), or None when
+// it has one already. That form is taken literally, so the path is made
+// absolute, with "." and ".." resolved and only backslashes, first.
+#[cfg(windows)]
+fn extended_path(path: &Path) -> Option<PathBuf> {
+    let text = path.to_str()?;
+    if text.starts_with(r"\\?\") {
+        return None;
+    }
+    let absolute = std::path::absolute(path).ok()?;
+    let absolute = absolute.to_str()?.replace('/', r"\");
+    Some(PathBuf::from(match absolute.strip_prefix(r"\\") {
+        Some(share) => format!(r"\\?\UNC\{}", share),
+        None => format!(r"\\?\{}", absolute),
+    }))
+}
+
+// The reverse of os_path, for paths shown to the user, returned to the
+// frontend or recorded in sidecars; commands put the prefix back as needed
+fn display_path(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    if let Some(text) = path.to_str() {
+        if let Some(share) = text.strip_prefix(r"\\?\UNC\") {
+            return PathBuf::from(format!(r"\\{}", share));
+        }
+        if let Some(local) = text.strip_prefix(r"\\?\") {
+            return PathBuf::from(local);
+        }
+    }
+    path.to_path_buf()
+}
+
+fn display_string(path: &Path) -> String {
+    display_path(path).to_string_lossy().to_string()
+}
+
+fn nfc(text: &str) -> String {
+    text.nfc().collect()
+}
+
+fn nfd(text: &str) -> String {
+    text.nfd().collect()
+}
+
+// Files synced from macOS keep NFD names ("Schro\u{308}dinger.pdf") while the
+// frontend stores what it was given, usually NFC. Where the filesystem opens
+// both spellings as the same file (macOS) the NFC one is used; elsewhere only
+// the spelling on disk opens the file, so it is kept.
+fn nfc_path(path: PathBuf) -> PathBuf {
+    let Some(text) = path.to_str() else {
+        return path;
+    };
+    let composed = PathBuf::from(nfc(text));
+    if composed != path && is_same_file(&composed, &path) {
+        composed
+    } else {
+        path
+    }
+}
+
+fn walk_pdfs(
+    dir_path: &str,
+    recursive: bool,
+    max_depth: usize,
+    follow_symlinks: bool,
+    deadline: Option<&ScanDeadline>,
+) -> Result<ScanResult, String> {
+    walk_pdfs_with_error_paths(dir_path, recursive, max_depth, follow_symlinks, deadline)
+        .map(|(scan, _)| scan)
+}
+
+// walk_pdfs, plus the path each of the scan's errors is about
+fn walk_pdfs_with_error_paths(
+    dir_path: &str,
+    recursive: bool,
+    max_depth: usize,
+    follow_symlinks: bool,
+    deadline: Option<&ScanDeadline>,
+) -> Result<(ScanResult, Vec<PathBuf>), String> {
+    let path = Path::new(dir_path);
+
+    if !path.exists() {
+        return Err(format!("Directory does not exist: {}", dir_path));
+    }
+
+    if !path.is_dir() {
+        return Err(format!("Path is not a directory: {}", dir_path));
+    }
+
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    // Mirrored into the deadline like the files; the timeout error is added
+    // after the walk, and by the caller when it gives up waiting
+    let mut record_error = |failed: PathBuf, message: String| {
+        if let Some(deadline) = deadline {
+            deadline.errors.lock().unwrap().push(message.clone());
+        }
+        errors.push((failed, message));
+    };
+
+    let walker = WalkDir::new(walk_root(path)).max_depth(walk_depth(recursive, max_depth));
+
+    // Two links to the same collection would list it twice, and a link back
+    // up the tree would never end, so each real directory is walked once
+    let mut visited_dirs = HashSet::new();
+    let mut entries = walker.follow_links(follow_symlinks).into_iter();
+    let mut timed_out = false;
+
+    while let Some(entry) = entries.next() {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline.at) {
+            timed_out = true;
+            break;
+        }
+
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                let failed = e.path().unwrap_or(path).to_path_buf();
+                let message = match e.loop_ancestor() {
+                    Some(ancestor) => format!(
+                        "Skipped symlink cycle at {} (points back to {})",
+                        failed.display(),
+                        ancestor.display()
+                    ),
+                    None => format!("Failed to read {}: {}", failed.display(), e),
+                };
+                record_error(failed, message);
+                continue;
+            }
+        };
+        let entry_path = entry.path();
+
+        if follow_symlinks && entry.file_type().is_dir() {
+            let canonical =
+                fs::canonicalize(entry_path).unwrap_or_else(|_| entry_path.to_path_buf());
+            if !visited_dirs.insert(canonical) {
+                record_error(
+                    entry_path.to_path_buf(),
+                    format!(
+                        "Skipped {}: directory already scanned through another link",
+                        entry_path.display()
+                    ),
+                );
+                entries.skip_current_dir();
+                continue;
+            }
+        }
+
+        if entry_path.is_file() {
+            if let Some(extension) = entry_path.extension() {
+                if extension.to_string_lossy().to_lowercase() == "pdf" {
+                    match entry_path.metadata() {
+                        Ok(metadata) => {
+                            let path = match normalized_path(entry_path) {
+                                Ok(normalized) => normalized,
+                                Err(e) => {
+                                    record_error(
+                                        entry_path.to_path_buf(),
+                                        format!(
+                                            "Failed to normalize path {}: {}",
+                                            entry_path.display(),
+                                            e
+                                        ),
+                                    );
+                                    entry_path.to_path_buf()
+                                }
+                            };
+                            let file = PdfFile {
+                                // Spelled like the path, see nfc_path
+                                name: path
+                                    .file_name()
+                                    .map(|n| n.to_string_lossy().to_string())
+                                    .unwrap_or_default(),
+                                path: path.to_string_lossy().to_string(),
+                                size: metadata.len(),
+                                is_symlink: entry.path_is_symlink(),
+                                link_target: link_target(entry.path_is_symlink(), entry_path),
+                                file_id: file_id(entry_path, &metadata),
+                            };
+                            if let Some(deadline) = deadline {
+                                deadline.found.lock().unwrap().push(file.clone());
+                            }
+                            files.push(file);
+                        }
+                        Err(e) => {
+                            record_error(
+                                entry_path.to_path_buf(),
+                                format!(
+                                    "Failed to read metadata for {}: {}",
+                                    entry_path.display(),
+                                    e
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(deadline) = deadline.filter(|_| timed_out) {
+        errors.push((path.to_path_buf(), timeout_message(deadline)));
+    }
+
+    // Sort files by name
+    files.sort_by_key(|file| file.name.to_lowercase());
+
+    let error_count = errors.len();
+    let (error_paths, errors) = errors.into_iter().unzip();
+    Ok((
+        ScanResult {
+            total_count: files.len(),
+            error_count,
+            errors,
+            files,
+            has_more: false,
+            timed_out,
+        },
+        error_paths,
+    ))
+}
+
+// max_depth 0 means unlimited; it's ignored unless recursive. With
+// timeout_secs the walk runs on its own thread and, once over budget, the
+// files found so far come back with timed_out set and a "timeout: ..." error.
+#[tauri::command]
+async fn scan_directory_for_pdfs(
+    dir_path: String,
+    recursive: bool,
+    max_depth: usize,
+    follow_symlinks: Option<bool>,
+    timeout_secs: Option<u64>,
+) -> Result<ScanResult, String> {
+    let follow_symlinks = follow_symlinks.unwrap_or(false);
+    let Some(timeout_secs) = timeout_secs else {
+        return tauri::async_runtime::spawn_blocking(move || {
+            walk_pdfs(&dir_path, recursive, max_depth, follow_symlinks, None)
+        })
+        .await
+        .map_err(|e| format!("Failed to scan directory: {}", e))?;
+    };
+
+    let budget = Duration::from_secs(timeout_secs);
+    let deadline = Arc::new(ScanDeadline {
+        at: Instant::now() + budget,
+        timeout_secs,
+        found: Mutex::new(Vec::new()),
+        errors: Mutex::new(Vec::new()),
+    });
+
+    // A plain thread rather than the blocking pool: if it hangs on the
+    // mount it is left behind, and it shouldn't hold a pool slot meanwhile
+    let (done, result) = tokio::sync::oneshot::channel();
+    let worker_deadline = deadline.clone();
+    std::thread::spawn(move || {
+        let _ = done.send(walk_pdfs(
+            &dir_path,
+            recursive,
+            max_depth,
+            follow_symlinks,
+            Some(&worker_deadline),
+        ));
+    });
+
+    match tokio::time::timeout(budget + SCAN_TIMEOUT_GRACE, result).await {
+        Ok(Ok(scan)) => scan,
+        Ok(Err(_)) => Err("Failed to scan directory: scan thread exited".to_string()),
+        Err(_) => {
+            let mut files = deadline.found.lock().unwrap().clone();
+            files.sort_by_key(|file| file.name.to_lowercase());
+            let mut errors = deadline.errors.lock().unwrap().clone();
+            errors.push(timeout_message(&deadline));
+            Ok(ScanResult {
+                total_count: files.len(),
+                error_count: errors.len(),
+                errors,
+                files,
+                has_more: false,
+                timed_out: true,
+            })
+        }
+    }
+}
+
+#[tauri::command]
+async fn scan_directory_paged(
+    dir_path: String,
+    recursive: bool,
+    max_depth: usize,
+    offset: usize,
+    limit: usize,
+    follow_symlinks: Option<bool>,
+) -> Result<ScanResult, String> {
+    let follow_symlinks = follow_symlinks.unwrap_or(false);
+    let cache_key = format!(
+        "{}|{}|{}|{}",
+        dir_path, recursive, max_depth, follow_symlinks
+    );
+
+    let page = move |scan: &ScanResult| {
+        let start = offset.min(scan.files.len());
+        let end = start.saturating_add(limit).min(scan.files.len());
+        ScanResult {
+            files: scan.files[start..end].to_vec(),
+            total_count: scan.total_count,
+            error_count: scan.error_count,
+            errors: scan.errors.clone(),
+            has_more: end < scan.files.len(),
+            timed_out: scan.timed_out,
+        }
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        // The first page always re-walks so a fresh listing picks up changes
+        // on disk; later pages are served from the walk cached by that first
+        // call
+        if offset > 0 {
+            let mut cache = SCAN_CACHE.lock().unwrap();
+            if let Some((used, scan)) = cache.as_mut().and_then(|scans| scans.get_mut(&cache_key)) {
+                *used = Instant::now();
+                return Ok(page(scan));
+            }
+        }
+
+        // Walked without the lock, so pages of other scans aren't held up
+        let scan = walk_pdfs(&dir_path, recursive, max_depth, follow_symlinks, None)?;
+        let result = page(&scan);
+
+        let mut cache = SCAN_CACHE.lock().unwrap();
+        let scans = cache.get_or_insert_with(HashMap::new);
+        scans.insert(cache_key, (Instant::now(), scan));
+        while scans.len() > MAX_CACHED_SCANS {
+            let oldest = scans
+                .iter()
+                .min_by_key(|(_, (used, _))| *used)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => scans.remove(&key),
+                None => break,
+            };
+        }
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("Failed to scan directory: {}", e))?
+}
+
+const MIB: u64 = 1024 * 1024;
+// Upper bounds (exclusive) of the histogram buckets; the last one is open
+const SIZE_BUCKETS: &[(&str, Option<u64>)] = &[
+    ("<1MB", Some(MIB)),
+    ("1-10MB", Some(10 * MIB)),
+    (">10MB", None),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeBucket {
+    pub label: String,
+    pub max_bytes: Option<u64>,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryStats {
+    pub total_files: usize,
+    pub total_bytes: u64,
+    // PDFs with a metadata sidecar next to them, in any format
+    pub with_metadata: usize,
+    pub size_histogram: Vec<SizeBucket>,
+    pub error_count: usize,
+}
+
+#[tauri::command]
+async fn library_stats(dir_path: String, recursive: bool) -> Result<LibraryStats, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let scan = walk_pdfs(&dir_path, recursive, usize::MAX, false, None)?;
+
+        // One listing per folder instead of probing every sidecar suffix for
+        // every PDF
+        let mut sidecar_stems = HashMap::<PathBuf, HashSet<String>>::new();
+        let mut with_metadata = 0;
+        for file in &scan.files {
+            let path = Path::new(&file.path);
+            let dir = path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+            let stems = sidecar_stems.entry(dir).or_insert_with_key(|dir| {
+                fs::read_dir(dir)
+                    .map(|entries| {
+                        entries
+                            .filter_map(|entry| entry.ok())
+                            .filter_map(|entry| sidecar_stem(&entry.path()))
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            });
+            let stem = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if stems.contains(&stem) {
+                with_metadata += 1;
+            }
+        }
+
+        let mut size_histogram = SIZE_BUCKETS
+            .iter()
+            .map(|(label, max_bytes)| SizeBucket {
+                label: label.to_string(),
+                max_bytes: *max_bytes,
+                count: 0,
+            })
+            .collect::<Vec<_>>();
+        for file in &scan.files {
+            if let Some(bucket) = size_histogram
+                .iter_mut()
+                .find(|bucket| bucket.max_bytes.is_none_or(|max| file.size < max))
+            {
+                bucket.count += 1;
+            }
+        }
+
+        Ok(LibraryStats {
+            total_files: scan.files.len(),
+            total_bytes: scan.files.iter().map(|file| file.size).sum(),
+            with_metadata,
+            size_histogram,
+            error_count: scan.error_count,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to compute library stats: {}", e))?
+}
+
+#[tauri::command]
+async fn start_watch_folder(
+    app: AppHandle,
+    folder_path: String,
+    recursive: bool,
+    replace: Option<bool>,
+    stable_ms: Option<u64>,
+    poll_interval_ms: Option<u64>,
+    emit_initial: Option<bool>,
+) -> Result<String, String> {
+    let path = &os_path(Path::new(&folder_path));
+    let emit_initial = emit_initial.unwrap_or(false);
+    let stable_for = Duration::from_millis(stable_ms.unwrap_or(DEFAULT_STABLE_MS));
+    let poll_interval = Duration::from_millis(poll_interval_ms.unwrap_or(DEFAULT_POLL_INTERVAL_MS));
+    if poll_interval.is_zero() {
+        return Err("poll_interval_ms must be at least 1".to_string());
+    }
+
+    if !path.exists() {
+        return Err(format!("Directory does not exist: {}", folder_path));
+    }
+
+    if !path.is_dir() {
+        return Err(format!("Path is not a directory: {}", folder_path));
+    }
+
+    let canonical_path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+    // Held until the new watcher is stored, so two concurrent calls can't
+    // both register the same folder
+    let mut watchers = WATCHERS.lock().unwrap();
+    let watchers_map = watchers.get_or_insert_with(HashMap::new);
+
+    // A second watcher on the same folder would emit every event twice
+    let existing_id = watchers_map
+        .iter()
+        .find(|(_, active)| {
+            active.file_path.is_none()
+                && active.folder_path == canonical_path
+                && active.recursive == recursive
+        })
+        .map(|(id, _)| id.clone());
+    if let Some(existing_id) = existing_id.as_ref() {
+        if !replace.unwrap_or(false) {
+            if emit_initial {
+                emit_initial_snapshot(&app, existing_id, &folder_path, recursive);
+            }
+            return Ok(existing_id.clone());
+        }
+    }
+
+    // Generate a unique ID for this watcher
+    let watch_id = uuid::Uuid::new_v4().to_string();
+    let watch_id_clone = watch_id.clone();
+    let folder_path_clone = folder_path.clone();
+
+    let app_handle = app.clone();
+    let mode = if recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+
+    let mut watcher = RecommendedWatcher::new(
+        move |res: Result<Event, notify::Error>| {
+            match res {
+                Ok(event) => {
+                    // Files either get created in place or renamed into the
+                    // folder (moves, and our own atomic writes)
+                    let arrived = match event.kind {
+                        EventKind::Create(_) => true,
+                        EventKind::Modify(ModifyKind::Name(mode)) => {
+                            matches!(mode, RenameMode::To | RenameMode::Both | RenameMode::Any)
+                        }
+                        _ => false,
+                    };
+                    // Content changes, as opposed to renames and metadata
+                    let written = matches!(
+                        event.kind,
+                        EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Any)
+                    );
+                    // Trashing shows up as a rename away on some platforms
+                    let departed: &[PathBuf] = match event.kind {
+                        EventKind::Remove(_)
+                        | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => &event.paths,
+                        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                            &event.paths[..event.paths.len().min(1)]
+                        }
+                        _ => &[],
+                    };
+                    for path in departed {
+                        if is_pdf_path(path) {
+                            emit_pdf_removed(
+                                &app_handle,
+                                &watch_id_clone,
+                                &folder_path_clone,
+                                path,
+                            );
+                        } else if recursive {
+                            // Possibly a subfolder; WATCHERS is locked on
+                            // another thread for the same reason watch() is
+                            let watch_id = watch_id_clone.clone();
+                            let removed = path.clone();
+                            std::thread::spawn(move || forget_subfolder(&watch_id, &removed));
+                        }
+                    }
+                    if written {
+                        for path in event.paths.iter().filter(|path| is_pdf_path(path)) {
+                            emit_once_settled(
+                                &app_handle,
+                                &watch_id_clone,
+                                &folder_path_clone,
+                                path,
+                                "modified",
+                                poll_interval,
+                                stable_for,
+                            );
+                        }
+                    }
+                    if arrived {
+                        for path in event.paths.iter().filter(|path| path.exists()) {
+                            if is_pdf_path(path) {
+                                // A created file may still be being written, so
+                                // it's only reported once its size settles
+                                emit_once_settled(
+                                    &app_handle,
+                                    &watch_id_clone,
+                                    &folder_path_clone,
+                                    path,
+                                    "created",
+                                    poll_interval,
+                                    stable_for,
+                                );
+                            } else if recursive && path.is_dir() {
+                                // Not every backend picks up directories created
+                                // after the watch started, and a folder dropped in
+                                // whole can fill up before any watch exists on it.
+                                // watch() round-trips through the event loop that
+                                // is running this handler, so it must happen on
+                                // another thread.
+                                let app_handle = app_handle.clone();
+                                let watch_id = watch_id_clone.clone();
+                                let folder_path = folder_path_clone.clone();
+                                let new_dir = path.clone();
+                                std::thread::spawn(move || {
+                                    watch_new_subfolder(
+                                        &app_handle,
+                                        &watch_id,
+                                        &folder_path,
+                                        &new_dir,
+                                    );
+                                });
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!("Watch error: {:?}", e);
+                    // Removing the watcher drops it, which must not happen on
+                    // its own event loop thread
+                    let app_handle = app_handle.clone();
+                    let watch_id = watch_id_clone.clone();
+                    let folder_path = folder_path_clone.clone();
+                    std::thread::spawn(move || {
+                        report_watcher_error(&app_handle, &watch_id, &folder_path, &e);
+                    });
+                }
+            }
+        },
+        Config::default(),
+    )
+    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+    watcher
+        .watch(path, mode)
+        .map_err(|e| format!("Failed to start watching: {}", e))?;
+
+    // The replaced watcher only goes once its successor is running, so a
+    // failed replace leaves the folder watched as before
+    if let Some(existing_id) = existing_id {
+        watchers_map.remove(&existing_id);
+    }
+
+    // Store the watcher
+    watchers_map.insert(
+        watch_id.clone(),
+        ActiveWatcher {
+            watcher,
+            folder_path: canonical_path,
+            recursive,
+            file_path: None,
+            subfolders: HashSet::new(),
+        },
+    );
+
+    if emit_initial {
+        emit_initial_snapshot(&app, &watch_id, &folder_path, recursive);
+    }
+
+    Ok(watch_id)
+}
+
+fn emit_file_changed(
+    app: &AppHandle,
+    watch_id: &str,
+    file_path: &Path,
+    event_type: &str,
+    new_path: Option<&Path>,
+) {
+    let _ = app.emit(
+        "file-changed",
+        serde_json::json!({
+            "watchId": watch_id,
+            "filePath": display_string(file_path),
+            "eventType": event_type,
+            "newPath": new_path.map(display_string),
+        }),
+    );
+}
+
+// Watches one file and emits "file-changed" with eventType "modified",
+// "deleted" or "renamed" (with newPath when the platform reports where it
+// went). The folder is watched rather than the file, since editors that save
+// by writing a temp file and renaming it over the original replace the inode
+// a file watch would be on; such a save comes through as "modified". Stopped
+// with stop_watch_folder like any other watcher.
+#[tauri::command]
+fn start_watch_file(app: AppHandle, file_path: String) -> Result<String, String> {
+    let path = &os_path(Path::new(&file_path));
+    if !path.is_file() {
+        return Err(format!("File does not exist: {}", file_path));
+    }
+
+    let canonical_file = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let folder = canonical_file
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| format!("File has no parent folder: {}", file_path))?;
+
+    let mut watchers = WATCHERS.lock().unwrap();
+    let watchers_map = watchers.get_or_insert_with(HashMap::new);
+    if let Some(existing_id) = watchers_map
+        .iter()
+        .find(|(_, active)| active.file_path.as_ref() == Some(&canonical_file))
+        .map(|(id, _)| id.clone())
+    {
+        return Ok(existing_id);
+    }
+
+    let watch_id = uuid::Uuid::new_v4().to_string();
+    let watch_id_clone = watch_id.clone();
+    let watched_file = canonical_file.clone();
+    let folder_string = folder.to_string_lossy().to_string();
+    let app_handle = app.clone();
+
+    let mut watcher = RecommendedWatcher::new(
+        move |res: Result<Event, notify::Error>| match res {
+            Ok(event) => {
+                let is_file = |index: usize| event.paths.get(index) == Some(&watched_file);
+                let (event_type, new_path) = match event.kind {
+                    EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if is_file(0) => {
+                        ("renamed", event.paths.get(1).map(PathBuf::as_path))
+                    }
+                    // Something else was renamed over it
+                    EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if is_file(1) => {
+                        ("modified", None)
+                    }
+                    EventKind::Modify(ModifyKind::Name(RenameMode::From)) if is_file(0) => {
+                        ("renamed", None)
+                    }
+                    EventKind::Modify(ModifyKind::Name(RenameMode::To)) | EventKind::Create(_)
+                        if is_file(0) =>
+                    {
+                        ("modified", None)
+                    }
+                    // Renames of other files; metadata-only changes (permissions,
+                    // timestamps) leave the content as it was
+                    EventKind::Modify(ModifyKind::Name(_) | ModifyKind::Metadata(_)) => return,
+                    EventKind::Modify(_) if is_file(0) => ("modified", None),
+                    EventKind::Remove(_) if is_file(0) => ("deleted", None),
+                    _ => return,
+                };
+                emit_file_changed(
+                    &app_handle,
+                    &watch_id_clone,
+                    &watched_file,
+                    event_type,
+                    new_path,
+                );
+            }
+            Err(e) => {
+                error!("Watch error: {:?}", e);
+                let app_handle = app_handle.clone();
+                let watch_id = watch_id_clone.clone();
+                let folder_path = folder_string.clone();
+                std::thread::spawn(move || {
+                    report_watcher_error(&app_handle, &watch_id, &folder_path, &e);
+                });
+            }
+        },
+        Config::default(),
+    )
+    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+    watcher
+        .watch(&folder, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to start watching: {}", e))?;
+
+    watchers_map.insert(
+        watch_id.clone(),
+        ActiveWatcher {
+            watcher,
+            folder_path: folder,
+            recursive: false,
+            file_path: Some(canonical_file),
+            subfolders: HashSet::new(),
+        },
+    );
+
+    Ok(watch_id)
+}
+
+#[tauri::command]
+fn list_active_watchers() -> Vec<WatcherInfo> {
+    WATCHERS
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|watchers| {
+            watchers
+                .iter()
+                .map(|(watch_id, active)| WatcherInfo {
+                    watch_id: watch_id.clone(),
+                    folder_path: active.folder_path.to_string_lossy().to_string(),
+                    recursive: active.recursive,
+                    file_path: active
+                        .file_path
+                        .as_ref()
+                        .map(|file_path| file_path.to_string_lossy().to_string()),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+fn stop_watch_folder(watch_id: String) -> Result<(), String> {
+    let mut watchers = WATCHERS.lock().unwrap();
+
+    if let Some(watchers_map) = watchers.as_mut() {
+        if watchers_map.remove(&watch_id).is_some() {
+            return Ok(());
+        }
+    }
+
+    Err(format!("Watcher with ID {} not found", watch_id))
+}
+
+// What a symlinked entry points to, as reported in link_target
+fn link_target(is_symlink: bool, path: &Path) -> Option<String> {
+    is_symlink
+        .then(|| canonical_path(path).ok())
+        .flatten()
+        .map(|target| target.to_string_lossy().to_string())
+}
+
+fn unix_seconds(time: io::Result<SystemTime>) -> Option<i64> {
+    time.ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+}
+
+fn file_metadata(file_path: &str) -> Result<FileMetadata, String> {
+    let path = &os_path(Path::new(file_path));
+
+    if !path.exists() {
+        return Err(format!("File does not exist: {}", file_path));
+    }
+
+    // The link's own entry says whether it is one; the rest describes the
+    // file it points to
+    let is_symlink = fs::symlink_metadata(path)
+        .map(|metadata| metadata.is_symlink())
+        .unwrap_or(false);
+    let metadata = path
+        .metadata()
+        .map_err(|e| format!("Failed to read file metadata: {}", e))?;
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let (pdf_version, pdfa_conformance) = if metadata.is_file() {
+        (pdf::header_version(path), pdf::pdfa_conformance(path))
+    } else {
+        (None, None)
+    };
+
+    Ok(FileMetadata {
+        name,
+        path: normalized_path(path)
+            .map(|normalized| normalized.to_string_lossy().to_string())
+            .unwrap_or_else(|_| file_path.to_string()),
+        size: metadata.len(),
+        modified: unix_seconds(metadata.modified()),
+        created: unix_seconds(metadata.created()),
+        readonly: metadata.permissions().readonly(),
+        is_symlink,
+        link_target: link_target(is_symlink, path),
+        pdf_version,
+        pdfa_conformance,
+    })
+}
+
+#[tauri::command]
+fn get_file_metadata(file_path: String) -> Result<FileMetadata, String> {
+    file_metadata(&file_path)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileMetadataResult {
+    pub path: String,
+    pub metadata: Option<FileMetadata>,
+    pub error: Option<String>,
+}
+
+// get_file_metadata for many paths in one call, in input order, with each
+// failure reported on its own entry. Reads run in parallel within the local
+// concurrency budget.
+#[tauri::command]
+async fn get_files_metadata(
+    concurrency: tauri::State<'_, Concurrency>,
+    paths: Vec<String>,
+) -> Result<Vec<FileMetadataResult>, String> {
+    let permits = concurrency.acquire_up_to(Budget::Local, paths.len()).await;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(Vec::with_capacity(paths.len()));
+
+        std::thread::scope(|scope| {
+            for _ in 0..permits.num_permits().min(paths.len()) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(path) = paths.get(index) else {
+                        break;
+                    };
+                    let (metadata, error) = match file_metadata(path) {
+                        Ok(metadata) => (Some(metadata), None),
+                        Err(error) => (None, Some(error)),
+                    };
+                    results.lock().unwrap().push((
+                        index,
+                        FileMetadataResult {
+                            path: path.clone(),
+                            metadata,
+                            error,
+                        },
+                    ));
+                });
+            }
+        });
+
+        let mut results = results.into_inner().unwrap();
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    })
+    .await
+    .map_err(|e| format!("Failed to read file metadata: {}", e))
+}
+
+#[tauri::command]
+fn verify_files_exist(file_paths: Vec<String>) -> Vec<(String, bool)> {
+    file_paths
+        .iter()
+        .map(|path| {
+            // A stored NFC path still counts when the file on disk is
+            // spelled NFD, or the other way round
+            let exists = os_path(Path::new(path)).exists()
+                || os_path(Path::new(&nfc(path))).exists()
+                || os_path(Path::new(&nfd(path))).exists();
+            (path.clone(), exists)
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileStatus {
+    pub path: String,
+    pub exists: bool,
+    // Exists and could be opened for reading
+    pub accessible: bool,
+    // Why it isn't accessible: not_found, permission_denied,
+    // parent_unreachable (its folder is gone too, e.g. an unmounted drive),
+    // is_directory or broken_symlink
+    pub reason: Option<String>,
+    pub size: Option<u64>,
+    pub modified: Option<i64>,
+}
+
+fn file_status(file_path: &str) -> FileStatus {
+    let path = &os_path(Path::new(file_path));
+    let mut status = FileStatus {
+        path: file_path.to_string(),
+        exists: false,
+        accessible: false,
+        reason: None,
+        size: None,
+        modified: None,
+    };
+    let reason_for = |error: &io::Error| match error.kind() {
+        io::ErrorKind::PermissionDenied => "permission_denied",
+        _ if path
+            .parent()
+            .is_some_and(|parent| !parent.as_os_str().is_empty() && !parent.exists()) =>
+        {
+            "parent_unreachable"
+        }
+        _ => "not_found",
+    };
+
+    // The link itself first, so a dangling one isn't mistaken for nothing
+    let link_metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(error) => {
+            status.reason = Some(reason_for(&error).to_string());
+            return status;
+        }
+    };
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(error) if link_metadata.is_symlink() && error.kind() == io::ErrorKind::NotFound => {
+            status.reason = Some("broken_symlink".to_string());
+            return status;
+        }
+        Err(error) => {
+            status.reason = Some(reason_for(&error).to_string());
+            return status;
+        }
+    };
+
+    status.exists = true;
+    status.size = Some(metadata.len());
+    status.modified = unix_seconds(metadata.modified());
+
+    if metadata.is_dir() {
+        status.reason = Some("is_directory".to_string());
+        return status;
+    }
+    match fs::File::open(path) {
+        Ok(_) => status.accessible = true,
+        Err(error) if error.kind() == io::ErrorKind::PermissionDenied => {
+            status.reason = Some("permission_denied".to_string());
+        }
+        Err(error) => status.reason = Some(reason_for(&error).to_string()),
+    }
+
+    status
+}
+
+// Like verify_files_exist, but says why a file can't be used so the UI can
+// offer the right fix (relink, grant access, mount the drive)
+#[tauri::command]
+fn verify_files_detailed(file_paths: Vec<String>) -> Vec<FileStatus> {
+    file_paths.iter().map(|path| file_status(path)).collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MovedFileQuery {
+    pub original_path: String,
+    pub expected_size: Option<u64>,
+    // Hex SHA-256, as recorded in metadata sidecars
+    pub expected_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelinkCandidate {
+    pub path: String,
+    // 0.3 for the file name alone, 0.6 with the size and 1.0 with the hash
+    // too; 0.1 when the size is known to differ
+    pub confidence: f64,
+    pub size_matches: Option<bool>,
+    pub hash_matches: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MovedFileMatch {
+    pub original_path: String,
+    // Best first
+    pub candidates: Vec<RelinkCandidate>,
+}
+
+fn rank_relink_candidate(query: &MovedFileQuery, path: &Path) -> Option<RelinkCandidate> {
+    let size = fs::metadata(path).ok()?.len();
+    let size_matches = query.expected_size.map(|expected| expected == size);
+
+    // Hashing is only worth it for files that could still be the one
+    let hash_matches = match (&query.expected_hash, size_matches) {
+        (Some(expected), None | Some(true)) => Some(
+            checksum::sha256_file(path)
+                .map(|actual| actual.eq_ignore_ascii_case(expected.trim()))
+                .unwrap_or(false),
+        ),
+        _ => None,
+    };
+
+    let confidence = match (size_matches, hash_matches) {
+        // Same name, different content
+        (_, Some(false)) => return None,
+        (Some(false), _) => 0.1,
+        (_, Some(true)) => 1.0,
+        (Some(true), None) => 0.6,
+        (None, None) => 0.3,
+    };
+
+    Some(RelinkCandidate {
+        path: display_string(path),
+        confidence,
+        size_matches,
+        hash_matches,
+    })
+}
+
+// One walk over the search roots for every query, matching on file name
+// (ignoring case) and ranking by size and hash
+fn find_moved_files_in(queries: &[MovedFileQuery], search_roots: &[String]) -> Vec<MovedFileMatch> {
+    let mut wanted = HashMap::<String, Vec<usize>>::new();
+    for (index, query) in queries.iter().enumerate() {
+        if let Some(name) = Path::new(&query.original_path).file_name() {
+            wanted
+                .entry(name.to_string_lossy().to_lowercase())
+                .or_default()
+                .push(index);
+        }
+    }
+
+    let mut matches = queries
+        .iter()
+        .map(|query| MovedFileMatch {
+            original_path: query.original_path.clone(),
+            candidates: Vec::new(),
+        })
+        .collect::<Vec<_>>();
+    // Overlapping roots would otherwise report a file twice
+    let mut seen = HashSet::new();
+
+    for root in search_roots {
+        let root_path = os_path(Path::new(root));
+        if !root_path.is_dir() {
+            warn!("Skipping search root that is not a directory: {}", root);
+            continue;
+        }
+
+        for entry in WalkDir::new(walk_root(&root_path))
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            let Some(indices) = wanted.get(&name) else {
+                continue;
+            };
+            let canonical =
+                fs::canonicalize(entry.path()).unwrap_or_else(|_| entry.path().to_path_buf());
+            if !seen.insert(canonical) {
+                continue;
+            }
+
+            for &index in indices {
+                let original = os_path(Path::new(&queries[index].original_path));
+                if is_same_file(&original, entry.path()) {
+                    continue;
+                }
+                if let Some(candidate) = rank_relink_candidate(&queries[index], entry.path()) {
+                    matches[index].candidates.push(candidate);
+                }
+            }
+        }
+    }
+
+    for found in &mut matches {
+        found.candidates.sort_by(|a, b| {
+            b.confidence
+                .total_cmp(&a.confidence)
+                .then(a.path.cmp(&b.path))
+        });
+    }
+    matches
+}
+
+// Candidates for where a file that's gone from original_path went
+#[tauri::command]
+async fn find_moved_file(
+    original_path: String,
+    expected_size: Option<u64>,
+    expected_hash: Option<String>,
+    search_roots: Vec<String>,
+) -> Result<Vec<RelinkCandidate>, String> {
+    let query = MovedFileQuery {
+        original_path,
+        expected_size,
+        expected_hash,
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        find_moved_files_in(&[query], &search_roots)
+            .pop()
+            .map(|found| found.candidates)
+            .unwrap_or_default()
+    })
+    .await
+    .map_err(|e| format!("Failed to search for moved file: {}", e))
+}
+
+// find_moved_file for many entries at once, in input order
+#[tauri::command]
+async fn find_moved_files(
+    queries: Vec<MovedFileQuery>,
+    search_roots: Vec<String>,
+) -> Result<Vec<MovedFileMatch>, String> {
+    tauri::async_runtime::spawn_blocking(move || find_moved_files_in(&queries, &search_roots))
+        .await
+        .map_err(|e| format!("Failed to search for moved files: {}", e))
+}
+
+const COMPARE_CHUNK_BYTES: usize = 64 * 1024;
+
+fn compare_file_contents(path_a: &Path, path_b: &Path) -> io::Result<bool> {
+    let mut file_a = fs::File::open(path_a)?;
+    let mut file_b = fs::File::open(path_b)?;
+    let mut chunk_a = vec![0u8; COMPARE_CHUNK_BYTES];
+    let mut chunk_b = vec![0u8; COMPARE_CHUNK_BYTES];
+
+    loop {
+        let read = file_a.read(&mut chunk_a)?;
+        if read == 0 {
+            // Both ended together unless b grew since the size check
+            return Ok(file_b.read(&mut chunk_b[..1])? == 0);
+        }
+        match file_b.read_exact(&mut chunk_b[..read]) {
+            Ok(()) => {}
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+            Err(error) => return Err(error),
+        }
+        if chunk_a[..read] != chunk_b[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+// Byte-for-byte comparison, for confirming a pair before one of them is
+// deleted. Files of different sizes are never read.
+#[tauri::command]
+async fn files_identical(path_a: String, path_b: String) -> Result<bool, String> {
+    let (os_a, os_b) = (os_path(Path::new(&path_a)), os_path(Path::new(&path_b)));
+    let metadata_a = fs::metadata(&os_a).map_err(|_| format!("File does not exist: {}", path_a))?;
+    let metadata_b = fs::metadata(&os_b).map_err(|_| format!("File does not exist: {}", path_b))?;
+    for (path, metadata) in [(&path_a, &metadata_a), (&path_b, &metadata_b)] {
+        if !metadata.is_file() {
+            return Err(format!("Not a file: {}", path));
+        }
+    }
+    if metadata_a.len() != metadata_b.len() {
+        return Ok(false);
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        compare_file_contents(&os_a, &os_b).map_err(|e| format!("Failed to compare files: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Failed to compare files: {}", e))?
+}
+
+// The OS gets the canonical path of something that exists, never the raw
+// string from the frontend
+fn existing_file_for_os(file_path: &str) -> Result<PathBuf, String> {
+    let path = Path::new(file_path);
+    if file_path.trim().is_empty() || !path.exists() {
+        return Err(format!("File does not exist: {}", file_path));
+    }
+
+    fs::canonicalize(path).map_err(|e| format!("Failed to resolve path: {}", e))
+}
+
+// Opens the containing folder with the file selected. The opener plugin does
+// the platform work: explorer /select on Windows, a Finder reveal on macOS and
+// the FileManager1 D-Bus call on Linux, falling back to opening the folder.
+#[tauri::command]
+fn reveal_in_file_manager(file_path: String) -> Result<(), String> {
+    let path = existing_file_for_os(&file_path)?;
+
+    tauri_plugin_opener::reveal_item_in_dir(&path)
+        .map_err(|e| format!("Failed to reveal file: {}", e))
+}
+
+// Launches whatever the OS has registered for the file type, for reading a
+// PDF outside the app
+#[tauri::command]
+fn open_with_system_default(file_path: String) -> Result<(), String> {
+    let path = existing_file_for_os(&file_path)?;
+
+    tauri_plugin_opener::open_path(&path, None::<&str>)
+        .map_err(|e| format!("Failed to open file: {}", e))
+}
+
+// new_name is the new file stem; the file keeps its extension, so a name that
+// already ends in it ("paper.pdf" for a PDF) isn't doubled, and a file
+// without an extension is renamed to new_name exactly. Sidecars are renamed
+// along with it. If the name is taken, by a file or by a sidecar left at that
+// stem, on_conflict "error" (the default) fails and "auto_suffix" uses the
+// first free "<new_name>_N" instead; either way the final path is returned.
+// Renaming a file to its own name does nothing. A locked or read-only file
+// fails with file_locked or read_only (see file_operation_error);
+// clear_readonly makes the file and its sidecars writable first.
+#[tauri::command]
+fn rename_file(
+    old_path: String,
+    new_name: String,
+    on_conflict: Option<String>,
+    clear_readonly: Option<bool>,
+) -> Result<String, String> {
+    let on_conflict = on_conflict.unwrap_or_else(|| "error".to_string());
+    if !matches!(on_conflict.as_str(), "error" | "auto_suffix") {
+        return Err(invalid_argument(
+            "invalid_conflict_policy",
+            format!("Unsupported conflict policy: {}", on_conflict),
+        ));
+    }
+
+    let path = &os_path(Path::new(&old_path));
+
+    // Verify file exists
+    if !path.exists() {
+        return Err(format!("File does not exist: {}", old_path));
+    }
+
+    if !path.is_file() {
+        return Err(format!("Path is not a file: {}", old_path));
+    }
+
+    let new_name = new_name.trim();
+
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    let new_stem = match new_name.len().checked_sub(extension.len()) {
+        Some(split)
+            if !extension.is_empty()
+                && split > 0
+                && new_name.is_char_boundary(split)
+                && new_name[split..].eq_ignore_ascii_case(&extension) =>
+        {
+            &new_name[..split]
+        }
+        _ => new_name,
+    };
+    // Checked as the name the file will end up with, so a name that only
+    // works on this platform can't be synced somewhere it doesn't
+    if let Some(violation) =
+        importer::filename_violation(&format!("{}{}", new_stem, extension), max_filename_bytes())
+    {
+        return Err(invalid_argument(&violation.code, violation.message));
+    }
+
+    if clear_readonly.unwrap_or(false) {
+        clear_readonly_with_sidecars(path)?;
+    }
+
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let target = dir.join(format!("{}{}", new_stem, extension));
+    if target == *path {
+        return Ok(display_string(path));
+    }
+
+    // Only a change of case on a file system that ignores case, where the
+    // "taken" names are the file's own
+    let same_file = is_same_file(&target, path);
+    let taken = if same_file {
+        None
+    } else {
+        taken_at_stem(dir, new_stem, &extension)
+    };
+    if let (Some(taken), "error") = (&taken, on_conflict.as_str()) {
+        return Err(format!(
+            "A file named '{}' already exists in this location",
+            taken
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        ));
+    }
+
+    let first = Some(new_stem.to_string()).filter(|_| taken.is_none());
+    let suffixed = (on_conflict == "auto_suffix")
+        .then(|| {
+            (2..=MAX_RENAME_ATTEMPTS)
+                .map(|n| format!("{}_{}", new_stem, n))
+                .filter(|candidate| stem_is_free(dir, candidate, &extension))
+        })
+        .into_iter()
+        .flatten();
+    let (new_path, _, failed) = rename_paper_in_place(path, first.into_iter().chain(suffixed))?;
+    thumbnails::forget(path);
+    if !failed.is_empty() {
+        warn!(
+            "Renamed {} without some sidecars: {}",
+            old_path,
+            failed.join(", ")
+        );
+    }
+
+    Ok(display_string(&new_path))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilenameValidation {
+    pub valid: bool,
+    // See importer::filename_violation
+    pub code: Option<String>,
+    pub message: Option<String>,
+    // A corrected name that passes, when the name doesn't and one exists
+    pub suggestion: Option<String>,
+}
+
+// The checks rename_file applies, for live feedback while a name is typed.
+// max_bytes defaults to the max_filename_bytes importer setting.
+#[tauri::command]
+fn validate_filename(name: String, max_bytes: Option<usize>) -> FilenameValidation {
+    let max_bytes = max_bytes.unwrap_or_else(max_filename_bytes);
+    match importer::filename_violation(&name, max_bytes) {
+        None => FilenameValidation {
+            valid: true,
+            code: None,
+            message: None,
+            suggestion: None,
+        },
+        Some(violation) => {
+            let corrected = importer::correct_filename(&name, max_bytes);
+            FilenameValidation {
+                valid: false,
+                code: Some(violation.code),
+                message: Some(violation.message),
+                suggestion: Some(corrected).filter(|corrected| {
+                    importer::filename_violation(corrected, max_bytes).is_none()
+                }),
+            }
+        }
+    }
+}
+
+// Moves `from` to `to` by way of a temporary name, since where both spell the
+// same file (see nfc_path) renaming straight across may leave it unchanged
+fn rename_respelled(from: &Path, to: &Path) -> io::Result<()> {
+    let staging = from.with_file_name(format!(".renaming-{}", uuid::Uuid::new_v4()));
+    fs::rename(from, &staging)?;
+    relocate_file(&staging, to).inspect_err(|_| {
+        let _ = fs::rename(&staging, from);
+    })
+}
+
+// Renames a file whose name isn't in NFC (typically NFD, from macOS) to the
+// NFC spelling, sidecars included, and returns the new path. A name that is
+// already NFC is left alone; a different file holding the NFC name is an
+// error.
+#[tauri::command]
+fn normalize_filename(path: String) -> Result<String, String> {
+    let file = &os_path(Path::new(&path));
+    if !file.is_file() {
+        return Err(format!("File does not exist: {}", path));
+    }
+    let name = file
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("File name is not valid Unicode: {}", path))?;
+    let composed = nfc(name);
+    if composed == name {
+        return Ok(path);
+    }
+
+    let new_path = file.with_file_name(&composed);
+    if new_path.exists() && !is_same_file(&new_path, file) {
+        return Err(format!(
+            "A file named '{}' already exists in this location",
+            composed
+        ));
+    }
+
+    let sidecars = existing_sidecars(file);
+    rename_respelled(file, &new_path).map_err(|e| file_operation_error("rename file", file, e))?;
+
+    let stem = file_stem_string(file);
+    let new_stem = file_stem_string(&new_path);
+    for sidecar in sidecars {
+        let sidecar_name = sidecar
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let new_sidecar =
+            sidecar.with_file_name(format!("{}{}", new_stem, &sidecar_name[stem.len()..]));
+        if let Err(error) = rename_respelled(&sidecar, &new_sidecar) {
+            error!(
+                "Failed to rename sidecar {}: {:?}",
+                sidecar.display(),
+                error
+            );
+            continue;
+        }
+
+        if SidecarFormat::from_path(&new_sidecar).is_some() {
+            let updated = sidecar::read(&new_sidecar).and_then(|mut object| {
+                object.insert(
+                    "pdf_path".to_string(),
+                    serde_json::json!(display_string(&new_path)),
+                );
+                sidecar::write(&new_sidecar, object)
+            });
+            if let Err(error) = updated {
+                error!("Failed to update renamed metadata: {}", error);
+            }
+        }
+    }
+
+    Ok(display_string(&new_path))
+}
+
+// Creates `path` empty unless something is already there (false then). The
+// empty file holds the name until a rename replaces it, so a concurrent
+// writer can't be clobbered between checking the name and taking it.
+fn reserve_path(path: &Path) -> io::Result<bool> {
+    match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+    {
+        Ok(_) => Ok(true),
+        Err(error) if error.kind() == io::ErrorKind::AlreadyExists => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+// Renames a PDF within its folder to the first of `new_stems` that is free,
+// taking its sidecars along and pointing metadata sidecars at the new path.
+// Returns the new path, the renamed sidecars and the sidecars that couldn't
+// be renamed.
+fn rename_paper_in_place(
+    path: &Path,
+    new_stems: impl IntoIterator<Item = String>,
+) -> Result<(PathBuf, Vec<String>, Vec<String>), String> {
+    let stem = file_stem_string(path);
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut reserved = None;
+    let mut last_tried = None;
+    for candidate in new_stems {
+        let candidate_path = path.with_file_name(format!("{}{}", candidate, extension));
+        // A new case of the same name needs no placeholder, it's the file itself
+        if is_same_file(&candidate_path, path) {
+            reserved = Some((candidate, candidate_path, false));
+            break;
+        }
+        if reserve_path(&candidate_path).map_err(|e| format!("Failed to rename file: {}", e))? {
+            reserved = Some((candidate, candidate_path, true));
+            break;
+        }
+        last_tried = Some(candidate_path);
+    }
+    let Some((new_stem, new_path, placeholder)) = reserved else {
+        return Err(format!(
+            "A file named '{}' already exists in this location",
+            last_tried
+                .and_then(|path| path.file_name().map(|n| n.to_string_lossy().to_string()))
+                .unwrap_or_default()
+        ));
+    };
+
+    let sidecars = existing_sidecars(path);
+    if let Err(error) = fs::rename(path, &new_path) {
+        if placeholder {
+            let _ = fs::remove_file(&new_path);
+        }
+        return Err(file_operation_error("rename file", path, error));
+    }
+
+    let mut renamed = Vec::new();
+    let mut failed = Vec::new();
+    for sidecar in sidecars {
+        let name = sidecar
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let new_sidecar = sidecar.with_file_name(format!("{}{}", new_stem, &name[stem.len()..]));
+
+        let taken = new_sidecar.exists() && !is_same_file(&new_sidecar, &sidecar);
+        if taken || fs::rename(&sidecar, &new_sidecar).is_err() {
+            error!("Failed to rename sidecar {}", sidecar.display());
+            failed.push(display_string(&sidecar));
+            continue;
+        }
+
+        if SidecarFormat::from_path(&new_sidecar).is_some() {
+            let updated = sidecar::read(&new_sidecar).and_then(|mut object| {
+                object.insert(
+                    "pdf_path".to_string(),
+                    serde_json::json!(display_string(&new_path)),
+                );
+                sidecar::write(&new_sidecar, object)
+            });
+            if let Err(error) = updated {
+                error!("Failed to update renamed metadata: {}", error);
+            }
+        }
+
+        renamed.push(display_string(&new_sidecar));
+    }
+
+    Ok((new_path, renamed, failed))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchRenameItem {
+    pub old_path: String,
+    pub new_path: Option<String>,
+    // "planned" (dry run), "renamed", "unchanged", "collision" (dry run with
+    // collision policy abort) or "failed"
+    pub status: String,
+    pub sidecars: Vec<String>,
+    pub error: Option<String>,
+}
+
+// {title}, {first_author}, {year} and {arxiv_id} from whichever importer
+// wrote the sidecar; empty when there is none
+fn sidecar_template_values(pdf_path: &Path) -> [String; 4] {
+    let fields = sidecar::read_for_pdf(pdf_path).unwrap_or_default();
+    let text = |key: &str| match fields.get(key) {
+        Some(serde_json::Value::String(value)) => value.clone(),
+        Some(serde_json::Value::Number(value)) => value.to_string(),
+        _ => String::new(),
+    };
+
+    let first_author = fields
+        .get("authors")
+        .and_then(|authors| authors.get(0))
+        .and_then(|author| author.as_str().or_else(|| author.get("name")?.as_str()))
+        .and_then(|author| author.split_whitespace().last())
+        .unwrap_or_default()
+        .to_string();
+    let year = ["published_year", "year"]
+        .iter()
+        .map(|key| text(key))
+        .chain([text("published").chars().take(4).collect()])
+        .find(|year| !year.is_empty())
+        .unwrap_or_default();
+
+    [text("title"), first_author, year, text("arxiv_id")]
+}
+
+// Renames each file to `template` with {name} (current stem), {index} (1-based
+// position in `paths`), {date} (today, YYYY-MM-DD) and the sidecar
+// placeholders, each result going through sanitize_title_for_filename.
+// Targets already taken on disk or by an earlier file of the batch get
+// "<stem>_N" with collision_policy "auto_suffix" (the default); with "abort"
+// nothing is renamed and the call fails. dry_run only returns the plan.
+#[tauri::command]
+fn batch_rename_files(
+    paths: Vec<String>,
+    template: String,
+    dry_run: bool,
+    collision_policy: Option<String>,
+) -> Result<Vec<BatchRenameItem>, String> {
+    let collision_policy = collision_policy.unwrap_or_else(|| "auto_suffix".to_string());
+    if !matches!(collision_policy.as_str(), "auto_suffix" | "abort") {
+        return Err(invalid_argument(
+            "invalid_conflict_policy",
+            format!("Unsupported collision policy: {}", collision_policy),
+        ));
+    }
+
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+    // Paths the batch will occupy, starting with the files as they are
+    let mut claimed = paths.iter().map(PathBuf::from).collect::<HashSet<_>>();
+    let mut items = Vec::new();
+    let mut planned = Vec::new();
+    let mut collisions = Vec::new();
+
+    for (index, old_path) in paths.iter().enumerate() {
+        let path = Path::new(old_path);
+        let mut item = BatchRenameItem {
+            old_path: old_path.clone(),
+            new_path: None,
+            status: "failed".to_string(),
+            sidecars: Vec::new(),
+            error: None,
+        };
+        if !path.is_file() {
+            item.error = Some(format!("File does not exist: {}", old_path));
+            items.push(item);
+            continue;
+        }
+
+        let stem = file_stem_string(path);
+        let [title, first_author, year, arxiv_id] = sidecar_template_values(path);
+        let position = (index + 1).to_string();
+        let new_stem = importer::expand_filename_template(
+            &template,
+            &[
+                ("name", &stem),
+                ("index", &position),
+                ("date", &date),
+                ("title", &title),
+                ("first_author", &first_author),
+                ("year", &year),
+                ("arxiv_id", &arxiv_id),
+            ],
+        )?;
+
+        if new_stem == stem {
+            item.status = "unchanged".to_string();
+            item.new_path = Some(old_path.clone());
+            items.push(item);
+            continue;
+        }
+
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let extension = path
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_default();
+        let target_for = |stem: &str| dir.join(format!("{}{}", stem, extension));
+        let is_free = |stem: &str| {
+            !claimed.contains(&target_for(stem)) && stem_is_free(dir, stem, &extension)
+        };
+
+        let mut final_stem = new_stem.clone();
+        if !is_free(&new_stem) {
+            if collision_policy == "abort" {
+                collisions.push(target_for(&new_stem).to_string_lossy().to_string());
+                item.status = "collision".to_string();
+                item.new_path = Some(target_for(&new_stem).to_string_lossy().to_string());
+                items.push(item);
+                continue;
+            }
+            match (2..=MAX_RENAME_ATTEMPTS)
+                .map(|n| format!("{}_{}", new_stem, n))
+                .find(|candidate| is_free(candidate))
+            {
+                Some(candidate) => final_stem = candidate,
+                None => {
+                    item.error = Some(format!("No free file name for {}", new_stem));
+                    items.push(item);
+                    continue;
+                }
+            }
+        }
+
+        claimed.insert(target_for(&final_stem));
+        item.status = "planned".to_string();
+        item.new_path = Some(target_for(&final_stem).to_string_lossy().to_string());
+        planned.push((items.len(), final_stem));
+        items.push(item);
+    }
+
+    if dry_run {
+        return Ok(items);
+    }
+    if !collisions.is_empty() {
+        return Err(invalid_argument(
+            "name_collision",
+            format!("Renaming would overwrite {}", collisions.join(", ")),
+        ));
+    }
+
+    for (position, new_stem) in planned {
+        let item = &mut items[position];
+        match rename_paper_in_place(Path::new(&item.old_path), [new_stem]) {
+            Ok((new_path, sidecars, failed)) => {
+                item.status = "renamed".to_string();
+                item.new_path = Some(new_path.to_string_lossy().to_string());
+                item.sidecars = sidecars;
+                if !failed.is_empty() {
+                    item.error = Some(format!("Sidecars not renamed: {}", failed.join(", ")));
+                }
+            }
+            Err(error) => {
+                item.status = "failed".to_string();
+                item.error = Some(error);
+            }
+        }
+    }
+
+    Ok(items)
+}
+
+const IMPORT_HISTORY_FILE: &str = "import_history.jsonl";
+const DEFAULT_HISTORY_LIMIT: usize = 100;
+// Older lines are dropped once the file grows past this
+const MAX_HISTORY_ENTRIES: usize = 1000;
+
+// Serializes appends with the trim that may follow, which rewrites the file
+static IMPORT_HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+// One line of import_history.jsonl. Failed calls are recorded with status
+// "error" and the error message as the reason.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportHistoryEntry {
+    pub timestamp: String,
+    pub input: String,
+    pub arxiv_id: Option<String>,
+    // The paper's version and title, once its metadata was fetched; lines
+    // written before they were recorded have neither
+    #[serde(default)]
+    pub version: Option<u32>,
+    #[serde(default)]
+    pub title: Option<String>,
+    pub status: String,
+    pub reason: Option<String>,
+    pub pdf_path: Option<String>,
+    pub metadata_path: Option<String>,
+    pub bytes: Option<u64>,
+    pub duration_ms: u64,
+}
+
+fn import_history_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(IMPORT_HISTORY_FILE))
+        .map_err(|e| format!("Failed to resolve data directory: {}", e))
+}
+
+fn append_import_history(app: &AppHandle, entry: &ImportHistoryEntry) -> Result<(), String> {
+    append_history_entry(&import_history_path(app)?, entry, MAX_HISTORY_ENTRIES)
+}
+
+// Appends entry, then keeps only the newest max_entries lines
+fn append_history_entry(
+    path: &Path,
+    entry: &ImportHistoryEntry,
+    max_entries: usize,
+) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create data directory: {}", e))?;
+    }
+
+    let mut line = serde_json::to_string(entry)
+        .map_err(|e| format!("Failed to serialize history entry: {}", e))?;
+    line.push('\n');
+
+    let _guard = IMPORT_HISTORY_LOCK.lock().unwrap();
+    // A single append-mode write keeps concurrent imports from interleaving
+    // partial lines
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| format!("Failed to append to import history: {}", e))?;
+
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read import history: {}", e))?;
+    let lines = text.lines().collect::<Vec<_>>();
+    if lines.len() <= max_entries {
+        return Ok(());
+    }
+    let mut kept = lines[lines.len() - max_entries..].join("\n");
+    kept.push('\n');
+    atomic_write(path, kept.as_bytes()).map_err(|e| format!("Failed to trim import history: {}", e))
+}
+
+// Whether the import placed something, as opposed to a skip or an error
+fn history_entry_imported(entry: &ImportHistoryEntry) -> bool {
+    !matches!(entry.status.as_str(), "skipped" | "error")
+}
+
+async fn import_and_record(
+    app: &AppHandle,
+    input_url_or_id: String,
+    target_dir: String,
+    conflict_policy: String,
+    options: ArxivImportOptions,
+    library: &LibraryIndex,
+    import_id: Option<String>,
+) -> Result<ArxivImportResult, String> {
+    let cancel = ImportRegistration::register(import_id)?;
+    // Parallel imports queue here, each holding a network slot until done
+    let _slot = app.state::<Concurrency>().acquire(Budget::Network).await;
+    let _active = CounterGuard::enter(&ACTIVE_IMPORTS);
+    let started = std::time::Instant::now();
+    let downloads = app.state::<DownloadManager>();
+    let outcome = import_arxiv_into(
+        input_url_or_id.clone(),
+        target_dir,
+        conflict_policy,
+        options,
+        library,
+        &downloads,
+        &cancel.flag,
+    )
+    .await;
+
+    let paper = outcome.as_ref().ok().and_then(|r| r.paper.as_ref());
+    let entry = ImportHistoryEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        arxiv_id: parse_arxiv_input(&input_url_or_id).map(|(base_id, _)| base_id),
+        input: input_url_or_id,
+        version: paper.map(|paper| paper.version),
+        title: paper.map(|paper| paper.title.clone()),
+        status: match &outcome {
+            Ok(result) => result.status.clone(),
+            Err(_) => "error".to_string(),
+        },
+        reason: match &outcome {
+            Ok(result) => result.reason.clone(),
+            Err(error) => Some(error.clone()),
+        },
+        pdf_path: outcome.as_ref().ok().and_then(|r| r.pdf_path.clone()),
+        metadata_path: outcome.as_ref().ok().and_then(|r| r.metadata_path.clone()),
+        bytes: outcome.as_ref().ok().and_then(|r| r.pdf_size),
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+
+    // History is best-effort; the import already happened either way
+    if let Err(error) = append_import_history(app, &entry) {
+        warn!("{}", error);
+    }
+
+    outcome
+}
+
+// Newest first. With dedupe, a recents view: only the latest successful
+// import per paper version, so a paper imported again moves back to the top.
+// Skips and errors (a re-import that hit file_exists) are left out there, as
+// are entries that never got as far as a version.
+#[tauri::command]
+fn get_import_history(
+    app: AppHandle,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    status_filter: Option<String>,
+    dedupe: Option<bool>,
+) -> Result<Vec<ImportHistoryEntry>, String> {
+    read_import_history(
+        &import_history_path(&app)?,
+        limit,
+        offset,
+        status_filter,
+        dedupe,
+    )
+}
+
+fn read_import_history(
+    path: &Path,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    status_filter: Option<String>,
+    dedupe: Option<bool>,
+) -> Result<Vec<ImportHistoryEntry>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(format!("Failed to read import history: {}", error)),
+    };
+
+    // A torn or hand-edited line is skipped rather than failing the whole
+    // query
+    let mut seen = HashSet::new();
+    Ok(text
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<ImportHistoryEntry>(line).ok())
+        .filter(|entry| {
+            status_filter
+                .as_deref()
+                .map(|status| entry.status == status)
+                .unwrap_or(true)
+        })
+        .filter(|entry| {
+            !dedupe.unwrap_or(false)
+                || match (&entry.arxiv_id, entry.version) {
+                    (Some(arxiv_id), Some(version)) if history_entry_imported(entry) => {
+                        seen.insert((arxiv_id.clone(), version))
+                    }
+                    _ => false,
+                }
+        })
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(DEFAULT_HISTORY_LIMIT))
+        .collect())
+}
+
+#[tauri::command]
+fn clear_import_history(app: AppHandle) -> Result<(), String> {
+    let path = import_history_path(&app)?;
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(format!("Failed to clear import history: {}", error)),
+    }
+}
+
+// arXiv base id -> (metadata_path, pdf_path) for every sidecar found under
+// the library roots next to an existing PDF
+type LibraryIndex = HashMap<String, (String, String)>;
+
+fn build_library_index(roots: &[String]) -> LibraryIndex {
+    let mut index = LibraryIndex::new();
+
+    for root in roots {
+        let root = Path::new(root);
+        if !root.is_dir() {
+            warn!("Skipping library root that is not a directory: {:?}", root);
+            continue;
+        }
+
+        for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() || sidecar_stem(entry.path()).is_none() {
+                continue;
+            }
+
+            // Matching goes by the id recorded in the sidecar, since file names
+            // change with templates and manual renames
+            let sidecar = match sidecar::read(entry.path()) {
+                Ok(sidecar) => sidecar,
+                Err(_) => continue,
+            };
+            let arxiv_id = match sidecar_arxiv_id(&sidecar) {
+                Some(arxiv_id) => arxiv_id,
+                None => continue,
+            };
+
+            // Only a paper whose PDF is still there counts as imported; a
+            // sidecar left behind by a deleted PDF, or written by a
+            // metadata_only import, doesn't stop the download
+            let pdf_path = sidecar
+                .get("pdf_path")
+                .and_then(|value| value.as_str())
+                .map(|value| entry.path().parent().unwrap_or(root).join(value))
+                .filter(|pdf_path| pdf_path.is_file())
+                .or_else(|| {
+                    sidecar_stem(entry.path())
+                        .map(|stem| entry.path().with_file_name(format!("{}.pdf", stem)))
+                        .filter(|pdf_path| pdf_path.is_file())
+                });
+            let Some(pdf_path) = pdf_path else {
+                continue;
+            };
+
+            index.entry(arxiv_id).or_insert_with(|| {
+                (
+                    entry.path().to_string_lossy().to_string(),
+                    pdf_path.to_string_lossy().to_string(),
+                )
+            });
+        }
+    }
+
+    index
+}
+
+#[tauri::command]
+async fn import_arxiv_paper(
+    app: AppHandle,
+    input_url_or_id: String,
+    target_dir: String,
+    conflict_policy: String,
+    options: Option<ArxivImportOptions>,
+    import_id: Option<String>,
+) -> Result<ArxivImportResult, String> {
+    let options = options.unwrap_or_default();
+    let library = build_library_index(&options.library_roots);
+    import_and_record(
+        &app,
+        input_url_or_id,
+        target_dir,
+        conflict_policy,
+        options,
+        &library,
+        import_id,
+    )
+    .await
+}
+
+// Stops an import started with this import_id: its download is aborted, the
+// partial file removed, and it returns status "skipped" with reason
+// "cancelled". false if no such import is running.
+#[tauri::command]
+fn cancel_import(import_id: String) -> bool {
+    match IMPORT_CANCELS
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|imports| imports.get(&import_id))
+    {
+        Some(flag) => {
+            flag.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArxivBatchItem {
+    pub input: String,
+    pub result: Option<ArxivImportResult>,
+    // Set when this input could not be parsed in strict mode
+    pub error: Option<String>,
+}
+
+// Imports several papers with one scan of the library roots. Arguments shared
+// by the whole batch (policy, mode, target dir) abort it when invalid.
+#[tauri::command]
+async fn import_arxiv_papers(
+    app: AppHandle,
+    inputs: Vec<String>,
+    target_dir: String,
+    conflict_policy: String,
+    options: Option<ArxivImportOptions>,
+) -> Result<Vec<ArxivBatchItem>, String> {
+    import_arxiv_batch(
+        &app,
+        inputs,
+        target_dir,
+        conflict_policy,
+        options.unwrap_or_default(),
+    )
+    .await
+}
+
+async fn import_arxiv_batch(
+    app: &AppHandle,
+    inputs: Vec<String>,
+    target_dir: String,
+    conflict_policy: String,
+    options: ArxivImportOptions,
+) -> Result<Vec<ArxivBatchItem>, String> {
+    let mut library = build_library_index(&options.library_roots);
+    let mut items = Vec::with_capacity(inputs.len());
+
+    // Spacing between papers comes from the arXiv gate inside each import
+    for input in inputs {
+        let outcome = import_and_record(
+            app,
+            input.clone(),
+            target_dir.clone(),
+            conflict_policy.clone(),
+            options.clone(),
+            &library,
+            None,
+        )
+        .await;
+
+        match outcome {
+            Ok(result) => {
+                // Later inputs naming the same paper count as duplicates too
+                if let (Some(paper), Some(metadata_path), Some(pdf_path)) =
+                    (&result.paper, &result.metadata_path, &result.pdf_path)
+                {
+                    if result.status != "skipped" {
+                        library.insert(
+                            paper.arxiv_id.clone(),
+                            (metadata_path.clone(), pdf_path.clone()),
+                        );
+                    }
+                }
+
+                items.push(ArxivBatchItem {
+                    input,
+                    result: Some(result),
+                    error: None,
+                });
+            }
+            Err(error) if error.starts_with("invalid_link:") => items.push(ArxivBatchItem {
+                input,
+                result: None,
+                error: Some(error),
+            }),
+            Err(error) => return Err(error),
+        }
+    }
+
+    Ok(items)
+}
+
+// "rename" imports next to an existing file as the first free "<stem>_N"
+const ARXIV_CONFLICT_POLICIES: &[&str] = &["skip", "rename"];
+
+// Upper bounds on papers per author or search import, however many the
+// caller asks for
+const ARXIV_AUTHOR_IMPORT_MAX: u32 = 200;
+const ARXIV_SEARCH_IMPORT_MAX: u32 = 200;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArxivAuthorImport {
+    // Imported by this run (downloaded, attached, metadata_only or source_only)
+    pub downloaded: Vec<ArxivBatchItem>,
+    // Found in the library roots or the target dir, so left alone
+    pub already_in_library: Vec<ArxivBatchItem>,
+    // Every other skip reason, plus entries that could not be imported at all
+    pub failed: Vec<ArxivBatchItem>,
+}
+
+// Newest-first ids matching an arXiv search_query, stopping at the first
+// paper submitted before `since`. Pages go through the arXiv gate like every
+// other request, so a long listing is spaced out too.
+async fn search_arxiv_ids(
+    client: &Client,
+    search_query: &str,
+    max_results: usize,
+    since: Option<NaiveDate>,
+) -> Result<Vec<String>, String> {
+    let mut ids = Vec::new();
+    let mut start = 0;
+
+    'pages: while ids.len() < max_results {
+        let page_size = (max_results - ids.len()).min(ARXIV_SEARCH_MAX_RESULTS as usize);
+        let params = [
+            ("search_query", search_query.to_string()),
+            ("start", start.to_string()),
+            ("max_results", page_size.to_string()),
+            ("sortBy", "submittedDate".to_string()),
+            ("sortOrder", "descending".to_string()),
+        ];
+        let (feed, _) = fetch_arxiv_feed(client, &arxiv_api_base(), &params)
+            .await
+            .map_err(|reason| format!("arXiv search failed: {}", reason))?;
+
+        let entry_count = feed.entry.len();
+        start += entry_count;
+        for entry in feed.entry {
+            let published = entry.published.as_deref().and_then(parse_arxiv_date);
+            if let (Some(since), Some(published)) = (since, published) {
+                if published < since {
+                    break 'pages;
+                }
+            }
+            if let Some((base_id, _)) = entry.id.as_deref().and_then(parse_arxiv_input) {
+                ids.push(base_id);
+            }
+        }
+
+        if entry_count < page_size {
+            break;
+        }
+    }
+
+    Ok(ids)
+}
+
+// Imports the newest papers matching a search, e.g. "cat:cs.LG" for the
+// latest in a category, with the same query syntax as search_arxiv. Results
+// come back newest first; like import_arxiv_by_author, papers already in the
+// target dir or library roots are skipped.
+#[tauri::command]
+async fn import_arxiv_search(
+    app: AppHandle,
+    query: String,
+    max_results: u32,
+    target_dir: String,
+    conflict_policy: String,
+    options: Option<ArxivImportOptions>,
+) -> Result<Vec<ArxivImportResult>, String> {
+    let query = compact_text(&query);
+    if query.is_empty() {
+        return Err(invalid_argument(
+            "invalid_input",
+            "Search query is empty".to_string(),
+        ));
+    }
+
+    importer::check_conflict_policy_in(&conflict_policy, ARXIV_CONFLICT_POLICIES)?;
+    importer::check_target_dir(&target_dir)?;
+
+    let mut options = options.unwrap_or_default();
+    if !options.library_roots.contains(&target_dir) {
+        options.library_roots.push(target_dir.clone());
+    }
+
+    let client = arxiv_client().map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let max_results = max_results.clamp(1, ARXIV_SEARCH_IMPORT_MAX) as usize;
+    let ids = search_arxiv_ids(&client, &arxiv_search_query(query), max_results, None).await?;
+    let items = import_arxiv_batch(&app, ids, target_dir, conflict_policy, options).await?;
+
+    // Ids straight from the API always parse, so every item has a result
+    Ok(items.into_iter().filter_map(|item| item.result).collect())
+}
+
+// Imports an author's papers submitted on or after since_date (YYYY-MM-DD),
+// newest first. The target dir always counts as part of the library, so
+// re-running it only fetches papers that are new since the last run.
+#[tauri::command]
+async fn import_arxiv_by_author(
+    app: AppHandle,
+    author_query: String,
+    max_results: u32,
+    since_date: Option<String>,
+    target_dir: String,
+    conflict_policy: String,
+    options: Option<ArxivImportOptions>,
+) -> Result<ArxivAuthorImport, String> {
+    let author_query = compact_text(&author_query);
+    if author_query.is_empty() {
+        return Err(invalid_argument(
+            "invalid_input",
+            "Author query is empty".to_string(),
+        ));
+    }
+
+    let since = match since_date.as_deref().map(str::trim) {
+        Some(date) if !date.is_empty() => {
+            Some(NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+                invalid_argument(
+                    "invalid_input",
+                    format!("since_date must be YYYY-MM-DD: {}", date),
+                )
+            })?)
+        }
+        _ => None,
+    };
+
+    importer::check_conflict_policy_in(&conflict_policy, ARXIV_CONFLICT_POLICIES)?;
+    importer::check_target_dir(&target_dir)?;
+
+    let mut options = options.unwrap_or_default();
+    if !options.library_roots.contains(&target_dir) {
+        options.library_roots.push(target_dir.clone());
+    }
+
+    let client = arxiv_client().map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let search_query = format!("au:\"{}\"", author_query.replace('"', " "));
+    let max_results = max_results.min(ARXIV_AUTHOR_IMPORT_MAX) as usize;
+    let ids = search_arxiv_ids(&client, &search_query, max_results, since).await?;
+    let items = import_arxiv_batch(&app, ids, target_dir, conflict_policy, options).await?;
+
+    let mut grouped = ArxivAuthorImport::default();
+    for item in items {
+        let reason = item.result.as_ref().map(|result| {
+            (
+                result.status.as_str() == "skipped",
+                result.reason.as_deref().unwrap_or(""),
+            )
+        });
+        match reason {
+            Some((false, _)) => grouped.downloaded.push(item),
+            Some((true, "already_in_library" | "file_exists")) => {
+                grouped.already_in_library.push(item)
+            }
+            _ => grouped.failed.push(item),
+        }
+    }
+
+    Ok(grouped)
+}
+
+// What import_arxiv_into needs to know before it goes to the network
+struct ImportArguments<'a> {
+    mode: &'a str,
+    sidecar_format: SidecarFormat,
+    format: &'a str,
+    existing_pdf: Option<PathBuf>,
+    base_id: String,
+    requested_version: Option<u32>,
+}
+
+// Everything import_arxiv_into checks before fetching anything. Err is a
+// caller mistake; Ok(Err(_)) is an import that ends as skipped right away,
+// e.g. because the paper is already in the library.
+fn check_import_arguments<'a>(
+    input_url_or_id: &str,
+    target_dir: &str,
+    conflict_policy: &str,
+    options: &'a ArxivImportOptions,
+    library: &LibraryIndex,
+) -> Result<Result<ImportArguments<'a>, ArxivImportResult>, String> {
+    importer::check_conflict_policy_in(conflict_policy, ARXIV_CONFLICT_POLICIES)?;
+
+    let mode = match (options.mode.as_deref(), options.metadata_only) {
+        (None, true) => "metadata_only",
+        (mode, _) => mode.unwrap_or("full"),
+    };
+    if !matches!(mode, "full" | "metadata_only" | "attach") {
+        return Err(invalid_argument(
+            "invalid_mode",
+            format!("Unsupported import mode: {}", mode),
+        ));
+    }
+    if options.metadata_only && mode != "metadata_only" {
+        return Err(invalid_argument(
+            "invalid_mode",
+            format!("metadata_only cannot be combined with mode {}", mode),
+        ));
+    }
+
+    let sidecar_format = match options.sidecar_format.as_deref() {
+        Some(format) => SidecarFormat::parse(format)?,
+        None => default_sidecar_format(),
+    };
+
+    let format = options.format.as_deref().unwrap_or("pdf");
+    if !matches!(format, "pdf" | "source") {
+        return Err(invalid_argument(
+            "invalid_input",
+            format!("Unsupported download format: {}", format),
+        ));
+    }
+    if format == "source" && mode != "full" {
+        return Err(invalid_argument(
+            "invalid_mode",
+            format!("format source cannot be combined with mode {}", mode),
+        ));
+    }
+
+    // Catch unknown placeholders before anything is fetched
+    if let Some(template) = options.filename_template.as_deref() {
+        let blanks = FILENAME_PLACEHOLDERS
+            .iter()
+            .map(|name| (*name, ""))
+            .collect::<Vec<_>>();
+        importer::expand_filename_template(template, &blanks)?;
+    }
+
+    let existing_pdf = if mode == "attach" {
+        match options.existing_pdf_path.as_deref().map(Path::new) {
+            Some(existing) if existing.is_file() => Some(existing.to_path_buf()),
+            Some(_) => return Ok(Err(skipped_result("invalid_attachment", None))),
+            None => {
+                return Err(invalid_argument(
+                    "invalid_attachment",
+                    "Attach mode requires existing_pdf_path".to_string(),
+                ))
+            }
+        }
+    } else {
+        None
+    };
+
+    let (base_id, requested_version) = match parse_arxiv_input(input_url_or_id) {
+        Some(parsed) => parsed,
+        None if options.strict => {
+            return Err(invalid_argument(
+                "invalid_link",
+                format!("Not an arXiv URL or ID: {}", input_url_or_id),
+            ))
+        }
+        None => return Ok(Err(skipped_result("invalid_link", None))),
+    };
+
+    importer::check_target_dir(target_dir)?;
+    let target = Path::new(target_dir);
+    if !importer::ensure_target_dir(target) {
+        return Ok(Err(skipped_result("write_failed", None)));
+    }
+
+    if let Some((metadata_path, pdf_path)) = library.get(&base_id) {
+        return Ok(Err(ArxivImportResult {
+            status: "skipped".to_string(),
+            reason: Some("already_in_library".to_string()),
+            pdf_path: Some(pdf_path.clone()),
+            metadata_path: Some(metadata_path.clone()),
+            ..Default::default()
+        }));
+    }
+
+    Ok(Ok(ImportArguments {
+        mode,
+        sidecar_format,
+        format,
+        existing_pdf,
+        base_id,
+        requested_version,
+    }))
+}
+
+async fn import_arxiv_into(
+    input_url_or_id: String,
+    target_dir: String,
+    conflict_policy: String,
+    options: ArxivImportOptions,
+    library: &LibraryIndex,
+    downloads: &DownloadManager,
+    cancel: &Arc<AtomicBool>,
+) -> Result<ArxivImportResult, String> {
+    let ImportArguments {
+        mode,
+        sidecar_format,
+        format,
+        existing_pdf,
+        base_id,
+        requested_version,
+    } = match check_import_arguments(
+        &input_url_or_id,
+        &target_dir,
+        &conflict_policy,
+        &options,
+        library,
+    )? {
+        Ok(arguments) => arguments,
+        Err(skipped) => return Ok(skipped),
+    };
+    let target = Path::new(&target_dir);
+
+    let client = match arxiv_client() {
+        Ok(client) => client,
+        Err(error) => {
+            error!("Failed to create reqwest client: {:?}", error);
+            return Ok(skipped_result("network_error", None));
+        }
+    };
+
+    let api_base = resolve_base_url(options.api_base.as_deref(), &arxiv_api_base());
+    let pdf_base = resolve_base_url(options.pdf_base.as_deref(), &arxiv_pdf_base());
+
+    let (entry, api_endpoint) = match fetch_arxiv_entry(&client, &api_base, &base_id).await {
+        Ok(fetched) => fetched,
+        Err(reason) => return Ok(skipped_result(reason, None)),
+    };
+    // Which mirror answered, for diagnosing bad metadata later
+    let mut provenance = SidecarFields::new();
+    provenance.insert("api_endpoint".to_string(), serde_json::json!(api_endpoint));
+
+    let latest_version = entry_latest_version(&entry, &base_id);
+    // arXiv numbers versions 1..=latest without gaps
+    if let Some(requested) = requested_version.filter(|v| *v == 0 || *v > latest_version) {
+        let mut result = skipped_result("version_not_found", None);
+        result.detail = Some(format!(
+            "v{} does not exist; latest is v{}",
+            requested, latest_version
+        ));
+        result.available_versions = Some((1..=latest_version).collect());
+        return Ok(result);
+    }
+    let version = requested_version.unwrap_or(latest_version);
+    let mut paper = paper_from_entry(entry, &base_id, version, &pdf_base);
+    let id_with_version = format!("{}v{}", base_id, version);
+
+    let mut file_stem = match options.filename_template.as_deref() {
+        Some(template) => arxiv_file_stem(template, &paper)?,
+        None => importer::paper_file_stem(&id_with_version, &paper.title),
+    };
+
+    // Attaching a file that already sits at the canonical path is a no-op move
+    let canonical_pdf_path = target.join(format!("{}.pdf", file_stem));
+    let attach_in_place = existing_pdf
+        .as_deref()
+        .map(|existing| is_same_file(existing, &canonical_pdf_path))
+        .unwrap_or(false);
+
+    let mut renamed_from = None;
+    if conflict_policy == "rename" {
+        let taken = rename_conflict(target, &file_stem, mode, format, attach_in_place);
+        if let Some(taken) = taken {
+            let free = if format == "source" {
+                free_source_stem(target, &file_stem)
+            } else {
+                free_stem(target, &file_stem, ".pdf")
+            };
+            file_stem = match free {
+                Some(free) => free,
+                None => return Ok(skipped_result("file_exists", Some(paper))),
+            };
+            renamed_from = Some(taken.to_string_lossy().to_string());
+        }
+    }
+
+    let pdf_path = target.join(format!("{}.pdf", file_stem));
+    // An existing sidecar keeps its format so the paper never ends up with two
+    let metadata_path = sidecar::sidecar_for_pdf(&pdf_path, sidecar_format);
+    let bibtex_path = target.join(format!("{}.bib", file_stem));
+    let readme_path = target.join(format!("{}.md", file_stem));
+    let abstract_path = target.join(format!("{}.abstract.md", file_stem));
+    let write_abstract = options
+        .write_abstract
+        .unwrap_or_else(default_write_abstract);
+
+    if mode == "metadata_only" {
+        if conflict_policy == "skip" && metadata_path.exists() {
+            return Ok(ArxivImportResult {
+                status: "skipped".to_string(),
+                reason: Some("file_exists".to_string()),
+                metadata_path: Some(metadata_path.to_string_lossy().to_string()),
+                paper: Some(paper),
+                ..Default::default()
+            });
+        }
+
+        let mut warnings = Vec::new();
+        if options.enrich_orcid {
+            enrich_arxiv_authors(&client, &mut paper, &mut warnings).await;
+        }
+        let enrichment = if options.enrich {
+            enrich_arxiv_paper(&client, &paper, &mut warnings).await
+        } else {
+            None
+        };
+
+        let mut sidecar_extra = provenance;
+        if let Some(enrichment) = &enrichment {
+            sidecar_extra.extend(enrichment.sidecar_fields());
+        }
+        if let Err(error) = write_arxiv_sidecar(&paper, None, &metadata_path, sidecar_extra) {
+            error!("Failed to write metadata file: {:?}", error);
+            return Ok(skipped_result("write_failed", Some(paper)));
+        }
+
+        let readme_path = if options.write_readme {
+            write_arxiv_readme(&paper, &readme_path, &mut warnings)
+        } else {
+            None
+        };
+        let abstract_path = if write_abstract {
+            write_arxiv_abstract(&paper, &abstract_path, &mut warnings)
+        } else {
+            None
+        };
+
+        return Ok(ArxivImportResult {
+            status: "metadata_only".to_string(),
+            renamed_from,
+            metadata_path: Some(metadata_path.to_string_lossy().to_string()),
+            bibtex_path: write_arxiv_bibtex(&paper, &bibtex_path),
+            readme_path,
+            abstract_path,
+            enrichment,
+            warnings,
+            paper: Some(paper),
+            ..Default::default()
+        });
+    }
+
+    if format == "source" {
+        // The archive kind is only known after the download, so any of them
+        // counts as the paper being there
+        if let Some(source_path) = existing_primary_source(target, &file_stem) {
+            return Ok(ArxivImportResult {
+                status: "skipped".to_string(),
+                reason: Some("file_exists".to_string()),
+                source_path: Some(source_path.to_string_lossy().to_string()),
+                paper: Some(paper),
+                ..Default::default()
+            });
+        }
+        if paper.withdrawn {
+            return Ok(withdrawn_result(paper, &metadata_path, provenance));
+        }
+
+        let (source_path, source_size) = match download_arxiv_source(
+            downloads,
+            cancel,
+            &pdf_base,
+            &id_with_version,
+            target,
+            &file_stem,
+            SourcePlacement::Primary,
+        )
+        .await
+        {
+            Ok(source) => source,
+            // Without a PDF the source is the import, so its warning is the
+            // skip reason
+            Err(warning) => {
+                let reason = match warning {
+                    "source_exists" => "file_exists",
+                    "source_cancelled" => "cancelled",
+                    "source_write_failed" => "write_failed",
+                    "source_network_error" => "network_error",
+                    other => other,
+                };
+                return Ok(skipped_result(reason, Some(paper)));
+            }
+        };
+
+        let mut warnings = Vec::new();
+        if options.enrich_orcid {
+            enrich_arxiv_authors(&client, &mut paper, &mut warnings).await;
+        }
+        let enrichment = if options.enrich {
+            enrich_arxiv_paper(&client, &paper, &mut warnings).await
+        } else {
+            None
+        };
+
+        let mut sidecar_extra = provenance;
+        // A PDF imported earlier under the same stem stays described by the
+        // sidecar this writes over
+        if let Ok(existing) = sidecar::read(&metadata_path) {
+            for key in ["pdf_path", "sha256"] {
+                if let Some(value) = existing.get(key) {
+                    sidecar_extra.insert(key.to_string(), value.clone());
+                }
+            }
+        }
+        sidecar_extra.insert(
+            "source_path".to_string(),
+            serde_json::json!(source_path.to_string_lossy().to_string()),
+        );
+        sidecar_extra.insert("source_size".to_string(), serde_json::json!(source_size));
+        if let Some(enrichment) = &enrichment {
+            sidecar_extra.extend(enrichment.sidecar_fields());
+        }
+
+        if cancel.load(Ordering::SeqCst) {
+            let _ = fs::remove_file(&source_path);
+            return Ok(skipped_result("cancelled", Some(paper)));
+        }
+        if let Err(error) = write_arxiv_sidecar(&paper, None, &metadata_path, sidecar_extra) {
+            error!("Failed to write metadata file: {:?}", error);
+            let _ = fs::remove_file(&source_path);
+            return Ok(skipped_result("write_failed", Some(paper)));
+        }
+
+        let readme_path = if options.write_readme {
+            write_arxiv_readme(&paper, &readme_path, &mut warnings)
+        } else {
+            None
+        };
+        let abstract_path = if write_abstract {
+            write_arxiv_abstract(&paper, &abstract_path, &mut warnings)
+        } else {
+            None
+        };
+
+        return Ok(ArxivImportResult {
+            status: "source_only".to_string(),
+            renamed_from,
+            metadata_path: Some(metadata_path.to_string_lossy().to_string()),
+            bibtex_path: write_arxiv_bibtex(&paper, &bibtex_path),
+            source_path: Some(source_path.to_string_lossy().to_string()),
+            source_size: Some(source_size),
+            readme_path,
+            abstract_path,
+            enrichment,
+            warnings,
+            paper: Some(paper),
+            ..Default::default()
+        });
+    }
+
+    // The PDF and sidecar are staged as .part files and renamed into place
+    // together, so a failed import never leaves a PDF without its sidecar
+    let pdf_part = importer::part_path(&pdf_path);
+    let metadata_part = importer::part_path(&metadata_path);
+    importer::remove_stale_part(&pdf_part);
+    importer::remove_stale_part(&metadata_part);
+
+    if conflict_policy == "skip" && pdf_path.exists() && !attach_in_place {
+        return Ok(ArxivImportResult {
+            status: "skipped".to_string(),
+            reason: Some("file_exists".to_string()),
+            pdf_path: Some(pdf_path.to_string_lossy().to_string()),
+            metadata_path: if metadata_path.exists() {
+                Some(metadata_path.to_string_lossy().to_string())
+            } else {
+                None
+            },
+            bibtex_path: if bibtex_path.exists() {
+                Some(bibtex_path.to_string_lossy().to_string())
+            } else {
+                None
+            },
+            paper: Some(paper),
+            ..Default::default()
+        });
+    }
+
+    // What arXiv serves for a withdrawn paper is a one-page withdrawal notice
+    if paper.withdrawn && existing_pdf.is_none() {
+        return Ok(withdrawn_result(paper, &metadata_path, provenance));
+    }
+
+    let (pdf_size, sha256, resolved_pdf_url) = if let Some(existing) = &existing_pdf {
+        if !attach_in_place {
+            if let Err(error) = relocate_file(existing, &pdf_path) {
+                error!("Failed to move attached PDF: {:?}", error);
+                return Ok(skipped_result("write_failed", Some(paper)));
+            }
+        }
+        (
+            fs::metadata(&pdf_path).map(|m| m.len()).unwrap_or(0),
+            checksum::sha256_file(&pdf_path).ok(),
+            None,
+        )
+    } else {
+        let job = serde_json::json!({
+            "importer": "arxiv",
+            "arxiv_id": id_with_version,
+            "kind": "pdf",
+        });
+        match importer::queue_pdf_download(
+            downloads,
+            &paper.pdf_url,
+            &pdf_part,
+            job,
+            Some(cancel.clone()),
+            "corrupt_download",
+        )
+        .await
+        {
+            Ok(downloaded) => (
+                downloaded.size,
+                Some(downloaded.sha256),
+                downloaded.resolved_url,
+            ),
+            Err(failure) => {
+                // The API knows the paper, so a 404 only says its PDF can't be
+                // had right now; a withdrawal is told by the comment, above
+                let reason = match failure.reason {
+                    "paper_not_found" => "pdf_unavailable",
+                    reason => reason,
+                };
+                let mut result = skipped_result(reason, Some(paper));
+                result.detail = failure.detail;
+                result.resolved_pdf_url = failure.resolved_url;
+                result.http_status = failure.status;
+                return Ok(result);
+            }
+        }
+    };
+
+    let mut warnings = Vec::new();
+    if options.enrich_orcid {
+        enrich_arxiv_authors(&client, &mut paper, &mut warnings).await;
+    }
+    let enrichment = if options.enrich {
+        enrich_arxiv_paper(&client, &paper, &mut warnings).await
+    } else {
+        None
+    };
+
+    let mut sidecar_extra = sha256
+        .as_deref()
+        .map(importer::checksum_fields)
+        .unwrap_or_default();
+    sidecar_extra.extend(provenance);
+    if let Some(enrichment) = &enrichment {
+        sidecar_extra.extend(enrichment.sidecar_fields());
+    }
+
+    let source = if options.include_source {
+        match download_arxiv_source(
+            downloads,
+            cancel,
+            &pdf_base,
+            &id_with_version,
+            target,
+            &file_stem,
+            SourcePlacement::BesidePdf,
+        )
+        .await
+        {
+            Ok((source_path, source_size)) => {
+                sidecar_extra.insert(
+                    "source_path".to_string(),
+                    serde_json::json!(source_path.to_string_lossy().to_string()),
+                );
+                sidecar_extra.insert("source_size".to_string(), serde_json::json!(source_size));
+                Some((source_path, source_size))
+            }
+            Err(warning) => {
+                warnings.push(warning.to_string());
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Puts the directory back the way it was: the source goes away and an
+    // attached PDF moves back to where it came from
+    let roll_back = |source: &Option<(PathBuf, u64)>| {
+        if let Some((source_path, _)) = source {
+            let _ = fs::remove_file(source_path);
+        }
+        if let Some(existing) = existing_pdf.as_deref().filter(|_| !attach_in_place) {
+            if let Err(error) = relocate_file(&pdf_path, existing) {
+                error!("Failed to move attached PDF back: {:?}", error);
+            }
+        }
+    };
+
+    // A cancel that came in after the downloads still stops the import
+    if cancel.load(Ordering::SeqCst) {
+        importer::discard_parts(&[&pdf_part]);
+        roll_back(&source);
+        return Ok(skipped_result("cancelled", Some(paper)));
+    }
+
+    // An attached PDF was moved into place already
+    let staged_pdf =
+        Some((pdf_part.as_path(), pdf_path.as_path())).filter(|_| existing_pdf.is_none());
+    if let Err(error) =
+        importer::commit_import(staged_pdf, &metadata_part, &metadata_path, |part| {
+            write_arxiv_sidecar(&paper, Some(&pdf_path), part, sidecar_extra)
+        })
+    {
+        error!("{}", error);
+        roll_back(&source);
+        return Ok(skipped_result("write_failed", Some(paper)));
+    }
+
+    let bibtex_path = write_arxiv_bibtex(&paper, &bibtex_path);
+
+    let readme_path = if options.write_readme {
+        write_arxiv_readme(&paper, &readme_path, &mut warnings)
+    } else {
+        None
+    };
+    let abstract_path = if write_abstract {
+        write_arxiv_abstract(&paper, &abstract_path, &mut warnings)
+    } else {
+        None
+    };
+
+    Ok(ArxivImportResult {
+        status: if existing_pdf.is_some() {
+            "attached".to_string()
+        } else {
+            "downloaded".to_string()
+        },
+        pdf_path: Some(pdf_path.to_string_lossy().to_string()),
+        renamed_from,
+        pdf_size: Some(pdf_size),
+        resolved_pdf_url,
+        sha256,
+        metadata_path: Some(metadata_path.to_string_lossy().to_string()),
+        bibtex_path,
+        source_path: source
+            .as_ref()
+            .map(|(source_path, _)| source_path.to_string_lossy().to_string()),
+        source_size: source.as_ref().map(|(_, source_size)| *source_size),
+        readme_path,
+        abstract_path,
+        enrichment,
+        warnings,
+        paper: Some(paper),
+        ..Default::default()
+    })
+}
+
+fn arxiv_file_stem(template: &str, paper: &ArxivPaperMetadata) -> Result<String, String> {
+    // Surname of the first author, which arXiv lists as "Given Family"
+    let first_author = paper
+        .authors
+        .first()
+        .and_then(|author| author.split_whitespace().last())
+        .unwrap_or_default();
+    let version = paper.version.to_string();
+    let year = paper
+        .published_year
+        .map(|year| year.to_string())
+        .unwrap_or_default();
+
+    importer::expand_filename_template(
+        template,
+        &[
+            ("id", &paper.arxiv_id),
+            ("version", &version),
+            ("title", &paper.title),
+            ("first_author", first_author),
+            ("year", &year),
+        ],
+    )
+}
+
+// The .bib is a convenience copy of the sidecar, so failing to write it
+// doesn't undo an otherwise complete import.
+fn write_arxiv_bibtex(paper: &ArxivPaperMetadata, bibtex_path: &Path) -> Option<String> {
+    match atomic_write(bibtex_path, arxiv_bibtex_entry(paper).as_bytes()) {
+        Ok(()) => Some(bibtex_path.to_string_lossy().to_string()),
+        Err(error) => {
+            error!("Failed to write BibTeX file: {:?}", error);
+            None
+        }
+    }
+}
+
+#[tauri::command]
+fn get_importer_settings() -> ImporterSettings {
+    IMPORTER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|(settings, _)| settings.clone())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+fn get_importer_status() -> ImporterStatus {
+    ImporterStatus {
+        waiting_requests: ARXIV_WAITING.load(Ordering::SeqCst),
+        active_imports: ACTIVE_IMPORTS.load(Ordering::SeqCst),
+        arxiv_request_interval_ms: arxiv_request_interval_ms(),
+    }
+}
+
+#[tauri::command]
+fn set_importer_settings(
+    app: AppHandle,
+    settings: ImporterSettings,
+) -> Result<ImporterSettings, String> {
+    let path = importer_settings_path(&app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+
+    // Validate before persisting so a bad proxy URL never reaches disk, and
+    // only switch over once the file is written, so the running importer
+    // never uses settings that would be gone after a restart
+    let client = check_importer_settings(&settings)?;
+
+    let json = serde_json::to_vec_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize importer settings: {}", e))?;
+    atomic_write(&path, &json).map_err(|e| format!("Failed to save importer settings: {}", e))?;
+
+    *IMPORTER.lock().unwrap() = Some((settings.clone(), client));
+    Ok(settings)
+}
+
+// Plain words search every field; anything using arXiv's field prefixes
+// (ti:, au:, cat:, ...) is passed through untouched. `query` is compacted.
+fn arxiv_search_query(query: String) -> String {
+    if query.contains(':') {
+        query
+    } else {
+        query
+            .split(' ')
+            .map(|term| format!("all:{}", term))
+            .collect::<Vec<_>>()
+            .join(" AND ")
+    }
+}
+
+#[tauri::command]
+async fn search_arxiv(
+    query: String,
+    max_results: u8,
+    start: u32,
+    sort_by: String,
+) -> Result<Vec<ArxivPaperMetadata>, String> {
+    let query = compact_text(&query);
+    if query.is_empty() {
+        return Err("Search query is empty".to_string());
+    }
+
+    if !matches!(
+        sort_by.as_str(),
+        "relevance" | "lastUpdatedDate" | "submittedDate"
+    ) {
+        return Err(format!("Unsupported sort order: {}", sort_by));
+    }
+
+    let search_query = arxiv_search_query(query);
+
+    let max_results = max_results.clamp(1, ARXIV_SEARCH_MAX_RESULTS);
+    let params = [
+        ("search_query", search_query),
+        ("start", start.to_string()),
+        ("max_results", max_results.to_string()),
+        ("sortBy", sort_by),
+        ("sortOrder", "descending".to_string()),
+    ];
+
+    let client = arxiv_client().map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let (feed, _) = fetch_arxiv_feed(&client, &arxiv_api_base(), &params)
+        .await
+        .map_err(|reason| format!("arXiv search failed: {}", reason))?;
+    let pdf_base = arxiv_pdf_base();
+
+    Ok(feed
+        .entry
+        .into_iter()
+        .filter_map(|entry| {
+            let (base_id, _) = entry.id.as_deref().and_then(parse_arxiv_input)?;
+            let version = entry_latest_version(&entry, &base_id);
+            Some(paper_from_entry(entry, &base_id, version, &pdf_base))
+        })
+        .collect())
+}
+
+#[tauri::command]
+fn export_bibtex_for_metadata(metadata_path: String) -> Result<String, String> {
+    let path = Path::new(&metadata_path);
+
+    if !path.is_file() {
+        return Err(format!("Metadata file does not exist: {}", metadata_path));
+    }
+
+    let stem =
+        sidecar_stem(path).ok_or_else(|| format!("Not a metadata sidecar: {}", metadata_path))?;
+    let sidecar = sidecar::read(path)?;
+    let paper = serde_json::from_value::<ArxivPaperMetadata>(serde_json::Value::Object(sidecar))
+        .map_err(|e| format!("Failed to parse metadata file: {}", e))?;
+    let bibtex_path = path.with_file_name(format!("{}.bib", stem));
+
+    atomic_write(&bibtex_path, arxiv_bibtex_entry(&paper).as_bytes())
+        .map_err(|e| format!("Failed to write BibTeX file: {}", e))?;
+
+    Ok(bibtex_path.to_string_lossy().to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryExportRow {
+    pub arxiv_id: Option<String>,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub year: Option<String>,
+    pub tags: Vec<String>,
+    pub path: String,
+    pub size: u64,
+    // false when the fields were derived from the file name
+    pub has_metadata: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportStats {
+    pub output_path: String,
+    pub exported: usize,
+    pub with_metadata: usize,
+    pub without_metadata: usize,
+    // Unreadable folders and sidecars; their PDFs are still exported
+    pub errors: Vec<String>,
+}
+
+// What an imported file's name gives away: "2301.12345v2_Some_Title" is
+// arXiv 2301.12345 from 2023 titled "Some Title". Other names only give a
+// title.
+fn export_row_from_filename(file: &PdfFile) -> LibraryExportRow {
+    let stem = file_stem_string(Path::new(&file.path));
+    let arxiv_id = audit::stem_id(&stem)
+        .filter(|(_, is_arxiv)| *is_arxiv)
+        .map(|(id, _)| id);
+
+    let title = match &arxiv_id {
+        Some(id) => stem
+            .strip_prefix(&id.replace('/', "_"))
+            .map(|rest| rest.trim_start_matches(|c: char| c == 'v' || c.is_ascii_digit()))
+            .unwrap_or(&stem)
+            .trim_start_matches('_'),
+        None => stem.as_str(),
+    };
+    // YYMM for new-style ids, YYMMNNN after the archive for old-style ones
+    let year = arxiv_id.as_deref().and_then(|id| {
+        let digits = id.rsplit('/').next()?.get(..2)?.parse::<u32>().ok()?;
+        let century = if id.contains('/') && digits >= 91 {
+            1900
+        } else {
+            2000
+        };
+        Some((century + digits).to_string())
+    });
+
+    LibraryExportRow {
+        arxiv_id,
+        title: compact_text(&title.replace('_', " ")),
+        authors: Vec::new(),
+        year,
+        tags: Vec::new(),
+        path: file.path.clone(),
+        size: file.size,
+        has_metadata: false,
+    }
+}
+
+// Sidecar fields win over the file name wherever the sidecar has them
+fn export_row(file: &PdfFile, fields: &SidecarFields) -> LibraryExportRow {
+    let mut row = export_row_from_filename(file);
+    let text = |key: &str| match fields.get(key) {
+        Some(serde_json::Value::String(value)) if !value.trim().is_empty() => {
+            Some(value.trim().to_string())
+        }
+        Some(serde_json::Value::Number(value)) => Some(value.to_string()),
+        _ => None,
+    };
+    let strings = |key: &str| -> Vec<String> {
+        fields
+            .get(key)
+            .and_then(|values| values.as_array())
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|value| value.as_str().or_else(|| value.get("name")?.as_str()))
+                    .map(|value| value.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    row.has_metadata = true;
+    row.arxiv_id = text("arxiv_id").or(row.arxiv_id);
+    row.title = text("title").unwrap_or(row.title);
+    row.authors = strings("authors");
+    row.year = text("published_year")
+        .or_else(|| text("year"))
+        .or_else(|| text("published").map(|date| date.chars().take(4).collect()))
+        .or(row.year);
+    row.tags = strings("tags");
+    row
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn library_csv(rows: &[LibraryExportRow]) -> String {
+    let mut csv = String::from("arxiv_id,title,authors,year,tags,path,size\r\n");
+    for row in rows {
+        let fields = [
+            row.arxiv_id.clone().unwrap_or_default(),
+            row.title.clone(),
+            row.authors.join("; "),
+            row.year.clone().unwrap_or_default(),
+            row.tags.join("; "),
+            row.path.clone(),
+            row.size.to_string(),
+        ];
+        let line = fields
+            .iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<_>>()
+            .join(",");
+        csv.push_str(&line);
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+// Writes one row per PDF under dir_path (recursively) to output_path, as
+// "csv" (authors and tags joined with "; ") or a "json" array. PDFs without a
+// sidecar are included with what their file name gives away.
+#[tauri::command]
+async fn export_library(
+    dir_path: String,
+    format: String,
+    output_path: String,
+) -> Result<ExportStats, String> {
+    let format = format.trim().to_lowercase();
+    if !matches!(format.as_str(), "csv" | "json") {
+        return Err(invalid_argument(
+            "invalid_input",
+            format!(
+                "Unsupported export format (expected csv or json): {}",
+                format
+            ),
+        ));
+    }
+    let output = PathBuf::from(&output_path);
+    if output.is_dir() {
+        return Err(format!("Output path is a directory: {}", output_path));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let scan = walk_pdfs(&dir_path, true, 0, false, None)?;
+        let mut errors = scan.errors;
+        let rows = scan
+            .files
+            .iter()
+            .map(|file| {
+                // Any format counts, and a PDF with sidecars in several
+                // formats gets them merged
+                let sidecar_path = metadata_sidecar_path(Path::new(&file.path));
+                if !sidecar_path.is_file() {
+                    return export_row_from_filename(file);
+                }
+                match sidecar::read_for_pdf(Path::new(&file.path)) {
+                    Ok(fields) => export_row(file, &fields),
+                    Err(error) => {
+                        errors.push(format!("{}: {}", sidecar_path.display(), error));
+                        export_row_from_filename(file)
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let contents = if format == "csv" {
+            library_csv(&rows)
+        } else {
+            serde_json::to_string_pretty(&rows)
+                .map_err(|e| format!("Failed to serialize library: {}", e))?
+        };
+        atomic_write(&output, contents.as_bytes())
+            .map_err(|e| format!("Failed to write export: {}", e))?;
+
+        let with_metadata = rows.iter().filter(|row| row.has_metadata).count();
+        Ok(ExportStats {
+            output_path: output.to_string_lossy().to_string(),
+            exported: rows.len(),
+            with_metadata,
+            without_metadata: rows.len() - with_metadata,
+            errors,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to export library: {}", e))?
+}
+
+fn sidecar_arxiv_id(sidecar: &SidecarFields) -> Option<String> {
+    sidecar
+        .get("arxiv_id")
+        .and_then(|value| value.as_str())
+        .and_then(parse_plain_arxiv_id)
+        .map(|(base_id, _)| base_id)
+}
+
+fn read_arxiv_sidecar_identity(metadata_path: &Path) -> Result<(String, u32), String> {
+    if !metadata_path.is_file() {
+        return Err(format!(
+            "Metadata file does not exist: {}",
+            metadata_path.display()
+        ));
+    }
+
+    let sidecar = sidecar::read(metadata_path)?;
+    let arxiv_id = sidecar_arxiv_id(&sidecar)
+        .ok_or_else(|| format!("No arXiv id in metadata file: {}", metadata_path.display()))?;
+    let version = sidecar
+        .get("version")
+        .and_then(|value| value.as_u64())
+        .unwrap_or(1) as u32;
+
+    Ok((arxiv_id, version))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArxivUpdateStatus {
+    pub metadata_path: String,
+    pub arxiv_id: String,
+    pub local_version: u32,
+    pub latest_version: Option<u32>,
+    pub updated: bool,
+    pub latest_title: Option<String>,
+    pub error: Option<String>,
+}
+
+#[tauri::command]
+async fn check_arxiv_updates(
+    metadata_paths: Vec<String>,
+) -> Result<Vec<ArxivUpdateStatus>, String> {
+    let mut statuses = Vec::new();
+    let mut pending = Vec::new();
+
+    for metadata_path in metadata_paths {
+        let identity = read_arxiv_sidecar_identity(Path::new(&metadata_path));
+        let (arxiv_id, local_version, error) = match identity {
+            Ok((arxiv_id, version)) => {
+                pending.push(statuses.len());
+                (arxiv_id, version, None)
+            }
+            Err(error) => (String::new(), 0, Some(error)),
+        };
+        statuses.push(ArxivUpdateStatus {
+            metadata_path,
+            arxiv_id,
+            local_version,
+            latest_version: None,
+            updated: false,
+            latest_title: None,
+            error,
+        });
+    }
+
+    let client = arxiv_client().map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    for chunk in pending.chunks(ARXIV_ID_LIST_CHUNK) {
+        let mut ids = chunk
+            .iter()
+            .map(|&index| statuses[index].arxiv_id.clone())
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+
+        // id_list answers with max_results=10 unless told otherwise
+        let params = [
+            ("id_list", ids.join(",")),
+            ("max_results", ids.len().to_string()),
+        ];
+
+        let latest = match fetch_arxiv_feed(&client, &arxiv_api_base(), &params).await {
+            Ok((feed, _)) => feed
+                .entry
+                .into_iter()
+                .filter_map(|entry| {
+                    let (base_id, _) = entry.id.as_deref().and_then(parse_arxiv_input)?;
+                    let version = entry_latest_version(&entry, &base_id);
+                    let paper = paper_from_entry(entry, &base_id, version, ARXIV_BASE_URL);
+                    Some((base_id, (version, paper.title)))
+                })
+                .collect::<HashMap<_, _>>(),
+            Err(reason) => {
+                for &index in chunk {
+                    statuses[index].error = Some(reason.to_string());
+                }
+                continue;
+            }
+        };
+
+        for &index in chunk {
+            let status = &mut statuses[index];
+            match latest.get(&status.arxiv_id) {
+                Some((version, title)) => {
+                    status.latest_version = Some(*version);
+                    status.updated = *version > status.local_version;
+                    status.latest_title = Some(title.clone());
+                }
+                None => status.error = Some("paper_not_found".to_string()),
+            }
+        }
+    }
+
+    Ok(statuses)
+}
+
+// The abs page's submission history, one line per version:
+// "[v1] Mon, 22 Jan 2024 18:00:01 UTC (1,234 KB)". Older versions are links.
+fn parse_submission_history(html: &str) -> Vec<ArxivVersion> {
+    let pattern = match Regex::new(
+        r"\[v(?P<version>[0-9]+)\](?:</a>)?\s*</strong>\s*(?P<date>[^(<]+?)\s*\((?P<size>[^)]*)\)",
+    ) {
+        Ok(pattern) => pattern,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut versions = pattern
+        .captures_iter(html)
+        .filter_map(|captures| {
+            let version = captures["version"].parse::<u32>().ok()?;
+            let date = compact_text(&captures["date"]);
+            let submitted = NaiveDateTime::parse_from_str(
+                date.trim_end_matches(" UTC"),
+                "%a, %d %b %Y %H:%M:%S",
+            )
+            .map(|date| date.and_utc().to_rfc3339())
+            .ok();
+            Some(ArxivVersion {
+                version,
+                submitted,
+                size: non_empty_text(Some(captures["size"].to_string())),
+            })
+        })
+        .collect::<Vec<_>>();
+    versions.sort_by_key(|version| version.version);
+    versions.dedup_by_key(|version| version.version);
+    versions
+}
+
+async fn fetch_submission_history(client: &Client, base_id: &str) -> Option<Vec<ArxivVersion>> {
+    wait_for_arxiv_slot().await;
+    let url = format!("{}/abs/{}", ARXIV_BASE_URL, base_id);
+    debug!("GET {}", url);
+    let response = match client.get(&url).send().await {
+        Ok(response) if response.status().is_success() => response,
+        Ok(response) => {
+            warn!(
+                "arXiv abs page returned {} for {}",
+                response.status(),
+                base_id
+            );
+            return None;
+        }
+        Err(error) => {
+            warn!("Failed to fetch arXiv abs page: {:?}", error);
+            return None;
+        }
+    };
+
+    let versions = parse_submission_history(&response.text().await.ok()?);
+    if versions.is_empty() {
+        None
+    } else {
+        Some(versions)
+    }
+}
+
+// Versions of a paper, oldest first. Dates come from the abs page; if that
+// can't be read, the API still gives the version count plus the first and
+// latest submission dates.
+#[tauri::command]
+async fn list_arxiv_versions(input: String) -> Result<Vec<ArxivVersion>, String> {
+    let (base_id, _) = parse_arxiv_input(&input).ok_or_else(|| {
+        invalid_argument("invalid_link", format!("Not an arXiv URL or ID: {}", input))
+    })?;
+
+    let client = arxiv_client().map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    if let Some(versions) = fetch_submission_history(&client, &base_id).await {
+        return Ok(versions);
+    }
+
+    let (feed, _) = fetch_arxiv_feed(&client, &arxiv_api_base(), &[("id_list", base_id.clone())])
+        .await
+        .map_err(|reason| format!("Failed to fetch arXiv metadata: {}", reason))?;
+    let entry = feed
+        .entry
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Paper not found on arXiv: {}", base_id))?;
+
+    let latest_version = entry_latest_version(&entry, &base_id);
+    Ok((1..=latest_version)
+        .map(|version| ArxivVersion {
+            version,
+            submitted: if version == 1 {
+                entry.published.clone()
+            } else if version == latest_version {
+                entry.updated.clone()
+            } else {
+                None
+            },
+            size: None,
+        })
+        .collect())
+}
+
+#[tauri::command]
+async fn update_arxiv_paper(
+    app: AppHandle,
+    metadata_path: String,
+    conflict_policy: String,
+) -> Result<ArxivImportResult, String> {
+    if conflict_policy != "keep" && conflict_policy != "replace" {
+        return Err(format!("Unsupported conflict policy: {}", conflict_policy));
+    }
+
+    let sidecar_path = Path::new(&metadata_path);
+    let (arxiv_id, local_version) = read_arxiv_sidecar_identity(sidecar_path)?;
+    let old_sidecar = sidecar::read(sidecar_path)?;
+    let target_dir = sidecar_path
+        .parent()
+        .map(|parent| parent.to_string_lossy().to_string())
+        .ok_or_else(|| "Could not determine parent directory".to_string())?;
+
+    // Filenames carry the version, so the newer revision lands next to the
+    // old one rather than colliding with it.
+    let result =
+        import_arxiv_paper(app, arxiv_id, target_dir, "skip".to_string(), None, None).await?;
+    if result.status != "downloaded" {
+        return Ok(result);
+    }
+
+    let new_version = result
+        .paper
+        .as_ref()
+        .map(|paper| paper.version)
+        .unwrap_or(0);
+    if new_version <= local_version {
+        return Ok(result);
+    }
+
+    if let (Some(tags), Some(new_metadata_path)) =
+        (old_sidecar.get("tags"), result.metadata_path.as_deref())
+    {
+        let new_sidecar_path = Path::new(new_metadata_path);
+        let mut new_sidecar = sidecar::read(new_sidecar_path)?;
+        new_sidecar.insert("tags".to_string(), tags.clone());
+        sidecar::write(new_sidecar_path, new_sidecar)?;
+    }
+
+    if conflict_policy == "replace" {
+        let stem = sidecar_stem(sidecar_path).unwrap_or_default();
+        let old_pdf = old_sidecar
+            .get("pdf_path")
+            .and_then(|value| value.as_str())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| sidecar_path.with_file_name(format!("{}.pdf", stem)));
+        let old_files = [
+            old_pdf,
+            sidecar_path.to_path_buf(),
+            sidecar_path.with_file_name(format!("{}.bib", stem)),
+        ];
+        let new_files = [
+            result.pdf_path.clone(),
+            result.metadata_path.clone(),
+            result.bibtex_path.clone(),
+        ];
+
+        for old_file in old_files {
+            let replaced_by_new = new_files
+                .iter()
+                .flatten()
+                .any(|new_file| Path::new(new_file) == old_file);
+            if old_file.exists() && !replaced_by_new {
+                if let Err(error) = fs::remove_file(&old_file) {
+                    error!(
+                        "Failed to remove superseded file {}: {:?}",
+                        old_file.display(),
+                        error
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+fn metadata_sidecar_path(pdf_path: &Path) -> PathBuf {
+    sidecar::sidecar_for_pdf(pdf_path, default_sidecar_format())
+}
+
+#[tauri::command]
+fn get_tags(file_path: String) -> Result<Vec<String>, String> {
+    let sidecar = sidecar::read(&metadata_sidecar_path(Path::new(&file_path)))?;
+
+    Ok(sidecar
+        .get("tags")
+        .and_then(|tags| tags.as_array())
+        .map(|tags| {
+            tags.iter()
+                .filter_map(|tag| tag.as_str().map(|tag| tag.to_string()))
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+#[tauri::command]
+fn set_tags(file_path: String, tags: Vec<String>) -> Result<Vec<String>, String> {
+    let path = Path::new(&file_path);
+
+    if !path.is_file() {
+        return Err(format!("File does not exist: {}", file_path));
+    }
+
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = compact_text(&tag);
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+
+    // Merge into whatever the sidecar already holds so arXiv metadata written
+    // by the importer survives tagging.
+    let sidecar_path = metadata_sidecar_path(path);
+    let mut sidecar = sidecar::read(&sidecar_path)?;
+    sidecar
+        .entry("pdf_path")
+        .or_insert_with(|| serde_json::Value::String(file_path.clone()));
+    sidecar.insert("tags".to_string(), serde_json::json!(normalized));
+    sidecar::write(&sidecar_path, sidecar)?;
+
+    Ok(normalized)
+}
+
+const SEARCH_HIT_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub pdf_path: String,
+    pub metadata_path: String,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub score: i64,
+}
+
+#[tauri::command]
+async fn search_metadata(dir_path: String, query: String) -> Result<Vec<SearchHit>, String> {
+    // Walks the folder and reads every sidecar in it
+    tauri::async_runtime::spawn_blocking(move || search_metadata_in(&dir_path, &query))
+        .await
+        .map_err(|e| format!("Failed to search metadata: {}", e))?
+}
+
+fn search_metadata_in(dir_path: &str, query: &str) -> Result<Vec<SearchHit>, String> {
+    let path = Path::new(dir_path);
+
+    if !path.is_dir() {
+        return Err(format!("Path is not a directory: {}", dir_path));
+    }
+
+    let query = compact_text(query);
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let matcher = SkimMatcherV2::default().ignore_case();
+    let mut hits = Vec::new();
+    let mut searched = HashSet::new();
+
+    for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let stem = match sidecar_stem(entry.path()) {
+            Some(stem) => stem,
+            None => continue,
+        };
+
+        // A PDF with sidecars in several formats is one hit, searched on
+        // their merged fields
+        let own_pdf = entry.path().with_file_name(format!("{}.pdf", stem));
+        if !searched.insert(own_pdf.clone()) {
+            continue;
+        }
+        let sidecar = match sidecar::read_for_pdf(&own_pdf) {
+            Ok(sidecar) => sidecar,
+            Err(error) => {
+                warn!("Skipping unreadable sidecar: {}", error);
+                continue;
+            }
+        };
+        let metadata_path = sidecar::existing(entry.path().parent().unwrap_or(path), &stem)
+            .unwrap_or_else(|| entry.path().to_path_buf());
+
+        let title = sidecar
+            .get("title")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let authors = sidecar
+            .get("authors")
+            .and_then(|value| value.as_array())
+            .map(|authors| {
+                authors
+                    .iter()
+                    .filter_map(|author| author.as_str().map(|name| name.to_string()))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let title_score = matcher.fuzzy_match(&title, &query);
+        let author_score = authors
+            .iter()
+            .filter_map(|author| matcher.fuzzy_match(author, &query))
+            .max();
+        let score = match title_score.max(author_score) {
+            Some(score) => score,
+            None => continue,
+        };
+
+        let pdf_path = sidecar
+            .get("pdf_path")
+            .and_then(|value| value.as_str())
+            .map(|value| value.to_string())
+            .unwrap_or_else(|| own_pdf.to_string_lossy().to_string());
+
+        hits.push(SearchHit {
+            pdf_path,
+            metadata_path: metadata_path.to_string_lossy().to_string(),
+            title,
+            authors,
+            score,
+        });
+    }
+
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit.score));
+    hits.truncate(SEARCH_HIT_LIMIT);
+
+    Ok(hits)
+}
+
+// Files that belong to a paper and share its file stem
+const SIDECAR_SUFFIXES: &[&str] = &[
+    ".metadata.json",
+    ".metadata.yaml",
+    ".metadata.toml",
+    ".metadata.md",
+    ".bib",
+    ".abstract.md",
+    ".md",
+    ".source.tar.gz",
+    ".source.tex.gz",
+    ".source.tar",
+    ".thumbnail.png",
+    ".thumbnail.jpg",
+];
+const MAX_RENAME_ATTEMPTS: u32 = 1000;
+
+fn file_stem_string(path: &Path) -> String {
+    path.file_stem()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn existing_sidecars(pdf_path: &Path) -> Vec<PathBuf> {
+    let stem = file_stem_string(pdf_path);
+    SIDECAR_SUFFIXES
+        .iter()
+        .map(|suffix| pdf_path.with_file_name(format!("{}{}", stem, suffix)))
+        .filter(|sidecar| sidecar.is_file())
+        .collect()
+}
+
+// The existing file that makes a "rename" import pick another stem, if any
+fn rename_conflict(
+    target: &Path,
+    file_stem: &str,
+    mode: &str,
+    format: &str,
+    attach_in_place: bool,
+) -> Option<PathBuf> {
+    if mode == "metadata_only" {
+        sidecar::existing(target, file_stem)
+    } else if format == "source" {
+        existing_primary_source(target, file_stem)
+    } else if attach_in_place {
+        None
+    } else {
+        // A metadata-only import leaves a sidecar with no PDF beside it,
+        // which a renamed import must not write over either
+        Some(target.join(format!("{}.pdf", file_stem)))
+            .filter(|pdf_path| pdf_path.exists())
+            .or_else(|| sidecar::existing(target, file_stem))
+    }
+}
+
+fn stem_is_free(dir: &Path, stem: &str, extension: &str) -> bool {
+    taken_at_stem(dir, stem, extension).is_none()
+}
+
+// The file or sidecar already using `stem` in dir, if any
+fn taken_at_stem(dir: &Path, stem: &str, extension: &str) -> Option<PathBuf> {
+    std::iter::once(extension)
+        .chain(SIDECAR_SUFFIXES.iter().copied())
+        .map(|suffix| dir.join(format!("{}{}", stem, suffix)))
+        .find(|path| path.exists())
+}
+
+// First "<stem>_N" with neither the file nor any of its sidecars in dir
+fn free_stem(dir: &Path, stem: &str, extension: &str) -> Option<String> {
+    (2..=MAX_RENAME_ATTEMPTS)
+        .map(|n| format!("{}_{}", stem, n))
+        .find(|candidate| stem_is_free(dir, candidate, extension))
+}
+
+// free_stem for a source import, which may end up as any archive kind
+fn free_source_stem(dir: &Path, stem: &str) -> Option<String> {
+    (2..=MAX_RENAME_ATTEMPTS)
+        .map(|n| format!("{}_{}", stem, n))
+        .find(|candidate| {
+            PRIMARY_SOURCE_SUFFIXES
+                .iter()
+                .all(|suffix| stem_is_free(dir, candidate, suffix))
+        })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MovedPaper {
+    pub status: String,
+    pub pdf_path: String,
+    pub sidecars: Vec<String>,
+}
+
+// Shared by every command that relocates papers. on_conflict is "skip",
+// "overwrite" or "rename" (first free "<stem>_N" for the PDF and its sidecars).
+fn move_paper(pdf_path: &Path, dest_dir: &Path, on_conflict: &str) -> Result<MovedPaper, String> {
+    if !matches!(on_conflict, "skip" | "overwrite" | "rename") {
+        return Err(format!("Unsupported conflict policy: {}", on_conflict));
+    }
+
+    if !pdf_path.is_file() {
+        return Err(format!("File does not exist: {}", pdf_path.display()));
+    }
+
+    if !dest_dir.is_dir() {
+        return Err(format!(
+            "Destination is not a directory: {}",
+            dest_dir.display()
+        ));
+    }
+
+    if pdf_path
+        .parent()
+        .map(|parent| is_same_file(parent, dest_dir))
+        .unwrap_or(false)
+    {
+        return Err(format!("File is already in {}", dest_dir.to_string_lossy()));
+    }
+
+    let stem = file_stem_string(pdf_path);
+    let extension = pdf_path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    let mut dest_stem = stem.clone();
+
+    if !stem_is_free(dest_dir, &stem, &extension) {
+        match on_conflict {
+            "skip" => {
+                return Ok(MovedPaper {
+                    status: "skipped".to_string(),
+                    pdf_path: display_string(pdf_path),
+                    sidecars: Vec::new(),
+                })
+            }
+            "overwrite" => {
+                let dest_pdf = dest_dir.join(format!("{}{}", stem, extension));
+                let mut stale = existing_sidecars(&dest_pdf);
+                stale.push(dest_pdf);
+                for file in stale.iter().filter(|file| file.exists()) {
+                    fs::remove_file(file)
+                        .map_err(|e| format!("Failed to replace {}: {}", file.display(), e))?;
+                }
+            }
+            _ => {
+                dest_stem = free_stem(dest_dir, &stem, &extension)
+                    .ok_or_else(|| format!("No free file name for {} in destination", stem))?;
+            }
+        }
+    }
+
+    let sidecars = existing_sidecars(pdf_path);
+    let dest_pdf = dest_dir.join(format!("{}{}", dest_stem, extension));
+    relocate_file(pdf_path, &dest_pdf)
+        .map_err(|e| file_operation_error("move file", pdf_path, e))?;
+
+    let mut moved_sidecars = Vec::new();
+    for sidecar in sidecars {
+        let name = sidecar
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let dest_sidecar = dest_dir.join(format!("{}{}", dest_stem, &name[stem.len()..]));
+
+        if let Err(error) = relocate_file(&sidecar, &dest_sidecar) {
+            error!("Failed to move sidecar {}: {:?}", sidecar.display(), error);
+            continue;
+        }
+
+        if SidecarFormat::from_path(&dest_sidecar).is_some() {
+            let updated = sidecar::read(&dest_sidecar).and_then(|mut object| {
+                object.insert(
+                    "pdf_path".to_string(),
+                    serde_json::json!(display_string(&dest_pdf)),
+                );
+                sidecar::write(&dest_sidecar, object)
+            });
+            if let Err(error) = updated {
+                error!("Failed to update moved metadata: {}", error);
+            }
+        }
+
+        moved_sidecars.push(display_string(&dest_sidecar));
+    }
+    thumbnails::forget(pdf_path);
+
+    Ok(MovedPaper {
+        status: "moved".to_string(),
+        pdf_path: display_string(&dest_pdf),
+        sidecars: moved_sidecars,
+    })
+}
+
+// clear_readonly as in rename_file
+#[tauri::command]
+fn move_with_sidecars(
+    pdf_path: String,
+    dest_dir: String,
+    on_conflict: String,
+    clear_readonly: Option<bool>,
+) -> Result<MovedPaper, String> {
+    let path = &os_path(Path::new(&pdf_path));
+    if clear_readonly.unwrap_or(false) && path.is_file() {
+        clear_readonly_with_sidecars(path)?;
+    }
+    move_paper(path, &os_path(Path::new(&dest_dir)), &on_conflict)
+}
+
+// move_with_sidecars with the importers' argument checks: conflict_policy is
+// skip, overwrite or rename, and errors carry the same codes
+#[tauri::command]
+async fn move_file(
+    old_path: String,
+    target_dir: String,
+    conflict_policy: String,
+    clear_readonly: Option<bool>,
+) -> Result<MovedPaper, String> {
+    importer::check_conflict_policy_in(&conflict_policy, &["skip", "overwrite", "rename"])?;
+    importer::check_target_dir(&target_dir)?;
+
+    // A move across volumes copies the PDF and every sidecar
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = &os_path(Path::new(&old_path));
+        if clear_readonly.unwrap_or(false) && path.is_file() {
+            clear_readonly_with_sidecars(path)?;
+        }
+        move_paper(path, &os_path(Path::new(&target_dir)), &conflict_policy)
+    })
+    .await
+    .map_err(|e| format!("Failed to move file: {}", e))?
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlattenError {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlattenReport {
+    pub moved: Vec<MovedPaper>,
+    // Left where they were under on_conflict "skip"
+    pub skipped: Vec<String>,
+    // Files that couldn't be moved, plus folders the walk couldn't read
+    pub errors: Vec<FlattenError>,
+}
+
+// Moves every PDF below src_dir, at any depth, straight into dest_dir with its
+// sidecars. PDFs already in dest_dir stay put; name clashes follow
+// on_conflict as in move_file.
+#[tauri::command]
+async fn flatten_directory(
+    src_dir: String,
+    dest_dir: String,
+    on_conflict: String,
+) -> Result<FlattenReport, String> {
+    importer::check_conflict_policy_in(&on_conflict, &["skip", "overwrite", "rename"])?;
+    importer::check_target_dir(&dest_dir)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let dest = Path::new(&dest_dir);
+        if !dest.is_dir() {
+            fs::create_dir_all(dest)
+                .map_err(|e| format!("Failed to create destination folder: {}", e))?;
+        }
+
+        let (scan, error_paths) = walk_pdfs_with_error_paths(&src_dir, true, 0, false, None)?;
+        let mut report = FlattenReport {
+            errors: error_paths
+                .iter()
+                .zip(scan.errors)
+                .map(|(path, error)| FlattenError {
+                    path: display_string(path),
+                    error,
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        for file in scan.files {
+            let path = Path::new(&file.path);
+            if path
+                .parent()
+                .map(|parent| is_same_file(parent, dest))
+                .unwrap_or(false)
+            {
+                continue;
+            }
+
+            match move_paper(path, dest, &on_conflict) {
+                Ok(moved) if moved.status == "skipped" => report.skipped.push(file.path),
+                Ok(moved) => report.moved.push(moved),
+                Err(error) => report.errors.push(FlattenError {
+                    path: file.path,
+                    error,
+                }),
+            }
+        }
+
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("Failed to flatten directory: {}", e))?
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizedFile {
+    pub source_path: String,
+    pub target_path: Option<String>,
+    // "planned" (dry run), "moved", "skipped" (conflict policy skip) or
+    // "failed"
+    pub status: String,
+    pub sidecars: Vec<String>,
+    pub error: Option<String>,
+}
+
+// The disk as it will be part way through a batch of moves, so the plan for
+// one file sees what the files before it added or replaced. Mirrors the
+// checks in move_paper.
+#[derive(Default)]
+struct PlannedDisk {
+    added: HashSet<PathBuf>,
+    removed: HashSet<PathBuf>,
+}
+
+impl PlannedDisk {
+    fn exists(&self, path: &Path) -> bool {
+        self.added.contains(path) || (!self.removed.contains(path) && path.exists())
+    }
+
+    fn stem_is_free(&self, dir: &Path, stem: &str, extension: &str) -> bool {
+        std::iter::once(extension)
+            .chain(SIDECAR_SUFFIXES.iter().copied())
+            .all(|suffix| !self.exists(&dir.join(format!("{}{}", stem, suffix))))
+    }
+
+    fn free_stem(&self, dir: &Path, stem: &str, extension: &str) -> Option<String> {
+        (2..=MAX_RENAME_ATTEMPTS)
+            .map(|n| format!("{}_{}", stem, n))
+            .find(|candidate| self.stem_is_free(dir, candidate, extension))
+    }
+}
+
+// Subfolder of source_dir a PDF goes to under `rule`
+fn organize_folder(pdf_path: &Path, rule: &str) -> Result<String, String> {
+    match rule {
+        "by_year_modified" => fs::metadata(pdf_path)
+            .and_then(|metadata| metadata.modified())
+            .map(|modified| DateTime::<chrono::Local>::from(modified).year().to_string())
+            .map_err(|e| format!("Failed to read modification time: {}", e)),
+        "by_first_letter" => Ok(match file_stem_string(pdf_path).chars().next() {
+            Some(c) if c.is_alphabetic() => c.to_uppercase().collect(),
+            Some(c) if c.is_ascii_digit() => "0-9".to_string(),
+            _ => "#".to_string(),
+        }),
+        _ => {
+            let category = sidecar::read_for_pdf(pdf_path)
+                .ok()
+                .and_then(|fields| {
+                    fields
+                        .get("primary_category")
+                        .and_then(|value| value.as_str())
+                        .map(|value| value.trim().replace(['/', '\\'], "_"))
+                })
+                .filter(|category| !category.is_empty());
+            Ok(category.unwrap_or_else(|| "uncategorized".to_string()))
+        }
+    }
+}
+
+// Moves the PDFs directly inside source_dir, with their sidecars, into
+// subfolders named by `rule`: "by_year_modified" (2024/...),
+// "by_first_letter" (A/..., 0-9/..., #/...) or "by_sidecar_category" (the
+// primary arXiv category, "uncategorized" without one). Name clashes follow
+// conflict_policy as in move_file. The plan is worked out in full before
+// anything moves, and dry_run returns it as is, so it is exactly what a real
+// run would do while the folder doesn't change in between.
+#[tauri::command]
+async fn organize_files(
+    source_dir: String,
+    rule: String,
+    dry_run: bool,
+    conflict_policy: String,
+) -> Result<Vec<OrganizedFile>, String> {
+    if !matches!(
+        rule.as_str(),
+        "by_year_modified" | "by_first_letter" | "by_sidecar_category"
+    ) {
+        return Err(invalid_argument(
+            "invalid_input",
+            format!("Unknown organize rule: {}", rule),
+        ));
+    }
+    importer::check_conflict_policy_in(&conflict_policy, &["skip", "overwrite", "rename"])?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let source = &os_path(Path::new(&source_dir));
+        if !source.is_dir() {
+            return Err(format!("Directory does not exist: {}", source_dir));
+        }
+
+        // Symlinks are left alone: moving one would move the link, not the paper
+        let mut pdfs = fs::read_dir(source)
+            .map_err(|e| format!("Failed to read directory: {}", e))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+            .map(|entry| entry.path())
+            .filter(|path| is_pdf_path(path))
+            .collect::<Vec<_>>();
+        pdfs.sort();
+
+        let mut disk = PlannedDisk::default();
+        let mut items = Vec::new();
+        let mut targets = Vec::new();
+        for pdf in &pdfs {
+            let mut item = OrganizedFile {
+                source_path: display_string(pdf),
+                target_path: None,
+                status: "failed".to_string(),
+                sidecars: Vec::new(),
+                error: None,
+            };
+            let folder = match organize_folder(pdf, &rule) {
+                Ok(folder) => folder,
+                Err(error) => {
+                    item.error = Some(error);
+                    items.push(item);
+                    targets.push(None);
+                    continue;
+                }
+            };
+
+            let dir = source.join(folder);
+            // create_dir_all would fail on it in the real run
+            if dir.exists() && !dir.is_dir() {
+                item.error = Some(format!(
+                    "Failed to create folder {}: a file with that name exists",
+                    display_string(&dir)
+                ));
+                items.push(item);
+                targets.push(None);
+                continue;
+            }
+            let stem = file_stem_string(pdf);
+            let extension = pdf
+                .extension()
+                .map(|ext| format!(".{}", ext.to_string_lossy()))
+                .unwrap_or_default();
+            let mut dest_stem = stem.clone();
+            if !disk.stem_is_free(&dir, &stem, &extension) {
+                match conflict_policy.as_str() {
+                    "skip" => {
+                        item.status = "skipped".to_string();
+                        item.target_path =
+                            Some(display_string(&dir.join(format!("{}{}", stem, extension))));
+                        items.push(item);
+                        targets.push(None);
+                        continue;
+                    }
+                    "overwrite" => {
+                        for suffix in std::iter::once(extension.as_str())
+                            .chain(SIDECAR_SUFFIXES.iter().copied())
+                        {
+                            let replaced = dir.join(format!("{}{}", stem, suffix));
+                            disk.added.remove(&replaced);
+                            disk.removed.insert(replaced);
+                        }
+                    }
+                    _ => match disk.free_stem(&dir, &stem, &extension) {
+                        Some(free) => dest_stem = free,
+                        None => {
+                            item.error =
+                                Some(format!("No free file name for {} in destination", stem));
+                            items.push(item);
+                            targets.push(None);
+                            continue;
+                        }
+                    },
+                }
+            }
+
+            let dest_pdf = dir.join(format!("{}{}", dest_stem, extension));
+            for sidecar in existing_sidecars(pdf) {
+                let name = sidecar
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let dest_sidecar = dir.join(format!("{}{}", dest_stem, &name[stem.len()..]));
+                item.sidecars.push(display_string(&dest_sidecar));
+                disk.removed.remove(&dest_sidecar);
+                disk.added.insert(dest_sidecar);
+            }
+            item.status = "planned".to_string();
+            item.target_path = Some(display_string(&dest_pdf));
+            disk.removed.remove(&dest_pdf);
+            disk.added.insert(dest_pdf);
+            items.push(item);
+            targets.push(Some(dir));
+        }
+
+        if dry_run {
+            return Ok(items);
+        }
+
+        for (item, (pdf, target)) in items.iter_mut().zip(pdfs.iter().zip(targets)) {
+            let Some(dir) = target else {
+                continue;
+            };
+            let moved = fs::create_dir_all(&dir)
+                .map_err(|e| format!("Failed to create folder {}: {}", dir.display(), e))
+                .and_then(|_| move_paper(pdf, &dir, &conflict_policy));
+            match moved {
+                Ok(moved) => {
+                    item.status = moved.status;
+                    item.target_path = Some(moved.pdf_path);
+                    item.sidecars = moved.sidecars;
+                }
+                Err(error) => {
+                    item.status = "failed".to_string();
+                    item.sidecars.clear();
+                    item.error = Some(error);
+                }
+            }
+        }
+
+        Ok(items)
+    })
+    .await
+    .map_err(|e| format!("Failed to organize files: {}", e))?
+}
+
+const COPY_CHUNK_BYTES: usize = 1024 * 1024;
+// Smaller copies are over before a progress bar would show
+const COPY_PROGRESS_MIN_BYTES: u64 = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CopiedFile {
+    // "copied", or "skipped" under conflict policy skip
+    pub status: String,
+    pub destination: String,
+    pub bytes_copied: u64,
+    pub sidecars: Vec<String>,
+}
+
+// Copies through "<to>.part" so a failed copy leaves nothing at `to`, and
+// keeps the source's mtime. Large files report "copy-progress" per chunk.
+fn copy_via_part(app: Option<&AppHandle>, from: &Path, to: &Path) -> io::Result<u64> {
+    let part = importer::part_path(to);
+    let copied = (|| {
+        let mut reader = fs::File::open(from)?;
+        let source = reader.metadata()?;
+        let total = source.len();
+        let mut writer = fs::File::create(&part)?;
+
+        let mut buffer = vec![0u8; COPY_CHUNK_BYTES];
+        let mut copied = 0u64;
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            writer.write_all(&buffer[..read])?;
+            copied += read as u64;
+
+            if let Some(app) = app.filter(|_| total >= COPY_PROGRESS_MIN_BYTES) {
+                let _ = app.emit(
+                    "copy-progress",
+                    serde_json::json!({
+                        "sourcePath": from.to_string_lossy().to_string(),
+                        "destinationPath": to.to_string_lossy().to_string(),
+                        "bytesCopied": copied,
+                        "totalBytes": total,
+                    }),
+                );
+            }
+        }
+
+        writer.set_permissions(source.permissions())?;
+        if let Ok(modified) = source.modified() {
+            writer.set_modified(modified)?;
+        }
+        writer.sync_all()?;
+        Ok(copied)
+    })();
+
+    match copied.and_then(|copied| fs::rename(&part, to).map(|_| copied)) {
+        Ok(copied) => Ok(copied),
+        Err(error) => {
+            let _ = fs::remove_file(&part);
+            Err(error)
+        }
+    }
+}
+
+// Duplicates a file into target_dir, as new_name if given (the source's
+// extension is added when missing). conflict_policy follows move_file, and
+// include_sidecars copies the same-stem sidecars along with it.
+#[tauri::command]
+async fn copy_file(
+    app: AppHandle,
+    source_path: String,
+    target_dir: String,
+    new_name: Option<String>,
+    conflict_policy: String,
+    include_sidecars: Option<bool>,
+) -> Result<CopiedFile, String> {
+    importer::check_conflict_policy_in(&conflict_policy, &["skip", "overwrite", "rename"])?;
+    importer::check_target_dir(&target_dir)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let source = &os_path(Path::new(&source_path));
+        let target = &os_path(Path::new(&target_dir));
+        if !source.is_file() {
+            return Err(format!("File does not exist: {}", source.display()));
+        }
+        if !target.is_dir() {
+            return Err(format!(
+                "Destination is not a directory: {}",
+                target.display()
+            ));
+        }
+
+        let extension = source
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_default();
+        let stem = match non_empty_text(new_name) {
+            Some(name) if name.contains(['/', '\\']) => {
+                return Err(invalid_argument(
+                    "invalid_input",
+                    format!("New name must be a file name, not a path: {}", name),
+                ))
+            }
+            Some(name) => match name.len().checked_sub(extension.len()) {
+                Some(split)
+                    if !extension.is_empty()
+                        && name.is_char_boundary(split)
+                        && name[split..].eq_ignore_ascii_case(&extension) =>
+                {
+                    name[..split].to_string()
+                }
+                _ => name,
+            },
+            None => file_stem_string(source),
+        };
+
+        let mut dest_stem = stem.clone();
+        let mut destination = target.join(format!("{}{}", stem, extension));
+        if is_same_file(source, &destination) {
+            return Err(format!("Cannot copy {} onto itself", source.display()));
+        }
+
+        let with_sidecars = include_sidecars.unwrap_or(false);
+        let taken = if with_sidecars {
+            !stem_is_free(target, &stem, &extension)
+        } else {
+            destination.exists()
+        };
+        if taken {
+            match conflict_policy.as_str() {
+                "skip" => {
+                    return Ok(CopiedFile {
+                        status: "skipped".to_string(),
+                        destination: display_string(&destination),
+                        bytes_copied: 0,
+                        sidecars: Vec::new(),
+                    })
+                }
+                "overwrite" => {}
+                _ => {
+                    dest_stem = free_stem(target, &stem, &extension)
+                        .ok_or_else(|| format!("No free file name for {} in destination", stem))?;
+                    destination = target.join(format!("{}{}", dest_stem, extension));
+                }
+            }
+        }
+
+        let bytes_copied = copy_via_part(Some(&app), source, &destination)
+            .map_err(|e| format!("Failed to copy file: {}", e))?;
+
+        let mut sidecars = Vec::new();
+        let source_stem = file_stem_string(source);
+        for sidecar in existing_sidecars(source)
+            .into_iter()
+            .filter(|_| with_sidecars)
+        {
+            let name = sidecar
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let dest_sidecar = target.join(format!("{}{}", dest_stem, &name[source_stem.len()..]));
+
+            if let Err(error) = copy_via_part(None, &sidecar, &dest_sidecar) {
+                error!("Failed to copy sidecar {}: {:?}", sidecar.display(), error);
+                continue;
+            }
+
+            if SidecarFormat::from_path(&dest_sidecar).is_some() {
+                let updated = sidecar::read(&dest_sidecar).and_then(|mut object| {
+                    object.insert(
+                        "pdf_path".to_string(),
+                        serde_json::json!(display_string(&destination)),
+                    );
+                    sidecar::write(&dest_sidecar, object)
+                });
+                if let Err(error) = updated {
+                    error!("Failed to update copied metadata: {}", error);
+                }
+            }
+
+            sidecars.push(display_string(&dest_sidecar));
+        }
+
+        Ok(CopiedFile {
+            status: "copied".to_string(),
+            destination: display_string(&destination),
+            bytes_copied,
+            sidecars,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to copy file: {}", e))?
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkedFile {
+    pub path: String,
+    // The kind actually created: a symlink request can end up as a hard link
+    pub link_type: String,
+}
+
+// What Windows returns for symlinks without Developer Mode or admin rights
+#[cfg(windows)]
+const ERROR_PRIVILEGE_NOT_HELD: i32 = 1314;
+
+fn hard_link(source: &Path, dest: &Path) -> Result<(), String> {
+    fs::hard_link(source, dest).map_err(|e| {
+        if e.kind() == io::ErrorKind::CrossesDevices {
+            invalid_argument(
+                "cross_device",
+                format!(
+                    "Cannot hard link {} into {}: hard links must stay on one volume",
+                    source.display(),
+                    dest.parent().unwrap_or(dest).display()
+                ),
+            )
+        } else {
+            format!("Failed to create hard link: {}", e)
+        }
+    })
+}
+
+#[cfg(unix)]
+fn symlink(source: &Path, dest: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(source, dest)
+}
+
+#[cfg(windows)]
+fn symlink(source: &Path, dest: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(source, dest)
+}
+
+// Puts a link to source_path into target_dir under the same name, so one
+// paper can sit in several project folders without extra copies. link_type
+// is "symlink" or "hardlink". Where Windows won't allow symlinks the file is
+// hard linked instead if it is on the same volume, otherwise the error is
+// "needs_developer_mode". Sidecars are not linked.
+#[tauri::command]
+async fn link_file(
+    source_path: String,
+    target_dir: String,
+    link_type: String,
+) -> Result<LinkedFile, String> {
+    if !matches!(link_type.as_str(), "symlink" | "hardlink") {
+        return Err(invalid_argument(
+            "invalid_input",
+            format!(
+                "Unknown link type (expected symlink or hardlink): {}",
+                link_type
+            ),
+        ));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let source = &os_path(Path::new(&source_path));
+        let target = &os_path(Path::new(&target_dir));
+        if !source.is_file() {
+            return Err(format!("File does not exist: {}", source.display()));
+        }
+        if !target.is_dir() {
+            return Err(format!(
+                "Destination is not a directory: {}",
+                target.display()
+            ));
+        }
+
+        let name = source
+            .file_name()
+            .ok_or_else(|| format!("Not a file path: {}", source.display()))?;
+        let destination = target.join(name);
+        if destination.symlink_metadata().is_ok() {
+            return Err(invalid_argument(
+                "file_exists",
+                format!("{} already exists", display_path(&destination).display()),
+            ));
+        }
+
+        let created = if link_type == "hardlink" {
+            hard_link(source, &destination)?;
+            "hardlink"
+        } else {
+            // Absolute, so the link still works when the folders move apart
+            let link_target = canonical_path(source)
+                .map(|path| os_path(&path))
+                .map_err(|e| format!("Failed to resolve {}: {}", source.display(), e))?;
+            match symlink(&link_target, &destination) {
+                Ok(()) => "symlink",
+                #[cfg(windows)]
+                Err(e) if e.raw_os_error() == Some(ERROR_PRIVILEGE_NOT_HELD) => {
+                    match hard_link(source, &destination) {
+                        Ok(()) => "hardlink",
+                        Err(error) if error.starts_with("cross_device:") => {
+                            return Err(invalid_argument(
+                                "needs_developer_mode",
+                                "Creating symlinks needs Developer Mode (or admin rights) on Windows, and a hard link can't reach another volume".to_string(),
+                            ))
+                        }
+                        Err(error) => return Err(error),
+                    }
+                }
+                Err(e) => return Err(format!("Failed to create symlink: {}", e)),
+            }
+        };
+
+        Ok(LinkedFile {
+            path: display_string(&destination),
+            link_type: created.to_string(),
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to link file: {}", e))?
+}
+
+// Files the app removed itself in the last APP_REMOVAL_WINDOW, so watcher
+// "removed" events for them can say initiatedByApp
+static APP_REMOVALS: Mutex<Option<HashMap<PathBuf, Instant>>> = Mutex::new(None);
+const APP_REMOVAL_WINDOW: Duration = Duration::from_secs(10);
+
+// Watchers report paths under the folder as it was given, so both sides are
+// compared by canonical parent (the file itself is gone by then)
+fn removal_key(path: &Path) -> PathBuf {
+    match (
+        path.parent().and_then(|parent| parent.canonicalize().ok()),
+        path.file_name(),
+    ) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn note_app_removal(path: &Path) {
+    let mut removals = APP_REMOVALS.lock().unwrap();
+    let removals = removals.get_or_insert_with(HashMap::new);
+    removals.retain(|_, removed_at| removed_at.elapsed() < APP_REMOVAL_WINDOW);
+    removals.insert(removal_key(path), Instant::now());
+}
+
+fn removed_by_app(path: &Path) -> bool {
+    APP_REMOVALS
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|removals| removals.get(&removal_key(path)))
+        .is_some_and(|removed_at| removed_at.elapsed() < APP_REMOVAL_WINDOW)
+}
+
+fn remove_file_or_trash(path: &Path, to_trash: bool) -> Result<(), String> {
+    note_app_removal(path);
+    if to_trash {
+        trash::delete(path).map_err(|e| format!("Failed to move to trash: {}", e))
+    } else {
+        fs::remove_file(path).map_err(|e| file_operation_error("delete file", path, e))
+    }
+}
+
+// Removes a file and (unless include_sidecars is false) its same-stem
+// sidecars, returning the removed paths with the file first. Only the file
+// itself has to go; sidecars that can't be removed are logged and left behind.
+// With clear_readonly the file and its sidecars are made writable first.
+fn remove_with_sidecars(
+    path: &Path,
+    include_sidecars: bool,
+    to_trash: bool,
+    clear_readonly: bool,
+) -> Result<Vec<String>, String> {
+    if !path.is_file() {
+        return Err(format!("File does not exist: {}", path.display()));
+    }
+    if clear_readonly {
+        clear_readonly_with_sidecars(path)?;
+    }
+
+    let sidecars = if include_sidecars {
+        existing_sidecars(path)
+    } else {
+        Vec::new()
+    };
+    remove_file_or_trash(path, to_trash)?;
+
+    let mut removed = vec![display_string(path)];
+    for sidecar in sidecars {
+        match remove_file_or_trash(&sidecar, to_trash) {
+            Ok(()) => removed.push(display_string(&sidecar)),
+            Err(error) => warn!("Failed to delete sidecar {}: {}", sidecar.display(), error),
+        }
+    }
+
+    Ok(removed)
+}
+
+#[tauri::command]
+fn delete_paper(pdf_path: String, to_trash: bool) -> Result<Vec<String>, String> {
+    let path = &os_path(Path::new(&pdf_path));
+    check_inside_library_roots(path, false)?;
+    let removed = remove_with_sidecars(path, true, to_trash, false)?;
+    thumbnails::forget(path);
+    Ok(removed)
+}
+
+// Safety rail for the commands that delete or restructure: the path has to
+// sit below one of the library_roots in the importer settings, or with
+// allow_root be one of them. Returns the canonical path.
+fn check_inside_library_roots(path: &Path, allow_root: bool) -> Result<PathBuf, String> {
+    let canonical = fs::canonicalize(path)
+        .map_err(|e| format!("Failed to resolve {}: {}", path.display(), e))?;
+    let inside = configured_library_roots()
+        .iter()
+        .any(|root| (allow_root || canonical != *root) && canonical.starts_with(root));
+    if !inside {
+        return Err(invalid_argument(
+            "outside_library_roots",
+            format!("{} is not inside a library root", path.display()),
+        ));
+    }
+    Ok(canonical)
+}
+
+// clear_readonly as in rename_file
+#[tauri::command]
+fn delete_file_to_trash(
+    file_path: String,
+    include_sidecars: bool,
+    clear_readonly: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let path = &os_path(Path::new(&file_path));
+    check_inside_library_roots(path, false)?;
+    remove_with_sidecars(
+        path,
+        include_sidecars,
+        true,
+        clear_readonly.unwrap_or(false),
+    )
+}
+
+// Not undoable; the frontend is expected to confirm first
+#[tauri::command]
+fn delete_file_permanently(
+    file_path: String,
+    include_sidecars: bool,
+    clear_readonly: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let path = &os_path(Path::new(&file_path));
+    check_inside_library_roots(path, false)?;
+    remove_with_sidecars(
+        path,
+        include_sidecars,
+        false,
+        clear_readonly.unwrap_or(false),
+    )
+}
+
+// A single path component from the frontend. Traversal ("..", anything
+// with a separator, absolute names) is refused outright; the rest goes
+// through the same sanitizer as generated file names.
+fn folder_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty()
+        || name == "."
+        || name == ".."
+        || name.contains(['/', '\\'])
+        || Path::new(name).is_absolute()
+    {
+        return Err(invalid_argument(
+            "invalid_name",
+            format!("Not a valid folder name: '{}'", name),
+        ));
+    }
+
+    let cleaned = sanitize_filename::sanitize(name);
+    if cleaned.is_empty() {
+        return Err(invalid_argument(
+            "invalid_name",
+            format!("Not a valid folder name: '{}'", name),
+        ));
+    }
+    Ok(cleaned)
+}
+
+// Tells every folder watcher that can see `old` that the folder moved to `new`
+// (or went away with None). Watchers on the folder itself or below it are
+// stopped, since their paths no longer exist; the event has "watcherStopped"
+// so the frontend can start a new one on newPath. start_watch_file watchers
+// only ever report on their file and are left out.
+fn notify_watchers_of_folder_change(app: &AppHandle, old: &Path, new: Option<&Path>) {
+    let mut watchers = WATCHERS.lock().unwrap();
+    let Some(watchers_map) = watchers.as_mut() else {
+        return;
+    };
+
+    let affected = watchers_map
+        .iter()
+        .filter(|(_, active)| active.file_path.is_none())
+        .filter(|(_, active)| {
+            active.folder_path.starts_with(old)
+                || (old.starts_with(&active.folder_path)
+                    && (active.recursive || old.parent() == Some(active.folder_path.as_path())))
+        })
+        .map(|(watch_id, active)| (watch_id.clone(), active.folder_path.clone()))
+        .collect::<Vec<_>>();
+
+    for (watch_id, folder_path) in affected {
+        let stopped = folder_path.starts_with(old);
+        if stopped {
+            watchers_map.remove(&watch_id);
+        }
+        let _ = app.emit(
+            "folder-changed",
+            serde_json::json!({
+                "watchId": watch_id,
+                "folderPath": display_string(&folder_path),
+                "eventType": if new.is_some() { "folder_renamed" } else { "folder_removed" },
+                "filePath": display_string(old),
+                "newPath": new.map(display_string),
+                "watcherStopped": stopped,
+            }),
+        );
+    }
+}
+
+// Creates `name` inside `parent`, which has to be a library root or a folder
+// below one. Returns the new folder's path.
+#[tauri::command]
+fn create_directory(parent: String, name: String) -> Result<String, String> {
+    let parent_path = Path::new(&parent);
+    if !parent_path.is_dir() {
+        return Err(format!("Directory does not exist: {}", parent));
+    }
+    let parent_path = check_inside_library_roots(parent_path, true)?;
+
+    let new_path = parent_path.join(folder_name(&name)?);
+    match fs::create_dir(&new_path) {
+        Ok(()) => Ok(new_path.to_string_lossy().to_string()),
+        Err(error) if error.kind() == io::ErrorKind::AlreadyExists => Err(format!(
+            "A file or folder named '{}' already exists in this location",
+            new_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        )),
+        Err(error) => Err(format!("Failed to create directory: {}", error)),
+    }
+}
+
+// Renames a folder below a library root in place; roots themselves are left
+// alone. Returns the new path.
+#[tauri::command]
+fn rename_directory(app: AppHandle, path: String, new_name: String) -> Result<String, String> {
+    let dir_path = Path::new(&path);
+    if !dir_path.is_dir() {
+        return Err(format!("Directory does not exist: {}", path));
+    }
+    let dir_path = check_inside_library_roots(dir_path, false)?;
+
+    let new_name = folder_name(&new_name)?;
+    let new_path = dir_path.with_file_name(&new_name);
+    if new_path == dir_path {
+        return Ok(new_path.to_string_lossy().to_string());
+    }
+    // fs::rename would replace an empty folder of that name
+    if new_path.exists() {
+        return Err(format!(
+            "A file or folder named '{}' already exists in this location",
+            new_name
+        ));
+    }
+
+    fs::rename(&dir_path, &new_path).map_err(|e| format!("Failed to rename directory: {}", e))?;
+    notify_watchers_of_folder_change(&app, &dir_path, Some(&new_path));
+
+    Ok(new_path.to_string_lossy().to_string())
+}
+
+// With must_be_empty the folder is removed only if it has nothing in it;
+// otherwise it goes to the trash with its contents, never a permanent
+// recursive delete. Returns the removed path.
+#[tauri::command]
+fn delete_directory(app: AppHandle, path: String, must_be_empty: bool) -> Result<String, String> {
+    let dir_path = Path::new(&path);
+    if !dir_path.is_dir() {
+        return Err(format!("Directory does not exist: {}", path));
+    }
+    let dir_path = check_inside_library_roots(dir_path, false)?;
+
+    if must_be_empty {
+        let empty = fs::read_dir(&dir_path)
+            .map_err(|e| format!("Failed to read directory: {}", e))?
+            .next()
+            .is_none();
+        if !empty {
+            return Err(format!("Directory is not empty: {}", path));
+        }
+        fs::remove_dir(&dir_path).map_err(|e| format!("Failed to delete directory: {}", e))?;
+    } else {
+        trash::delete(&dir_path).map_err(|e| format!("Failed to delete directory: {}", e))?;
+    }
+    notify_watchers_of_folder_change(&app, &dir_path, None);
+
+    Ok(dir_path.to_string_lossy().to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileMetadata {
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    pub modified: Option<i64>,
+    // Not every filesystem records a creation time
+    pub created: Option<i64>,
+    pub readonly: bool,
+    // As in PdfFile
+    pub is_symlink: bool,
+    pub link_target: Option<String>,
+    pub pdf_version: Option<String>,
+    pub pdfa_conformance: Option<String>,
+}
+
+#[tauri::command]
+fn greet(name: &str) -> String {
+    format!("Hello, {}! You've been greeted from Rust!", name)
+}
+
+// File name (without .log) in the app log dir, see get_log_path
+const LOG_FILE_NAME: &str = "docflow";
+const LOG_MAX_FILE_BYTES: u128 = 5 * 1024 * 1024;
+const LOG_KEPT_FILES: usize = 5;
+
+#[tauri::command]
+fn get_log_path(app: AppHandle) -> Result<String, String> {
+    app.path()
+        .app_log_dir()
+        .map(|dir| {
+            dir.join(format!("{}.log", LOG_FILE_NAME))
+                .to_string_lossy()
+                .to_string()
+        })
+        .map_err(|e| format!("Failed to resolve log directory: {}", e))
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(
+            tauri_plugin_log::Builder::new()
+                .targets([
+                    Target::new(TargetKind::Stderr),
+                    Target::new(TargetKind::LogDir {
+                        file_name: Some(LOG_FILE_NAME.to_string()),
+                    }),
+                ])
+                .level(if cfg!(debug_assertions) {
+                    log::LevelFilter::Debug
+                } else {
+                    log::LevelFilter::Info
+                })
+                .max_file_size(LOG_MAX_FILE_BYTES)
+                .rotation_strategy(RotationStrategy::KeepSome(LOG_KEPT_FILES))
+                .build(),
+        )
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_fs::init())
+        .setup(|app| {
+            load_importer_settings(app.handle());
+            app.manage(DownloadManager::start(app.handle().clone()));
+            app.manage(Concurrency::default());
+            thumbnails::init(app.handle());
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            get_log_path,
+            scan_directory_for_pdfs,
+            scan_directory_paged,
+            library_stats,
+            start_watch_folder,
+            stop_watch_folder,
+            start_watch_file,
+            list_active_watchers,
+            get_file_metadata,
+            get_files_metadata,
+            verify_files_exist,
+            verify_files_detailed,
+            find_moved_file,
+            find_moved_files,
+            rename_file,
+            validate_filename,
+            normalize_filename,
+            batch_rename_files,
+            reveal_in_file_manager,
+            open_with_system_default,
+            files_identical,
+            import_arxiv_paper,
+            cancel_import,
+            import_arxiv_papers,
+            import_arxiv_by_author,
+            import_arxiv_search,
+            downloads::enqueue_download,
+            downloads::cancel_download,
+            downloads::pause_download,
+            downloads::resume_download,
+            downloads::list_downloads,
+            extract_arxiv_ids,
+            get_import_history,
+            clear_import_history,
+            search_arxiv,
+            check_arxiv_updates,
+            list_arxiv_versions,
+            update_arxiv_paper,
+            export_bibtex_for_metadata,
+            export_library,
+            get_tags,
+            set_tags,
+            move_with_sidecars,
+            move_file,
+            copy_file,
+            link_file,
+            flatten_directory,
+            organize_files,
+            delete_paper,
+            delete_file_to_trash,
+            delete_file_permanently,
+            create_directory,
+            rename_directory,
+            delete_directory,
+            search_metadata,
+            get_importer_settings,
+            set_importer_settings,
+            get_importer_status,
+            biorxiv::import_biorxiv_paper,
+            checksum::verify_pdf_checksum,
+            checksum::verify_library_checksums,
+            checksum::hash_files,
+            archive::create_archive,
+            concurrency::get_max_concurrency,
+            concurrency::set_max_concurrency,
+            audit::audit_library,
+            audit::repair_library,
+            doi::import_doi,
+            openreview::import_openreview_paper,
+            url_import::import_pdf_from_url,
+            pdf::verify_pdf,
+            pdf::split_pdf,
+            pdf::merge_pdfs,
+            pdf::remove_annotations,
+            pdf::repair_pdf,
+            pdf::estimate_reading_time,
+            pdf::get_pdf_info,
+            pdf::get_pdf_info_batch,
+            pdf::extract_attachments,
+            pdf::render_page,
+            thumbnails::render_pdf_thumbnail,
+            thumbnails::invalidate_thumbnail_cache
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A captured export.arxiv.org response for hep-th/9711200
+    const HEP_TH_9711200_FEED: &str =
+        include_str!("../tests/fixtures/arxiv_feed_hep-th_9711200.xml");
+
+    #[test]
+    fn parse_arxiv_input_accepts_ids_urls_and_dois() {
+        let accepted: &[(&str, &str, Option<u32>)] = &[
+            ("2301.01234", "2301.01234", None),
+            ("  2301.01234v2 ", "2301.01234", Some(2)),
+            ("arXiv:2301.01234v3", "2301.01234", Some(3)),
+            ("10.48550/arXiv.2301.01234", "2301.01234", None),
+            ("doi:10.48550/ARXIV.2301.01234", "2301.01234", None),
+            (
+                "https://doi.org/10.48550/arXiv.2301.01234",
+                "2301.01234",
+                None,
+            ),
+            (
+                "https://dx.doi.org/10.48550/arXiv.2301.01234v2",
+                "2301.01234",
+                Some(2),
+            ),
+            ("https://arxiv.org/abs/2301.01234v4", "2301.01234", Some(4)),
+            (
+                "https://export.arxiv.org/abs/2301.01234",
+                "2301.01234",
+                None,
+            ),
+            ("browse.arxiv.org/abs/2301.01234v1", "2301.01234", Some(1)),
+            (
+                "https://arxiv.org/abs/2301.01234v2?context=cs.LG#S3",
+                "2301.01234",
+                Some(2),
+            ),
+            ("https://arxiv.org/pdf/2301.01234v2", "2301.01234", Some(2)),
+            (
+                "https://arxiv.org/pdf/2301.01234v2.pdf",
+                "2301.01234",
+                Some(2),
+            ),
+            ("math.GT/0309136v2", "math/0309136", Some(2)),
+            ("arXiv:hep-th/9711200", "hep-th/9711200", None),
+            (
+                "https://arxiv.org/abs/math.GT/0309136v2",
+                "math/0309136",
+                Some(2),
+            ),
+        ];
+        for (input, id, version) in accepted {
+            assert_eq!(
+                parse_arxiv_input(input),
+                Some((id.to_string(), *version)),
+                "{}",
+                input
+            );
+        }
+
+        let rejected = [
+            "",
+            "   ",
+            "2301.012",
+            "2301.01234v",
+            "arXiv:",
+            "10.1000/182",
+            "https://doi.org/10.1000/182",
+            "https://example.com/abs/2301.01234",
+            "https://arxiv.org/list/cs.AI/recent",
+            "https://arxiv.org/abs/",
+            "math.GT/03091",
+        ];
+        for input in rejected {
+            assert_eq!(parse_arxiv_input(input), None, "{}", input);
+        }
+    }
+
+    // The error code of a rejected import, or the skip reason of one that
+    // ends before the network ("" if it would go on to fetch)
+    fn import_check(
+        input: &str,
+        target_dir: &str,
+        conflict_policy: &str,
+        options: ArxivImportOptions,
+        library: &LibraryIndex,
+    ) -> Result<String, String> {
+        match check_import_arguments(input, target_dir, conflict_policy, &options, library) {
+            Ok(Ok(_)) => Ok(String::new()),
+            Ok(Err(skipped)) => {
+                assert_eq!(skipped.status, "skipped");
+                Ok(skipped.reason.unwrap_or_default())
+            }
+            Err(error) => Err(error.split(':').next().unwrap_or_default().to_string()),
+        }
+    }
+
+    #[test]
+    fn import_argument_errors_and_skips() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().to_string_lossy().to_string();
+        let blocked = dir.path().join("a_file");
+        fs::write(&blocked, b"").unwrap();
+        let under_file = blocked.join("target").to_string_lossy().to_string();
+        let no_library = LibraryIndex::new();
+        let options = |configure: fn(&mut ArxivImportOptions)| {
+            let mut options = ArxivImportOptions::default();
+            configure(&mut options);
+            options
+        };
+
+        let errors: &[(&str, &str, &str, ArxivImportOptions, &str)] = &[
+            (
+                "2301.01234",
+                &target,
+                "overwrite",
+                options(|_| {}),
+                "invalid_conflict_policy",
+            ),
+            (
+                "2301.01234",
+                &target,
+                "skip",
+                options(|o| o.mode = Some("bogus".to_string())),
+                "invalid_mode",
+            ),
+            (
+                "2301.01234",
+                &target,
+                "skip",
+                options(|o| {
+                    o.metadata_only = true;
+                    o.mode = Some("attach".to_string());
+                }),
+                "invalid_mode",
+            ),
+            (
+                "2301.01234",
+                &target,
+                "skip",
+                options(|o| o.sidecar_format = Some("xml".to_string())),
+                "invalid_sidecar_format",
+            ),
+            (
+                "2301.01234",
+                &target,
+                "skip",
+                options(|o| o.format = Some("epub".to_string())),
+                "invalid_input",
+            ),
+            (
+                "2301.01234",
+                &target,
+                "skip",
+                options(|o| {
+                    o.format = Some("source".to_string());
+                    o.mode = Some("metadata_only".to_string());
+                }),
+                "invalid_mode",
+            ),
+            (
+                "2301.01234",
+                &target,
+                "skip",
+                options(|o| o.filename_template = Some("{doi}".to_string())),
+                "invalid_input",
+            ),
+            (
+                "2301.01234",
+                &target,
+                "skip",
+                options(|o| o.mode = Some("attach".to_string())),
+                "invalid_attachment",
+            ),
+            (
+                "not a paper",
+                &target,
+                "skip",
+                options(|o| o.strict = true),
+                "invalid_link",
+            ),
+            (
+                "2301.01234",
+                "  ",
+                "skip",
+                options(|_| {}),
+                "invalid_target_dir",
+            ),
+            (
+                "2301.01234",
+                "relative/dir",
+                "skip",
+                options(|_| {}),
+                "invalid_target_dir",
+            ),
+        ];
+        for (input, target_dir, policy, options, code) in errors {
+            assert_eq!(
+                import_check(input, target_dir, policy, options.clone(), &no_library),
+                Err(code.to_string()),
+                "{}",
+                code
+            );
+        }
+
+        let mut library = LibraryIndex::new();
+        library.insert(
+            "2301.01234".to_string(),
+            (
+                "/papers/2301.01234v1.metadata.json".to_string(),
+                "/papers/2301.01234v1.pdf".to_string(),
+            ),
+        );
+        let mut missing_attachment = ArxivImportOptions {
+            mode: Some("attach".to_string()),
+            ..Default::default()
+        };
+        missing_attachment.existing_pdf_path =
+            Some(dir.path().join("missing.pdf").to_string_lossy().to_string());
+
+        let skips: &[(&str, &str, ArxivImportOptions, &LibraryIndex, &str)] = &[
+            (
+                "2301.01234",
+                &target,
+                missing_attachment,
+                &no_library,
+                "invalid_attachment",
+            ),
+            (
+                "not a paper",
+                &target,
+                options(|_| {}),
+                &no_library,
+                "invalid_link",
+            ),
+            (
+                "2301.01234",
+                &under_file,
+                options(|_| {}),
+                &no_library,
+                "write_failed",
+            ),
+            (
+                "2301.01234v2",
+                &target,
+                options(|_| {}),
+                &library,
+                "already_in_library",
+            ),
+            ("2301.01234", &target, options(|_| {}), &no_library, ""),
+        ];
+        for (input, target_dir, options, library, reason) in skips {
+            assert_eq!(
+                import_check(input, target_dir, "skip", options.clone(), library),
+                Ok(reason.to_string()),
+                "{}",
+                reason
+            );
+        }
+    }
+
+    #[test]
+    fn library_index_needs_the_pdf_itself() {
+        let dir = tempfile::tempdir().unwrap();
+        let sidecar = |stem: &str, id: &str| {
+            fs::write(
+                dir.path().join(format!("{}.metadata.json", stem)),
+                serde_json::json!({ "arxiv_id": id }).to_string(),
+            )
+            .unwrap();
+        };
+        sidecar("2301.00001v1_Kept", "2301.00001");
+        fs::write(dir.path().join("2301.00001v1_Kept.pdf"), b"%PDF-1.7").unwrap();
+        sidecar("2301.00002v1_Deleted", "2301.00002");
+
+        let index = build_library_index(&[dir.path().to_string_lossy().to_string()]);
+        assert!(index.contains_key("2301.00001"));
+        assert!(!index.contains_key("2301.00002"));
+    }
+
+    #[test]
+    fn rename_treats_a_metadata_only_sidecar_as_taken() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path();
+        let stem = "2301.00001v1_Paper";
+        assert_eq!(rename_conflict(target, stem, "pdf", "pdf", false), None);
+
+        let sidecar = target.join(format!("{}.metadata.yaml", stem));
+        fs::write(&sidecar, "arxiv_id: 2301.00001\n").unwrap();
+        assert_eq!(
+            rename_conflict(target, stem, "pdf", "pdf", false),
+            Some(sidecar.clone())
+        );
+        assert_eq!(
+            rename_conflict(target, stem, "metadata_only", "pdf", false),
+            Some(sidecar)
+        );
+        assert_eq!(
+            free_stem(target, stem, ".pdf").as_deref(),
+            Some("2301.00001v1_Paper_2")
+        );
+
+        let pdf = target.join(format!("{}.pdf", stem));
+        fs::write(&pdf, b"%PDF-1.7").unwrap();
+        assert_eq!(
+            rename_conflict(target, stem, "pdf", "pdf", false),
+            Some(pdf)
+        );
+        // The file being attached is the one at the canonical path
+        assert_eq!(rename_conflict(target, stem, "pdf", "pdf", true), None);
+    }
+
+    #[test]
+    fn source_imports_count_every_archive_kind_as_taken() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path();
+        let stem = "2301.00001v1_Paper";
+        assert_eq!(rename_conflict(target, stem, "full", "source", false), None);
+
+        let single_file = target.join(format!("{}.tex.gz", stem));
+        fs::write(&single_file, [0x1f, 0x8b]).unwrap();
+        assert_eq!(
+            rename_conflict(target, stem, "full", "source", false),
+            Some(single_file)
+        );
+
+        fs::write(target.join(format!("{}_2.tar", stem)), b"").unwrap();
+        assert_eq!(
+            free_source_stem(target, stem).as_deref(),
+            Some("2301.00001v1_Paper_3")
+        );
+    }
+
+    #[test]
+    fn max_depth_zero_is_unlimited_only_when_recursive() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut folder = dir.path().to_path_buf();
+        for name in ["a", "b", "c", "d"] {
+            fs::create_dir_all(&folder).unwrap();
+            fs::write(folder.join(format!("{}.pdf", name)), b"%PDF-1.7").unwrap();
+            folder.push(format!("level_{}", name));
+        }
+        let root = dir.path().to_string_lossy().to_string();
+        let names = |recursive, max_depth| {
+            let scan = walk_pdfs(&root, recursive, max_depth, false, None).unwrap();
+            scan.files
+                .into_iter()
+                .map(|file| file.name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(true, 0), ["a.pdf", "b.pdf", "c.pdf", "d.pdf"]);
+        assert_eq!(names(true, 2), ["a.pdf", "b.pdf"]);
+        // Not recursive is the folder itself, whatever max_depth says
+        assert_eq!(names(false, 0), ["a.pdf"]);
+        assert_eq!(names(false, 3), ["a.pdf"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn scan_keeps_a_symlink_next_to_its_target_as_its_own_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("a.pdf");
+        fs::write(&target, b"%PDF-1.7").unwrap();
+        std::os::unix::fs::symlink(&target, dir.path().join("link.pdf")).unwrap();
+
+        let root = format!("{}/./sub/..", dir.path().display());
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        let mut files = walk_pdfs(&root, false, 0, false, None).unwrap().files;
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let canonical_target = canonical_path(&target).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, target.to_string_lossy());
+        assert!(!files[0].is_symlink);
+        assert_eq!(files[0].link_target, None);
+        assert_eq!(files[1].path, dir.path().join("link.pdf").to_string_lossy());
+        assert!(files[1].is_symlink);
+        assert_eq!(
+            files[1].link_target.as_deref(),
+            Some(&*canonical_target.to_string_lossy())
+        );
+    }
+
+    #[test]
+    fn hard_links_share_a_file_id_and_copies_do_not() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("a.pdf");
+        fs::write(&original, b"%PDF-1.7").unwrap();
+        fs::hard_link(&original, dir.path().join("c-link.pdf")).unwrap();
+        fs::copy(&original, dir.path().join("b-copy.pdf")).unwrap();
+
+        let root = dir.path().to_string_lossy().to_string();
+        let mut files = walk_pdfs(&root, false, 0, false, None).unwrap().files;
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+
+        assert_eq!(files.len(), 3);
+        assert!(files[0].file_id.is_some());
+        assert_eq!(files[2].file_id, files[0].file_id);
+        assert_ne!(files[1].file_id, files[0].file_id);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn sharing_violations_are_reported_as_file_locked() {
+        use std::os::windows::fs::OpenOptionsExt;
+
+        let path = Path::new(r"C:\papers\paper.pdf");
+        for code in [32, 33] {
+            let error =
+                file_operation_error("rename file", path, io::Error::from_raw_os_error(code));
+            assert!(error.starts_with("file_locked: "), "{}", error);
+        }
+
+        // Held open without sharing, the way PDF viewers keep their files
+        let dir = tempfile::tempdir().unwrap();
+        let pdf = dir.path().join("paper.pdf");
+        fs::write(&pdf, b"%PDF-1.7").unwrap();
+        let _open = fs::OpenOptions::new()
+            .read(true)
+            .share_mode(0)
+            .open(&pdf)
+            .unwrap();
+        let error = rename_file(
+            pdf.to_string_lossy().to_string(),
+            "renamed".to_string(),
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert!(error.starts_with("file_locked: "), "{}", error);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn permission_denied_is_reported_as_read_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = Path::new("/papers/paper.pdf");
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        let error = file_operation_error("rename file", path, denied);
+        assert!(error.starts_with("read_only: "), "{}", error);
+        let other = file_operation_error("rename file", path, io::Error::other("disk full"));
+        assert_eq!(other, "Failed to rename file: disk full");
+
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path().join("read-only");
+        fs::create_dir(&folder).unwrap();
+        let pdf = folder.join("paper.pdf");
+        fs::write(&pdf, b"%PDF-1.7").unwrap();
+        fs::set_permissions(&pdf, fs::Permissions::from_mode(0o444)).unwrap();
+
+        // clear_readonly makes the file itself writable again
+        clear_readonly(&pdf).unwrap();
+        assert!(!fs::metadata(&pdf).unwrap().permissions().readonly());
+
+        fs::set_permissions(&folder, fs::Permissions::from_mode(0o555)).unwrap();
+        // Root ignores permissions, so there is nothing to observe then
+        let probe = folder.join("probe");
+        if fs::write(&probe, b"").is_ok() {
+            let _ = fs::remove_file(&probe);
+            fs::set_permissions(&folder, fs::Permissions::from_mode(0o755)).unwrap();
+            return;
+        }
+        let error = rename_file(
+            pdf.to_string_lossy().to_string(),
+            "renamed".to_string(),
+            None,
+            None,
+        );
+        fs::set_permissions(&folder, fs::Permissions::from_mode(0o755)).unwrap();
+        let error = error.unwrap_err();
+        assert!(error.starts_with("read_only: "), "{}", error);
+        assert!(pdf.is_file());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn paths_past_max_path_scan_and_rename() {
+        // 200 UTF-16 units but 400 UTF-8 bytes: still a legacy path
+        let accented = PathBuf::from(format!(r"C:\{}.pdf", "é".repeat(195)));
+        assert_eq!(os_path(&accented), accented);
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut folder = dir.path().to_path_buf();
+        while folder.as_os_str().len() <= 300 {
+            folder.push("a folder name long enough to add up quickly");
+        }
+        fs::create_dir_all(os_path(&folder)).unwrap();
+        let pdf = folder.join("paper.pdf");
+        fs::write(os_path(&pdf), b"%PDF-1.7").unwrap();
+        assert!(os_path(&pdf).starts_with(r"\\?\"));
+
+        let scan = walk_pdfs(&dir.path().to_string_lossy(), true, 0, false, None).unwrap();
+        assert_eq!(scan.error_count, 0, "{:?}", scan.errors);
+        assert_eq!(scan.files.len(), 1);
+        let found = &scan.files[0].path;
+        assert!(!found.starts_with(r"\\?\"));
+        assert!(found.encode_utf16().count() > 300);
+
+        let renamed = rename_file(found.clone(), "renamed".to_string(), None, None).unwrap();
+        assert_eq!(PathBuf::from(&renamed), folder.join("renamed.pdf"));
+        assert!(os_path(Path::new(&renamed)).is_file());
+        assert!(!os_path(&pdf).exists());
+    }
+
+    // Built from bytes so no editor or checkout can recompose them
+    fn decomposed_name() -> String {
+        String::from_utf8(b"Schro\xcc\x88dinger.pdf".to_vec()).unwrap()
+    }
+
+    fn composed_name() -> String {
+        String::from_utf8(b"Schr\xc3\xb6dinger.pdf".to_vec()).unwrap()
+    }
+
+    #[test]
+    fn decomposed_names_match_their_composed_form() {
+        assert_eq!(nfc(&decomposed_name()), composed_name());
+        assert_eq!(nfd(&composed_name()), decomposed_name());
+
+        let dir = tempfile::tempdir().unwrap();
+        let decomposed = dir.path().join(decomposed_name());
+        fs::write(&decomposed, b"%PDF-1.7").unwrap();
+        let composed = display_string(&dir.path().join(composed_name()));
+        assert_eq!(
+            verify_files_exist(vec![composed.clone(), display_string(&decomposed)]),
+            [
+                (composed.clone(), true),
+                (display_string(&decomposed), true)
+            ]
+        );
+
+        // The scan reports a path that opens the file, whichever spelling
+        // the filesystem takes
+        let scan = walk_pdfs(&dir.path().to_string_lossy(), false, 0, false, None).unwrap();
+        assert_eq!(scan.files.len(), 1);
+        assert!(Path::new(&scan.files[0].path).is_file());
+
+        let sidecar = dir
+            .path()
+            .join(decomposed_name().replace(".pdf", ".metadata.json"));
+        fs::write(&sidecar, "{}").unwrap();
+        let normalized = normalize_filename(display_string(&decomposed)).unwrap();
+        assert_eq!(normalized, composed);
+        let names = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<HashSet<_>>();
+        assert_eq!(
+            names,
+            HashSet::from([
+                composed_name(),
+                composed_name().replace(".pdf", ".metadata.json"),
+            ])
+        );
+    }
+
+    #[test]
+    fn rename_file_keeps_the_extension_and_moves_sidecars() {
+        let dir = tempfile::tempdir().unwrap();
+        let pdf = dir.path().join("paper.pdf");
+        fs::write(&pdf, b"%PDF-1.7").unwrap();
+        let sidecar = dir.path().join("paper.metadata.json");
+        fs::write(&sidecar, serde_json::json!({ "title": "T" }).to_string()).unwrap();
+        let rename = |from: &Path, to: &str| {
+            rename_file(
+                from.to_string_lossy().to_string(),
+                to.to_string(),
+                None,
+                None,
+            )
+        };
+
+        // The extension in new_name isn't doubled, whatever its case
+        let renamed = PathBuf::from(rename(&pdf, "Renamed.PDF").unwrap());
+        assert_eq!(renamed, dir.path().join("Renamed.pdf"));
+        assert!(renamed.is_file() && !pdf.exists());
+        let moved = dir.path().join("Renamed.metadata.json");
+        assert_eq!(
+            sidecar::read(&moved).unwrap().get("pdf_path"),
+            Some(&serde_json::json!(display_string(&renamed)))
+        );
+
+        // ".pdf" only counts as the extension when it ends the name
+        let renamed = PathBuf::from(rename(&renamed, "notes.pdf.draft").unwrap());
+        assert_eq!(renamed, dir.path().join("notes.pdf.draft.pdf"));
+
+        // Without an extension, new_name is the whole new name
+        let plain = dir.path().join("README");
+        fs::write(&plain, b"text").unwrap();
+        let renamed = PathBuf::from(rename(&plain, "notes.txt").unwrap());
+        assert_eq!(renamed, dir.path().join("notes.txt"));
+        assert!(renamed.is_file());
+    }
+
+    #[test]
+    fn rename_file_counts_sidecars_at_the_target_as_taken() {
+        let dir = tempfile::tempdir().unwrap();
+        let pdf = dir.path().join("paper.pdf");
+        fs::write(&pdf, b"%PDF-1.7").unwrap();
+        let rename = |to: &str, on_conflict: &str| {
+            rename_file(
+                pdf.to_string_lossy().to_string(),
+                to.to_string(),
+                Some(on_conflict.to_string()),
+                None,
+            )
+        };
+
+        assert_eq!(rename("paper", "error").unwrap(), display_string(&pdf));
+        assert!(pdf.is_file());
+
+        // Left behind by a paper that was deleted without its sidecar
+        fs::write(dir.path().join("other.metadata.yaml"), "title: Other\n").unwrap();
+        let error = rename("other", "error").unwrap_err();
+        assert!(error.contains("'other.metadata.yaml'"), "{}", error);
+        assert!(pdf.is_file());
+
+        let renamed = rename("other", "auto_suffix").unwrap();
+        assert_eq!(renamed, display_string(&dir.path().join("other_2.pdf")));
+    }
+
+    #[tokio::test]
+    async fn organize_by_category_reads_any_sidecar_format_and_plans_around_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a.pdf", "b.pdf"] {
+            fs::write(dir.path().join(name), b"%PDF-1.7").unwrap();
+        }
+        fs::write(
+            dir.path().join("a.metadata.yaml"),
+            "primary_category: cs.AI\n",
+        )
+        .unwrap();
+        // Where b.pdf, which has no sidecar, would go
+        fs::write(dir.path().join("uncategorized"), b"").unwrap();
+
+        let source_dir = dir.path().to_string_lossy().to_string();
+        let organize = |dry_run| {
+            organize_files(
+                source_dir.clone(),
+                "by_sidecar_category".to_string(),
+                dry_run,
+                "skip".to_string(),
+            )
+        };
+        let planned = organize(true).await.unwrap();
+        assert_eq!(planned[0].status, "planned");
+        assert_eq!(
+            planned[0].target_path.as_deref(),
+            Some(&*dir.path().join("cs.AI").join("a.pdf").to_string_lossy())
+        );
+        assert_eq!(planned[1].status, "failed");
+
+        let moved = organize(false).await.unwrap();
+        assert_eq!(moved[0].status, "moved");
+        assert_eq!(moved[1].status, "failed");
+        assert_eq!(moved[1].error, planned[1].error);
+        assert!(dir.path().join("b.pdf").is_file());
+    }
+
+    #[tokio::test]
+    async fn export_merges_sidecars_in_every_format() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("paper.pdf"), b"%PDF-1.7").unwrap();
+        fs::write(dir.path().join("paper.metadata.yaml"), "title: Kept\n").unwrap();
+        fs::write(
+            dir.path().join("paper.metadata.json"),
+            serde_json::json!({ "tags": ["read"] }).to_string(),
+        )
+        .unwrap();
+        let output = dir.path().join("export.json");
+
+        let stats = export_library(
+            dir.path().to_string_lossy().to_string(),
+            "json".to_string(),
+            output.to_string_lossy().to_string(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(stats.with_metadata, 1);
+        let rows: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(rows[0]["title"], "Kept");
+        assert_eq!(rows[0]["tags"], serde_json::json!(["read"]));
+    }
+
+    #[tokio::test]
+    async fn search_returns_a_pdf_with_two_sidecar_formats_once() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("paper.pdf"), b"%PDF-1.7").unwrap();
+        fs::write(
+            dir.path().join("paper.metadata.yaml"),
+            "title: Attention Is All You Need\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("paper.metadata.json"),
+            serde_json::json!({ "authors": ["Ashish Vaswani"] }).to_string(),
+        )
+        .unwrap();
+
+        let hits = search_metadata(
+            dir.path().to_string_lossy().to_string(),
+            "attention".to_string(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].title, "Attention Is All You Need");
+        assert_eq!(hits[0].authors, vec!["Ashish Vaswani".to_string()]);
+    }
+
+    #[test]
+    fn import_history_is_capped_and_recents_keep_the_successful_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(IMPORT_HISTORY_FILE);
+        let entry = |arxiv_id: &str, status: &str| ImportHistoryEntry {
+            timestamp: String::new(),
+            input: arxiv_id.to_string(),
+            arxiv_id: Some(arxiv_id.to_string()),
+            version: Some(1),
+            title: None,
+            status: status.to_string(),
+            reason: None,
+            pdf_path: (status == "downloaded").then(|| format!("/papers/{}.pdf", arxiv_id)),
+            metadata_path: None,
+            bytes: None,
+            duration_ms: 0,
+        };
+        for (arxiv_id, status) in [
+            ("2301.00001", "downloaded"),
+            ("2301.00002", "downloaded"),
+            ("2301.00003", "downloaded"),
+            ("2301.00003", "skipped"),
+        ] {
+            append_history_entry(&path, &entry(arxiv_id, status), 3).unwrap();
+        }
+
+        let all = read_import_history(&path, None, None, None, None).unwrap();
+        let ids = all
+            .iter()
+            .map(|entry| entry.input.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["2301.00003", "2301.00003", "2301.00002"]);
+
+        let recents = read_import_history(&path, None, None, None, Some(true)).unwrap();
+        assert_eq!(recents.len(), 2);
+        assert_eq!(recents[0].status, "downloaded");
+        assert_eq!(
+            recents[0].pdf_path.as_deref(),
+            Some("/papers/2301.00003.pdf")
+        );
+    }
+
+    #[test]
+    fn extract_arxiv_ids_from_references() {
+        let text = "See arXiv:2301.01234v2, https://doi.org/10.48550/arXiv.1706.03762 and \
+                    hep-th/9711200 (again: 2301.01234v2). Not ids: 1.2301.01234, \
+                    call 2013.01234, 192.168.1.1, math/0309136x";
+        assert_eq!(
+            extract_arxiv_ids(text.to_string()),
+            ["2301.01234v2", "1706.03762", "hep-th/9711200"]
+        );
+
+        // Long reference lists stay quick: only the bytes before each match
+        // are looked at
+        let many = "[1] arXiv:2301.01234. ".repeat(20_000);
+        assert_eq!(extract_arxiv_ids(many), ["2301.01234"]);
+    }
+
+    #[test]
+    fn interrupted_atomic_write_keeps_the_previous_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("paper.pdf");
+        fs::write(&path, b"%PDF-1.7 previous").unwrap();
+
+        let result = atomic_write_with(&path, |file| {
+            file.write_all(b"%PDF-1.7 half of the ne")?;
+            Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"%PDF-1.7 previous");
+        // The temp file is cleaned up, nothing but the original is left
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    fn single_entry(feed_xml: &str) -> ArxivApiEntry {
+        let mut feed = from_str::<ArxivApiFeed>(feed_xml).unwrap();
+        assert_eq!(feed.entry.len(), 1);
+        feed.entry.remove(0)
+    }
+
+    #[test]
+    fn withdrawal_is_read_from_the_latest_versions_comment() {
+        let feed = include_str!("../tests/fixtures/arxiv_feed_withdrawn.xml");
+        let entry = single_entry(feed);
+        assert_eq!(entry_latest_version(&entry, "1405.01234"), 2);
+        let latest = paper_from_entry(entry, "1405.01234", 2, ARXIV_BASE_URL);
+        assert!(latest.withdrawn);
+
+        // v1 is the paper as it was before the withdrawal
+        let first = paper_from_entry(single_entry(feed), "1405.01234", 1, ARXIV_BASE_URL);
+        assert!(!first.withdrawn);
+    }
+
+    #[test]
+    fn withdrawal_comment_phrasing() {
+        let withdrawn = [
+            "This paper has been withdrawn by the author due to an error in Lemma 3",
+            "This submission has been withdrawn by arXiv administrators due to \
+             inappropriate text overlap with external sources",
+            "The manuscript was withdrawn",
+            "Withdrawn by the authors",
+            "withdrawn",
+            "Paper withdrawn due to a sign error",
+            "The authors have withdrawn this paper",
+        ];
+        for comment in withdrawn {
+            assert!(is_withdrawal_comment(comment), "{}", comment);
+        }
+
+        let not_withdrawn = [
+            "22 pages; v2: compares withdrawn and corrected statements of Lemma 4.1",
+            "Data on withdrawn drug approvals, 14 pages",
+            "v3: the withdrawn claim in Section 5 is replaced",
+            "15 pages, 5 figures",
+        ];
+        for comment in not_withdrawn {
+            assert!(!is_withdrawal_comment(comment), "{}", comment);
+        }
+    }
+
+    #[test]
+    fn cross_listed_entry_matches_any_subject_class_prefix() {
+        let feed = include_str!("../tests/fixtures/arxiv_feed_cross_list.xml");
+        // Cited under the primary or the cross-listed subject class
+        for input in ["math.GT/0309136", "math.DG/0309136v1", "arXiv:math/0309136"] {
+            let (base_id, _) = parse_arxiv_input(input).unwrap();
+            assert_eq!(base_id, "math/0309136", "{}", input);
+            assert_eq!(
+                entry_latest_version(&single_entry(feed), &base_id),
+                2,
+                "{}",
+                input
+            );
+        }
+
+        let paper = paper_from_entry(single_entry(feed), "math/0309136", 2, ARXIV_BASE_URL);
+        assert_eq!(paper.primary_category.as_deref(), Some("math.GT"));
+        assert_eq!(
+            paper.categories,
+            vec!["math.GT".to_string(), "math.DG".to_string()]
+        );
+        assert_eq!(paper.pdf_url, "https://arxiv.org/pdf/math/0309136v2.pdf");
+        assert!(!paper.withdrawn);
+    }
+
+    #[tokio::test]
+    async fn error_feed_is_reported_as_paper_not_found() {
+        // What the API answers, with a 200, for an id it can't parse
+        let feed = include_str!("../tests/fixtures/arxiv_feed_error.xml");
+        assert!(single_entry(feed).is_error_marker());
+
+        let (base, _requests) =
+            importer::testing::serve(vec![(200, "application/atom+xml", feed.to_string())]);
+        let client = arxiv_client().unwrap();
+        let fetched = fetch_arxiv_entry(&client, &base, "2301.99999v").await;
+        assert_eq!(fetched.err(), Some("paper_not_found"));
+    }
+
+    #[test]
+    fn feed_entry_carries_categories_doi_journal_ref_and_comment() {
+        let mut feed = from_str::<ArxivApiFeed>(HEP_TH_9711200_FEED).unwrap();
+        assert_eq!(feed.entry.len(), 1);
+        let paper = paper_from_entry(feed.entry.remove(0), "hep-th/9711200", 3, ARXIV_BASE_URL);
+
+        assert_eq!(paper.primary_category.as_deref(), Some("hep-th"));
+        assert_eq!(paper.categories, vec!["hep-th".to_string()]);
+        assert_eq!(paper.doi.as_deref(), Some("10.1023/A:1026654312961"));
+        assert_eq!(
+            paper.journal_ref.as_deref(),
+            Some("Adv.Theor.Math.Phys.2:231-252,1998")
+        );
+        assert_eq!(
+            paper.comment.as_deref(),
+            Some(
+                "20 pages, harvmac, v2: section on AdS_2 corrected, references added, v3: \
+                 more references and a sign in eq. (3.6) corrected"
+            )
+        );
+    }
+}
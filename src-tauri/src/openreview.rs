@@ -0,0 +1,330 @@
+use log::{debug, error, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use tauri::State;
+use url::Url;
+
+use crate::downloads::DownloadManager;
+use crate::importer::{self, compact_text, non_empty_text};
+use crate::sidecar;
+use crate::SidecarFields;
+
+const OPENREVIEW_API_URL: &str = "https://api2.openreview.net/notes";
+const OPENREVIEW_BASE_URL: &str = "https://openreview.net";
+const OPENREVIEW_HOSTS: &[&str] = &["openreview.net", "www.openreview.net"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenReviewPaperMetadata {
+    pub id: String,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub summary: String,
+    // e.g. "ICLR 2024 poster" or "Submitted to ICLR 2024"
+    pub venue: Option<String>,
+    pub venue_id: Option<String>,
+    // From the forum's decision note, e.g. "Accept (oral)"
+    pub decision: Option<String>,
+    pub withdrawn: bool,
+    pub abs_url: String,
+    pub pdf_url: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpenReviewImportResult {
+    pub status: String,
+    pub reason: Option<String>,
+    pub detail: Option<String>,
+    pub pdf_path: Option<String>,
+    pub pdf_size: Option<u64>,
+    pub metadata_path: Option<String>,
+    pub paper: Option<OpenReviewPaperMetadata>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NotesResponse {
+    #[serde(default)]
+    notes: Vec<Note>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Note {
+    #[serde(default)]
+    invitations: Vec<String>,
+    #[serde(default)]
+    content: serde_json::Map<String, Value>,
+}
+
+fn skipped_result(reason: &str, paper: Option<OpenReviewPaperMetadata>) -> OpenReviewImportResult {
+    OpenReviewImportResult {
+        status: "skipped".to_string(),
+        reason: Some(reason.to_string()),
+        paper,
+        ..Default::default()
+    }
+}
+
+// Note id from forum, pdf and attachment links ("...?id=XYZ")
+fn parse_openreview_url(input: &str) -> Option<String> {
+    let url = Url::parse(input.trim()).ok()?;
+    let host = url.host_str()?.to_lowercase();
+    if !OPENREVIEW_HOSTS.contains(&host.as_str()) {
+        return None;
+    }
+
+    url.query_pairs()
+        .find(|(key, _)| key == "id")
+        .map(|(_, value)| value.trim().to_string())
+        .filter(|id| {
+            !id.is_empty()
+                && id
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-')
+        })
+}
+
+// API v2 wraps every content field as { "value": ... }
+fn content_value<'a>(content: &'a serde_json::Map<String, Value>, key: &str) -> Option<&'a Value> {
+    content.get(key).and_then(|field| field.get("value"))
+}
+
+fn content_text(content: &serde_json::Map<String, Value>, key: &str) -> Option<String> {
+    non_empty_text(
+        content_value(content, key)?
+            .as_str()
+            .map(|value| value.to_string()),
+    )
+}
+
+async fn fetch_notes(
+    client: &reqwest::Client,
+    query: (&str, &str),
+) -> Result<Vec<Note>, &'static str> {
+    let url = Url::parse_with_params(OPENREVIEW_API_URL, [query]).map_err(|_| "invalid_link")?;
+    debug!("GET {}", url);
+
+    let response = match client.get(url).send().await {
+        Ok(response) => response,
+        Err(error) => {
+            warn!("Failed to fetch OpenReview notes: {:?}", error);
+            return Err("network_error");
+        }
+    };
+
+    // Restricted and deleted notes answer 403/404 rather than an empty list
+    let status = response.status().as_u16();
+    if matches!(status, 403 | 404) {
+        return Err("paper_not_found");
+    }
+    if !response.status().is_success() {
+        warn!("OpenReview returned {}", response.status());
+        return Err("network_error");
+    }
+
+    let body = match response.text().await {
+        Ok(body) => body,
+        Err(error) => {
+            warn!("Failed to read OpenReview notes: {:?}", error);
+            return Err("network_error");
+        }
+    };
+
+    match serde_json::from_str::<NotesResponse>(&body) {
+        Ok(parsed) => Ok(parsed.notes),
+        Err(error) => {
+            warn!("Failed to parse OpenReview notes: {:?}", error);
+            Err("paper_not_found")
+        }
+    }
+}
+
+// The decision lives on a reply note in the forum, not on the submission.
+// Missing decisions (still under review, or the fetch failed) are just None.
+async fn fetch_decision(client: &reqwest::Client, forum_id: &str) -> Option<String> {
+    let notes = fetch_notes(client, ("forum", forum_id)).await.ok()?;
+    notes
+        .iter()
+        .find(|note| {
+            note.invitations
+                .iter()
+                .any(|invitation| invitation.ends_with("/Decision"))
+        })
+        .and_then(|note| content_text(&note.content, "decision"))
+}
+
+fn paper_from_note(note: &Note, id: &str, decision: Option<String>) -> OpenReviewPaperMetadata {
+    let content = &note.content;
+    let venue = content_text(content, "venue");
+    let venue_id = content_text(content, "venueid");
+    let withdrawn = [&venue, &venue_id]
+        .iter()
+        .filter_map(|value| value.as_deref())
+        .any(|value| value.to_lowercase().contains("withdrawn"));
+
+    OpenReviewPaperMetadata {
+        id: id.to_string(),
+        title: content_text(content, "title").unwrap_or_else(|| format!("OpenReview {}", id)),
+        authors: content_value(content, "authors")
+            .and_then(|authors| authors.as_array())
+            .map(|authors| {
+                authors
+                    .iter()
+                    .filter_map(|author| author.as_str().map(compact_text))
+                    .filter(|name| !name.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        summary: content_text(content, "abstract").unwrap_or_default(),
+        venue,
+        venue_id,
+        decision,
+        withdrawn,
+        abs_url: format!("{}/forum?id={}", OPENREVIEW_BASE_URL, id),
+        pdf_url: format!("{}/pdf?id={}", OPENREVIEW_BASE_URL, id),
+    }
+}
+
+fn write_sidecar(
+    paper: &OpenReviewPaperMetadata,
+    pdf_path: Option<&Path>,
+    metadata_path: &Path,
+    extra: SidecarFields,
+) -> Result<(), String> {
+    importer::write_sidecar(paper, "openreview", pdf_path, metadata_path, extra)
+}
+
+#[tauri::command]
+pub async fn import_openreview_paper(
+    downloads: State<'_, DownloadManager>,
+    forum_url: String,
+    target_dir: String,
+    conflict_policy: String,
+) -> Result<OpenReviewImportResult, String> {
+    importer::check_conflict_policy(&conflict_policy)?;
+    importer::check_target_dir(&target_dir)?;
+
+    let id = match parse_openreview_url(&forum_url) {
+        Some(id) => id,
+        None => return Ok(skipped_result("invalid_link", None)),
+    };
+
+    let target = Path::new(&target_dir);
+    if !importer::ensure_target_dir(target) {
+        return Ok(skipped_result("write_failed", None));
+    }
+
+    let client = match crate::arxiv_client() {
+        Ok(client) => client,
+        Err(error) => {
+            error!("Failed to create reqwest client: {:?}", error);
+            return Ok(skipped_result("network_error", None));
+        }
+    };
+
+    let notes = match fetch_notes(&client, ("id", &id)).await {
+        Ok(notes) => notes,
+        Err(reason) => return Ok(skipped_result(reason, None)),
+    };
+    let note = match notes.first() {
+        Some(note) => note,
+        None => return Ok(skipped_result("paper_not_found", None)),
+    };
+
+    let decision = fetch_decision(&client, &id).await;
+    let paper = paper_from_note(note, &id, decision);
+    let has_pdf = content_value(&note.content, "pdf").is_some();
+
+    let file_stem = importer::paper_file_stem(&id, &paper.title);
+    let pdf_path = target.join(format!("{}.pdf", file_stem));
+    let metadata_path = sidecar::sidecar_for_pdf(&pdf_path, crate::default_sidecar_format());
+
+    if pdf_path.exists() {
+        return Ok(OpenReviewImportResult {
+            status: "skipped".to_string(),
+            reason: Some("file_exists".to_string()),
+            pdf_path: Some(pdf_path.to_string_lossy().to_string()),
+            metadata_path: if metadata_path.exists() {
+                Some(metadata_path.to_string_lossy().to_string())
+            } else {
+                None
+            },
+            paper: Some(paper),
+            ..Default::default()
+        });
+    }
+
+    // Staged and renamed into place with the sidecar, as for arXiv
+    let pdf_part = importer::part_path(&pdf_path);
+    let metadata_part = importer::part_path(&metadata_path);
+    importer::remove_stale_part(&pdf_part);
+    importer::remove_stale_part(&metadata_part);
+
+    let download = if has_pdf && !paper.withdrawn {
+        let job = serde_json::json!({ "importer": "openreview", "forum_id": id, "kind": "pdf" });
+        importer::queue_pdf_download(
+            &downloads,
+            &paper.pdf_url,
+            &pdf_part,
+            job,
+            None,
+            "pdf_unavailable",
+        )
+        .await
+    } else {
+        Err(importer::DownloadFailure {
+            reason: "pdf_unavailable",
+            detail: Some("The submission has no accessible PDF".to_string()),
+            status: None,
+            resolved_url: None,
+        })
+    };
+
+    let downloaded = match download {
+        Ok(downloaded) => downloaded,
+        Err(failure) => {
+            // Withdrawn and access-restricted PDFs still get cataloged
+            let reason = match (failure.reason, failure.status) {
+                (_, Some(401 | 403 | 404)) | ("paper_not_found", _) => "pdf_unavailable",
+                (reason, _) => reason,
+            };
+            let mut result = skipped_result(reason, None);
+            result.detail = failure.detail;
+            if reason == "pdf_unavailable" && !metadata_path.exists() {
+                match write_sidecar(&paper, None, &metadata_path, SidecarFields::new()) {
+                    Ok(()) => {
+                        result.metadata_path = Some(metadata_path.to_string_lossy().to_string())
+                    }
+                    Err(error) => error!("Failed to write metadata file: {:?}", error),
+                }
+            }
+            result.paper = Some(paper);
+            return Ok(result);
+        }
+    };
+
+    if let Err(error) = importer::commit_import(
+        Some((&pdf_part, &pdf_path)),
+        &metadata_part,
+        &metadata_path,
+        |part| {
+            write_sidecar(
+                &paper,
+                Some(&pdf_path),
+                part,
+                importer::checksum_fields(&downloaded.sha256),
+            )
+        },
+    ) {
+        error!("{}", error);
+        return Ok(skipped_result("write_failed", Some(paper)));
+    }
+
+    Ok(OpenReviewImportResult {
+        status: "downloaded".to_string(),
+        pdf_path: Some(pdf_path.to_string_lossy().to_string()),
+        pdf_size: Some(downloaded.size),
+        metadata_path: Some(metadata_path.to_string_lossy().to_string()),
+        paper: Some(paper),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,326 @@
+// Optional ORCID iDs for arXiv authors, looked up through the public API's
+// expanded search. Name matching is fuzzy, so this is strictly best effort: an
+// iD is only attached when exactly one plausible profile has the paper's title
+// among its works, and the score behind it is stored alongside.
+
+use log::{debug, warn};
+use reqwest::Client;
+use serde::Deserialize;
+use url::Url;
+
+use crate::importer::compact_text;
+use crate::ArxivAuthor;
+
+const ORCID_SEARCH_URL: &str = "https://pub.orcid.org/v3.0/expanded-search/";
+const ORCID_SEARCH_ROWS: &str = "5";
+// Large collaborations list hundreds of authors; the rest are left alone
+const MAX_ORCID_LOOKUPS: usize = 25;
+const MIN_ORCID_CONFIDENCE: f64 = 0.8;
+
+// Score parts: the name (full, or family name plus first initial), the title
+// match the query already requires, and a shared affiliation
+const FULL_NAME_SCORE: f64 = 0.6;
+const INITIAL_NAME_SCORE: f64 = 0.4;
+const TITLE_SCORE: f64 = 0.3;
+const AFFILIATION_SCORE: f64 = 0.1;
+
+#[derive(Debug, Deserialize)]
+struct ExpandedSearch {
+    #[serde(rename = "expanded-result", default)]
+    expanded_result: Option<Vec<OrcidCandidate>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OrcidCandidate {
+    #[serde(rename = "orcid-id")]
+    orcid_id: String,
+    #[serde(rename = "given-names", default)]
+    given_names: Option<String>,
+    #[serde(rename = "family-names", default)]
+    family_names: Option<String>,
+    #[serde(rename = "credit-name", default)]
+    credit_name: Option<String>,
+    #[serde(rename = "institution-name", default)]
+    institution_name: Option<Vec<String>>,
+}
+
+// Accepts a bare iD or an orcid.org link and returns the bare iD, or None if
+// it isn't one or its check digit doesn't match (ISO 7064 MOD 11-2)
+pub fn parse_orcid_id(value: &str) -> Option<String> {
+    let value = value.trim().trim_end_matches('/');
+    let id = value.rsplit('/').next()?.to_uppercase();
+
+    let digits = id.replace('-', "");
+    let groups_ok = id.len() == 19 && id.split('-').all(|group| group.len() == 4);
+    if !groups_ok || digits.len() != 16 {
+        return None;
+    }
+
+    let (body, check) = digits.split_at(15);
+    let mut total = 0u32;
+    for ch in body.chars() {
+        total = (total + ch.to_digit(10)?) * 2;
+    }
+    let expected = match (12 - total % 11) % 11 {
+        10 => 'X',
+        digit => char::from_digit(digit, 10)?,
+    };
+    check.starts_with(expected).then_some(id)
+}
+
+fn normalize_name(name: &str) -> String {
+    compact_text(&name.to_lowercase().replace(['.', ',', '-'], " "))
+}
+
+fn name_score(author: &str, candidate: &OrcidCandidate) -> f64 {
+    let author = normalize_name(author);
+    let given = normalize_name(candidate.given_names.as_deref().unwrap_or(""));
+    let family = normalize_name(candidate.family_names.as_deref().unwrap_or(""));
+
+    let full_names = [
+        compact_text(&format!("{} {}", given, family)),
+        normalize_name(candidate.credit_name.as_deref().unwrap_or("")),
+    ];
+    if full_names
+        .iter()
+        .any(|name| !name.is_empty() && *name == author)
+    {
+        return FULL_NAME_SCORE;
+    }
+
+    // "J Smith" for "John Smith", and the other way round
+    let same_family = !family.is_empty()
+        && author
+            .strip_suffix(&family)
+            .is_some_and(|rest| rest.is_empty() || rest.ends_with(' '));
+    let same_initial = match (author.chars().next(), given.chars().next()) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    };
+    if same_family && same_initial {
+        INITIAL_NAME_SCORE
+    } else {
+        0.0
+    }
+}
+
+fn shares_affiliation(author: &ArxivAuthor, candidate: &OrcidCandidate) -> bool {
+    let Some(affiliation) = author.affiliation.as_deref().map(str::to_lowercase) else {
+        return false;
+    };
+    candidate
+        .institution_name
+        .iter()
+        .flatten()
+        .map(|institution| institution.trim().to_lowercase())
+        .filter(|institution| !institution.is_empty())
+        .any(|institution| affiliation.contains(&institution) || institution.contains(&affiliation))
+}
+
+// The iD and confidence for `author` among profiles that matched their name
+// and the paper title, or None unless exactly one of them is plausible and
+// it scores at least MIN_ORCID_CONFIDENCE
+fn match_candidates(author: &ArxivAuthor, candidates: &[OrcidCandidate]) -> Option<(String, f64)> {
+    let plausible = candidates
+        .iter()
+        .filter_map(|candidate| {
+            let name = name_score(&author.name, candidate);
+            if name == 0.0 {
+                return None;
+            }
+            let affiliation = if shares_affiliation(author, candidate) {
+                AFFILIATION_SCORE
+            } else {
+                0.0
+            };
+            Some((candidate, name + TITLE_SCORE + affiliation))
+        })
+        .collect::<Vec<_>>();
+
+    // Two profiles with the same name on the same title can't be told apart
+    let [(candidate, score)] = plausible.as_slice() else {
+        return None;
+    };
+    let score = (score * 100.0).round() / 100.0;
+    (score >= MIN_ORCID_CONFIDENCE).then(|| (candidate.orcid_id.clone(), score))
+}
+
+// Lucene phrase for the search query, without anything that would end it
+fn phrase(value: &str) -> String {
+    format!("\"{}\"", compact_text(&value.replace(['"', '\\'], " ")))
+}
+
+async fn search(
+    client: &Client,
+    search_url: &str,
+    name: &str,
+    title: &str,
+) -> Result<Vec<OrcidCandidate>, String> {
+    let query = format!(
+        "given-and-family-names:{} AND work-titles:{}",
+        phrase(name),
+        phrase(title)
+    );
+    let url = Url::parse_with_params(
+        search_url,
+        [("q", query.as_str()), ("rows", ORCID_SEARCH_ROWS)],
+    )
+    .map_err(|e| format!("Failed to build ORCID query: {}", e))?;
+
+    debug!("GET {}", url);
+    let response = client
+        .get(url)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to query ORCID: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("ORCID returned {}", response.status()));
+    }
+
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read ORCID response: {}", e))?;
+    let parsed = serde_json::from_str::<ExpandedSearch>(&body)
+        .map_err(|e| format!("Failed to parse ORCID response: {}", e))?;
+    Ok(parsed.expanded_result.unwrap_or_default())
+}
+
+// Fills in orcid and orcid_confidence where a confident match exists. Returns
+// false if any lookup failed; those authors are just left without an iD.
+pub async fn enrich_authors(client: &Client, authors: &mut [ArxivAuthor], title: &str) -> bool {
+    enrich_authors_at(client, ORCID_SEARCH_URL, authors, title).await
+}
+
+async fn enrich_authors_at(
+    client: &Client,
+    search_url: &str,
+    authors: &mut [ArxivAuthor],
+    title: &str,
+) -> bool {
+    let mut complete = true;
+    for author in authors
+        .iter_mut()
+        .filter(|author| author.orcid.is_none())
+        .take(MAX_ORCID_LOOKUPS)
+    {
+        match search(client, search_url, &author.name, title).await {
+            Ok(candidates) => {
+                let matched = match_candidates(author, &candidates)
+                    .and_then(|(orcid, confidence)| Some((parse_orcid_id(&orcid)?, confidence)));
+                if let Some((orcid, confidence)) = matched {
+                    author.orcid = Some(orcid);
+                    author.orcid_confidence = Some(confidence);
+                }
+            }
+            Err(error) => {
+                warn!("ORCID lookup for {} failed: {}", author.name, error);
+                complete = false;
+            }
+        }
+    }
+    complete
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::testing;
+    use serde_json::json;
+
+    fn author(name: &str, affiliation: Option<&str>) -> ArxivAuthor {
+        ArxivAuthor {
+            name: name.to_string(),
+            affiliation: affiliation.map(str::to_string),
+            orcid: None,
+            orcid_confidence: None,
+        }
+    }
+
+    fn profile(orcid: &str, given: &str, family: &str, institution: &[&str]) -> serde_json::Value {
+        json!({
+            "orcid-id": orcid,
+            "given-names": given,
+            "family-names": family,
+            "credit-name": null,
+            "institution-name": institution,
+        })
+    }
+
+    fn results(profiles: Vec<serde_json::Value>) -> (u16, &'static str, String) {
+        let body = json!({ "expanded-result": profiles, "num-found": profiles.len() });
+        (200, "application/json", body.to_string())
+    }
+
+    #[test]
+    fn parse_orcid_id_checks_the_check_digit() {
+        assert_eq!(
+            parse_orcid_id("https://orcid.org/0000-0002-1825-0097/").as_deref(),
+            Some("0000-0002-1825-0097")
+        );
+        assert_eq!(
+            parse_orcid_id("0000-0002-1694-233x").as_deref(),
+            Some("0000-0002-1694-233X")
+        );
+        assert_eq!(parse_orcid_id("0000-0002-1825-0098"), None);
+        assert_eq!(parse_orcid_id("0000-0002-18250097"), None);
+    }
+
+    #[tokio::test]
+    async fn only_an_unambiguous_match_gets_an_id() {
+        let (base, requests) = testing::serve(vec![
+            results(vec![profile(
+                "0000-0002-1825-0097",
+                "Ada",
+                "Lovelace",
+                &["University of London"],
+            )]),
+            // Two profiles share the name and the title: neither is attached
+            results(vec![
+                profile("0000-0001-5109-3700", "John", "Smith", &[]),
+                profile("0000-0002-1694-233X", "J.", "Smith", &[]),
+            ]),
+            // An initial-only name match needs a shared affiliation as well
+            results(vec![profile("0000-0001-5109-3700", "Grace", "Hopper", &[])]),
+            (503, "text/plain", "Service unavailable".to_string()),
+        ]);
+        let search_url = format!("{}/v3.0/expanded-search/", base);
+        let mut authors = vec![
+            author("Ada Lovelace", Some("University of London")),
+            author("John Smith", None),
+            author("G. Hopper", None),
+            author("Alan Turing", None),
+        ];
+
+        let complete = enrich_authors_at(
+            &Client::new(),
+            &search_url,
+            &mut authors,
+            "Notes on the \"Analytical Engine\"",
+        )
+        .await;
+        assert!(!complete);
+
+        assert_eq!(authors[0].orcid.as_deref(), Some("0000-0002-1825-0097"));
+        assert_eq!(authors[0].orcid_confidence, Some(1.0));
+        for author in &authors[1..] {
+            assert_eq!(author.orcid, None, "{}", author.name);
+            assert_eq!(author.orcid_confidence, None, "{}", author.name);
+        }
+
+        let first = requests.recv().unwrap();
+        assert!(
+            first.starts_with("GET /v3.0/expanded-search/?q="),
+            "{}",
+            first
+        );
+        assert!(first.contains("rows=5"), "{}", first);
+        // Quotes in the title would end the phrase early
+        assert!(
+            first.contains("work-titles%3A%22Notes+on+the+Analytical+Engine%22"),
+            "{}",
+            first
+        );
+    }
+}
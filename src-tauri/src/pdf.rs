@@ -0,0 +1,1531 @@
+use log::warn;
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId};
+use pdfium_render::prelude::{
+    PdfDocument, PdfRenderConfig, Pdfium, PdfiumError, PdfiumInternalError, PdfiumLibraryBindings,
+};
+use regex::bytes::Regex;
+use sanitize_filename::sanitize;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::concurrency::{Budget, Concurrency};
+
+// The spec only requires %%EOF "near" the end; writers commonly append a few
+// bytes of padding or a trailing newline after it.
+const TRAILER_SCAN_BYTES: u64 = 1024;
+// How much of each end of the file pdfa_conformance looks through
+const XMP_SCAN_BYTES: u64 = 1 << 20;
+// Name trees are shallow in practice; this only stops malformed cycles
+const MAX_NAME_TREE_DEPTH: usize = 32;
+// Page attributes a page may inherit from its ancestors in the page tree
+const INHERITABLE_PAGE_KEYS: &[&[u8]] = &[b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfValidity {
+    pub exists: bool,
+    pub is_pdf: bool,
+    pub page_count: Option<u32>,
+    pub truncated: bool,
+}
+
+fn has_pdf_header(file: &mut File) -> io::Result<bool> {
+    let mut header = [0u8; 5];
+    file.seek(SeekFrom::Start(0))?;
+    match file.read_exact(&mut header) {
+        Ok(()) => Ok(&header == b"%PDF-"),
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+fn has_eof_marker(file: &mut File, len: u64) -> io::Result<bool> {
+    file.seek(SeekFrom::Start(len.saturating_sub(TRAILER_SCAN_BYTES)))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+    Ok(tail_has_eof_marker(&tail))
+}
+
+// Whether the file at path ends in a %%EOF marker, reading only its tail
+pub fn file_has_eof_marker(path: &Path) -> io::Result<bool> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    has_eof_marker(&mut file, len)
+}
+
+// Same check for a PDF that is still in memory
+pub fn tail_has_eof_marker(bytes: &[u8]) -> bool {
+    let start = bytes.len().saturating_sub(TRAILER_SCAN_BYTES as usize);
+    bytes[start..].windows(5).any(|window| window == b"%%EOF")
+}
+
+// Version from the "%PDF-x.y" header line
+pub fn header_version(path: &Path) -> Option<String> {
+    let mut header = [0u8; 16];
+    let read = File::open(path).ok()?.read(&mut header).ok()?;
+    let version = header[..read].strip_prefix(b"%PDF-")?;
+    let version = version
+        .iter()
+        .take_while(|byte| byte.is_ascii_digit() || **byte == b'.')
+        .map(|byte| *byte as char)
+        .collect::<String>();
+    if version.is_empty() {
+        None
+    } else {
+        Some(version)
+    }
+}
+
+static XMP_PACKET: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<x:xmpmeta\b.*?</x:xmpmeta>").unwrap());
+static PDFAID_PART: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"pdfaid:part\s*(?:=\s*["']|>)\s*([0-9]+)"#).unwrap());
+static PDFAID_CONFORMANCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"pdfaid:conformance\s*(?:=\s*["']|>)\s*([A-Za-z])"#).unwrap());
+
+// PDF/A identification ("PDF/A-2B" etc.) from the XMP pdfaid schema. PDF/A
+// forbids filtering the metadata stream, so its packet can be found as raw
+// bytes without parsing the document. Writers put it near the start, or near
+// the end when it came with an incremental update, so only the head and the
+// tail of the file are read, and only text inside an x:xmpmeta packet counts:
+// a page that merely quotes "pdfaid:part" doesn't make the file PDF/A.
+pub fn pdfa_conformance(path: &Path) -> Option<String> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let mut regions = Vec::new();
+    if len <= 2 * XMP_SCAN_BYTES {
+        let mut whole = Vec::new();
+        file.read_to_end(&mut whole).ok()?;
+        regions.push(whole);
+    } else {
+        let mut head = vec![0u8; XMP_SCAN_BYTES as usize];
+        file.read_exact(&mut head).ok()?;
+        let mut tail = Vec::new();
+        file.seek(SeekFrom::Start(len - XMP_SCAN_BYTES)).ok()?;
+        file.read_to_end(&mut tail).ok()?;
+        // The newest packet wins when an update replaced the metadata
+        regions.push(tail);
+        regions.push(head);
+    }
+
+    regions.iter().find_map(|region| {
+        XMP_PACKET
+            .find_iter(region)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .find_map(|packet| {
+                let packet = packet.as_bytes();
+                let part = PDFAID_PART.captures(packet)?.get(1)?;
+                let conformance = PDFAID_CONFORMANCE
+                    .captures(packet)
+                    .and_then(|c| c.get(1))
+                    .map(|c| String::from_utf8_lossy(c.as_bytes()).to_uppercase())
+                    .unwrap_or_default();
+                Some(format!(
+                    "PDF/A-{}{}",
+                    String::from_utf8_lossy(part.as_bytes()),
+                    conformance
+                ))
+            })
+    })
+}
+
+// Text strings are either UTF-16BE with a byte order mark or PDFDocEncoding,
+// which matches Latin-1 for everything a title is likely to contain
+fn decode_text_string(bytes: &[u8]) -> String {
+    match bytes.strip_prefix(&[0xfe, 0xff]) {
+        Some(utf16) => {
+            let units = utf16
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect::<Vec<_>>();
+            String::from_utf16_lossy(&units)
+        }
+        None => bytes.iter().map(|byte| *byte as char).collect(),
+    }
+}
+
+// Title from the document Info dictionary, for PDFs already in memory
+pub fn info_title(bytes: &[u8]) -> Option<String> {
+    let document = Document::load_mem(bytes).ok()?;
+    let info = document.trailer.get(b"Info").ok()?;
+    let (_, info) = document.dereference(info).ok()?;
+    let title = info.as_dict().ok()?.get(b"Title").ok()?;
+    let (_, title) = document.dereference(title).ok()?;
+    let title = decode_text_string(title.as_str().ok()?);
+    let title = title.trim();
+    if title.is_empty() {
+        None
+    } else {
+        Some(title.to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn verify_pdf(file_path: String) -> Result<PdfValidity, String> {
+    // A full parse, for the page count
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = &crate::os_path(Path::new(&file_path));
+
+        if !path.exists() {
+            return Ok(PdfValidity {
+                exists: false,
+                is_pdf: false,
+                page_count: None,
+                truncated: false,
+            });
+        }
+
+        if !path.is_file() {
+            return Err(format!("Path is not a file: {}", file_path));
+        }
+
+        let mut file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+        let len = file
+            .metadata()
+            .map_err(|e| format!("Failed to read file metadata: {}", e))?
+            .len();
+
+        let has_header =
+            has_pdf_header(&mut file).map_err(|e| format!("Failed to read file: {}", e))?;
+        if !has_header {
+            return Ok(PdfValidity {
+                exists: true,
+                is_pdf: false,
+                page_count: None,
+                truncated: false,
+            });
+        }
+
+        let truncated =
+            !has_eof_marker(&mut file, len).map_err(|e| format!("Failed to read file: {}", e))?;
+
+        // Loading parses the xref and the page tree but never decodes page
+        // content, so this stays cheap compared to rendering.
+        let page_count = Document::load(path)
+            .ok()
+            .map(|document| document.get_pages().len() as u32);
+
+        Ok(PdfValidity {
+            exists: true,
+            is_pdf: page_count.is_some(),
+            page_count,
+            truncated,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to verify PDF: {}", e))?
+}
+
+// Sorted copy of the ranges, rejecting anything outside 1..=page_count or
+// overlapping a neighbour
+fn validate_page_ranges(ranges: &[(u32, u32)], page_count: u32) -> Result<Vec<(u32, u32)>, String> {
+    if ranges.is_empty() {
+        return Err("No page ranges given".to_string());
+    }
+
+    let mut sorted = ranges.to_vec();
+    sorted.sort_unstable();
+
+    for (start, end) in &sorted {
+        if *start == 0 || start > end {
+            return Err(format!("Invalid page range: {}-{}", start, end));
+        }
+        if *end > page_count {
+            return Err(format!(
+                "Page range {}-{} is outside the document ({} pages)",
+                start, end, page_count
+            ));
+        }
+    }
+
+    for pair in sorted.windows(2) {
+        if pair[1].0 <= pair[0].1 {
+            return Err(format!(
+                "Page ranges overlap: {}-{} and {}-{}",
+                pair[0].0, pair[0].1, pair[1].0, pair[1].1
+            ));
+        }
+    }
+
+    Ok(sorted)
+}
+
+// Writes one PDF per inclusive page range. Each output is the source with the
+// other pages deleted, so the trailer's Info dictionary carries over as is.
+#[tauri::command]
+pub async fn split_pdf(
+    file_path: String,
+    ranges: Vec<(u32, u32)>,
+    output_dir: String,
+) -> Result<Vec<String>, String> {
+    // The document is loaded once and saved again for every range
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = &crate::os_path(Path::new(&file_path));
+        if !path.is_file() {
+            return Err(format!("Path is not a file: {}", file_path));
+        }
+
+        let output = &crate::os_path(Path::new(&output_dir));
+        if !output.is_dir() {
+            return Err(format!("Path is not a directory: {}", output_dir));
+        }
+
+        let document = Document::load(path).map_err(|e| format!("Failed to load PDF: {}", e))?;
+        let page_count = document.get_pages().len() as u32;
+        let ranges = validate_page_ranges(&ranges, page_count)?;
+
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "split".to_string());
+
+        // Check every destination up front so a late collision doesn't leave a
+        // partial set of outputs behind
+        let targets = ranges
+            .iter()
+            .map(|(start, end)| output.join(format!("{}_p{}-{}.pdf", stem, start, end)))
+            .collect::<Vec<PathBuf>>();
+        if let Some(existing) = targets.iter().find(|target| target.exists()) {
+            return Err(format!("File already exists: {}", existing.display()));
+        }
+
+        let mut written = Vec::with_capacity(targets.len());
+        for ((start, end), target) in ranges.iter().zip(&targets) {
+            let mut part = document.clone();
+            let removed = (1..=page_count)
+                .filter(|page| page < start || page > end)
+                .collect::<Vec<_>>();
+            part.delete_pages(&removed);
+            part.prune_objects();
+            part.renumber_objects();
+
+            let mut bytes = Vec::new();
+            part.save_to(&mut bytes)
+                .map_err(|e| format!("Failed to serialize pages {}-{}: {}", start, end, e))?;
+            crate::atomic_write(target, &bytes)
+                .map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+
+            written.push(crate::display_string(target));
+        }
+
+        Ok(written)
+    })
+    .await
+    .map_err(|e| format!("Failed to split PDF: {}", e))?
+}
+
+fn object_type(object: &Object) -> &[u8] {
+    let dict = match object {
+        Object::Dictionary(dict) => dict,
+        Object::Stream(stream) => &stream.dict,
+        _ => return b"",
+    };
+    dict.get(b"Type")
+        .and_then(Object::as_name)
+        .unwrap_or_default()
+}
+
+// Copies attributes the page inherits from its parent nodes, since merged
+// pages hang directly off a new Pages root
+fn flatten_page(document: &Document, page_id: ObjectId) -> Option<Dictionary> {
+    let mut page = document.get_dictionary(page_id).ok()?.clone();
+    let mut parent = page.get(b"Parent").and_then(Object::as_reference).ok();
+
+    while let Some(parent_id) = parent {
+        let node = match document.get_dictionary(parent_id) {
+            Ok(node) => node,
+            Err(_) => break,
+        };
+        for key in INHERITABLE_PAGE_KEYS {
+            if !page.has(key) {
+                if let Ok(value) = node.get(key) {
+                    page.set(key.to_vec(), value.clone());
+                }
+            }
+        }
+        parent = node.get(b"Parent").and_then(Object::as_reference).ok();
+    }
+
+    Some(page)
+}
+
+// Concatenates the inputs in order. Object ids are renumbered per input so
+// they can't collide; outlines are dropped because they point into the old
+// page trees, and the first input's Info dictionary is kept.
+#[tauri::command]
+pub async fn merge_pdfs(
+    inputs: Vec<String>,
+    output_path: String,
+    overwrite: bool,
+) -> Result<crate::FileMetadata, String> {
+    // Every input is loaded and the merged document written out
+    tauri::async_runtime::spawn_blocking(move || {
+        if inputs.is_empty() {
+            return Err("No input PDFs given".to_string());
+        }
+
+        let output = &crate::os_path(Path::new(&output_path));
+        if output.is_dir() {
+            return Err(format!("Output path is a directory: {}", output_path));
+        }
+        if output.exists() && !overwrite {
+            return Err(format!("File already exists: {}", output_path));
+        }
+
+        // Load everything first so a bad input fails before anything is written
+        let mut documents = Vec::with_capacity(inputs.len());
+        for input in &inputs {
+            let path = &crate::os_path(Path::new(input));
+            if !path.is_file() {
+                return Err(format!("Path is not a file: {}", input));
+            }
+            let document =
+                Document::load(path).map_err(|e| format!("Failed to load PDF {}: {}", input, e))?;
+            documents.push(document);
+        }
+
+        let mut max_id = 1;
+        let mut pages = Vec::new();
+        let mut objects = BTreeMap::new();
+        let mut info = None;
+
+        for mut document in documents {
+            document.renumber_objects_with(max_id);
+            max_id = document.max_id + 1;
+
+            if info.is_none() {
+                info = document.trailer.get(b"Info").ok().cloned();
+            }
+
+            for page_id in document.get_pages().into_values() {
+                if let Some(page) = flatten_page(&document, page_id) {
+                    pages.push((page_id, page));
+                }
+            }
+            objects.extend(document.objects);
+        }
+
+        let mut merged = Document::with_version("1.5");
+        for (id, object) in objects {
+            let skip = matches!(
+                object_type(&object),
+                b"Catalog" | b"Pages" | b"Page" | b"Outlines" | b"Outline"
+            );
+            if !skip {
+                merged.objects.insert(id, object);
+            }
+        }
+
+        let pages_id = (max_id, 0);
+        let catalog_id = (max_id + 1, 0);
+        let kids = pages
+            .iter()
+            .map(|(id, _)| Object::Reference(*id))
+            .collect::<Vec<_>>();
+        let count = kids.len() as u32;
+
+        for (id, mut page) in pages {
+            page.set("Parent", pages_id);
+            merged.objects.insert(id, Object::Dictionary(page));
+        }
+        merged.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => count,
+            }),
+        );
+        merged.objects.insert(
+            catalog_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Catalog",
+                "Pages" => pages_id,
+            }),
+        );
+
+        merged.trailer.set("Root", catalog_id);
+        if let Some(info) = info {
+            merged.trailer.set("Info", info);
+        }
+        merged.max_id = catalog_id.0;
+        merged.renumber_objects();
+
+        let mut bytes = Vec::new();
+        merged
+            .save_to(&mut bytes)
+            .map_err(|e| format!("Failed to serialize merged PDF: {}", e))?;
+        crate::atomic_write(output, &bytes)
+            .map_err(|e| format!("Failed to write {}: {}", output_path, e))?;
+
+        crate::get_file_metadata(output_path)
+    })
+    .await
+    .map_err(|e| format!("Failed to merge PDFs: {}", e))?
+}
+
+// Collects the file specifications in a name tree (leaves hold
+// [name1 value1 name2 value2 ...] under Names, inner nodes list Kids)
+fn collect_name_tree<'a>(
+    document: &'a Document,
+    node: &'a Dictionary,
+    depth: usize,
+    values: &mut Vec<&'a Dictionary>,
+) {
+    if depth > MAX_NAME_TREE_DEPTH {
+        return;
+    }
+
+    if let Ok(names) = node.get(b"Names").and_then(Object::as_array) {
+        for value in names.iter().skip(1).step_by(2) {
+            if let Ok((_, Object::Dictionary(spec))) = document.dereference(value) {
+                values.push(spec);
+            }
+        }
+    }
+
+    if let Ok(kids) = node.get(b"Kids").and_then(Object::as_array) {
+        for kid in kids {
+            if let Ok((_, Object::Dictionary(kid))) = document.dereference(kid) {
+                collect_name_tree(document, kid, depth + 1, values);
+            }
+        }
+    }
+}
+
+// (file name, contents) of an embedded file specification. UF is the Unicode
+// name; F is the older byte-string one.
+fn embedded_file(document: &Document, spec: &Dictionary) -> Option<(String, Vec<u8>)> {
+    let name = [b"UF".as_slice(), b"F".as_slice()]
+        .iter()
+        .find_map(|key| {
+            let (_, name) = document.dereference(spec.get(key).ok()?).ok()?;
+            Some(decode_text_string(name.as_str().ok()?))
+        })
+        .unwrap_or_default();
+
+    let files = document
+        .dereference(spec.get(b"EF").ok()?)
+        .ok()?
+        .1
+        .as_dict()
+        .ok()?;
+    let stream = [b"UF".as_slice(), b"F".as_slice()].iter().find_map(|key| {
+        let (_, stream) = document.dereference(files.get(key).ok()?).ok()?;
+        stream.as_stream().ok()
+    })?;
+
+    let contents = if stream.dict.has(b"Filter") {
+        stream.decompressed_content().ok()?
+    } else {
+        stream.content.clone()
+    };
+    Some((name, contents))
+}
+
+// "name_2.ext", "name_3.ext", ... for attachments that share a file name
+fn numbered_file_name(name: &str, n: usize) -> String {
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => format!("{}_{}.{}", stem, n, extension),
+        _ => format!("{}_{}", name, n),
+    }
+}
+
+// Saves every file in the document's EmbeddedFiles name tree to output_dir
+// under its own (sanitized) name
+#[tauri::command]
+pub async fn extract_attachments(
+    file_path: String,
+    output_dir: String,
+) -> Result<Vec<String>, String> {
+    // Loads the whole document and writes out every embedded file
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = &crate::os_path(Path::new(&file_path));
+        if !path.is_file() {
+            return Err(format!("Path is not a file: {}", file_path));
+        }
+
+        let output = &crate::os_path(Path::new(&output_dir));
+        if !output.is_dir() {
+            return Err(format!("Path is not a directory: {}", output_dir));
+        }
+
+        let document = Document::load(path).map_err(|e| format!("Failed to load PDF: {}", e))?;
+        let catalog = document
+            .catalog()
+            .map_err(|e| format!("Failed to read PDF catalog: {}", e))?;
+
+        let tree = catalog
+            .get(b"Names")
+            .and_then(|names| document.dereference(names))
+            .and_then(|(_, names)| names.as_dict())
+            .and_then(|names| names.get(b"EmbeddedFiles"))
+            .and_then(|tree| document.dereference(tree))
+            .and_then(|(_, tree)| tree.as_dict());
+        let tree = match tree {
+            Ok(tree) => tree,
+            Err(_) => return Ok(Vec::new()),
+        };
+
+        let mut specs = Vec::new();
+        collect_name_tree(&document, tree, 0, &mut specs);
+
+        let mut attachments = Vec::new();
+        let mut taken = HashSet::new();
+        for (index, spec) in specs.into_iter().enumerate() {
+            let Some((name, contents)) = embedded_file(&document, spec) else {
+                warn!(
+                    "Skipping unreadable attachment #{} in {}",
+                    index + 1,
+                    file_path
+                );
+                continue;
+            };
+
+            let mut file_name = sanitize(name.trim());
+            if file_name.is_empty() {
+                file_name = format!("attachment_{}", index + 1);
+            }
+            let base_name = file_name.clone();
+            let mut n = 2;
+            while !taken.insert(file_name.to_lowercase()) {
+                file_name = numbered_file_name(&base_name, n);
+                n += 1;
+            }
+
+            attachments.push((output.join(file_name), contents));
+        }
+
+        // Same as split_pdf: refuse before writing anything rather than leave a
+        // partial set behind
+        if let Some((existing, _)) = attachments.iter().find(|(target, _)| target.exists()) {
+            return Err(format!("File already exists: {}", existing.display()));
+        }
+
+        let mut written = Vec::with_capacity(attachments.len());
+        for (target, contents) in attachments {
+            crate::atomic_write(&target, &contents)
+                .map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+            written.push(crate::display_string(&target));
+        }
+
+        Ok(written)
+    })
+    .await
+    .map_err(|e| format!("Failed to extract attachments: {}", e))?
+}
+
+// Annotation flags (PDF 32000-1, 12.5.3) for ones that are never drawn
+const ANNOT_FLAG_HIDDEN: i64 = 1 << 1;
+const ANNOT_FLAG_NO_VIEW: i64 = 1 << 5;
+
+fn number_array(document: &Document, object: &Object) -> Option<Vec<f32>> {
+    let (_, object) = document.dereference(object).ok()?;
+    object
+        .as_array()
+        .ok()?
+        .iter()
+        .map(|item| document.dereference(item).ok()?.1.as_float().ok())
+        .collect()
+}
+
+// The normal appearance: /AP /N is either the stream itself or a dictionary
+// of appearance states, picked by /AS
+fn normal_appearance(document: &Document, annot: &Dictionary) -> Option<ObjectId> {
+    let appearances = annot
+        .get(b"AP")
+        .and_then(|ap| document.dereference(ap))
+        .and_then(|(_, ap)| ap.as_dict())
+        .ok()?;
+    let normal = appearances.get(b"N").ok()?;
+    match document.dereference(normal).ok()? {
+        (Some(id), Object::Stream(_)) => Some(id),
+        (_, Object::Dictionary(states)) => {
+            let state = annot.get(b"AS").and_then(Object::as_name).ok()?;
+            let id = states.get(state).and_then(Object::as_reference).ok()?;
+            matches!(document.get_object(id), Ok(Object::Stream(_))).then_some(id)
+        }
+        _ => None,
+    }
+}
+
+// Maps the appearance's BBox, transformed by its Matrix, onto the
+// annotation's Rect (12.5.5, "Appearance streams")
+fn appearance_matrix(bbox: &[f32], matrix: &[f32], rect: &[f32]) -> Option<[f32; 6]> {
+    if bbox.len() != 4 || matrix.len() != 6 || rect.len() != 4 {
+        return None;
+    }
+    let corners = [
+        (bbox[0], bbox[1]),
+        (bbox[2], bbox[1]),
+        (bbox[0], bbox[3]),
+        (bbox[2], bbox[3]),
+    ]
+    .map(|(x, y)| {
+        (
+            matrix[0] * x + matrix[2] * y + matrix[4],
+            matrix[1] * x + matrix[3] * y + matrix[5],
+        )
+    });
+    let min_x = corners.iter().map(|c| c.0).fold(f32::INFINITY, f32::min);
+    let max_x = corners
+        .iter()
+        .map(|c| c.0)
+        .fold(f32::NEG_INFINITY, f32::max);
+    let min_y = corners.iter().map(|c| c.1).fold(f32::INFINITY, f32::min);
+    let max_y = corners
+        .iter()
+        .map(|c| c.1)
+        .fold(f32::NEG_INFINITY, f32::max);
+    if max_x - min_x <= f32::EPSILON || max_y - min_y <= f32::EPSILON {
+        return None;
+    }
+
+    let (left, right) = (rect[0].min(rect[2]), rect[0].max(rect[2]));
+    let (bottom, top) = (rect[1].min(rect[3]), rect[1].max(rect[3]));
+    let scale_x = (right - left) / (max_x - min_x);
+    let scale_y = (top - bottom) / (max_y - min_y);
+    Some([
+        scale_x,
+        0.0,
+        0.0,
+        scale_y,
+        left - min_x * scale_x,
+        bottom - min_y * scale_y,
+    ])
+}
+
+// The page's own Resources or the ones it inherits, as an owned dictionary
+// (with an owned XObject dictionary) that can be changed for this page alone
+fn page_resources(document: &Document, page_id: ObjectId) -> Dictionary {
+    let mut node = document.get_dictionary(page_id).ok();
+    let mut resources = Dictionary::new();
+    while let Some(dict) = node {
+        if let Ok(found) = dict.get(b"Resources") {
+            if let Ok((_, Object::Dictionary(found))) = document.dereference(found) {
+                resources = found.clone();
+            }
+            break;
+        }
+        node = dict
+            .get(b"Parent")
+            .and_then(Object::as_reference)
+            .and_then(|parent| document.get_dictionary(parent))
+            .ok();
+    }
+
+    let xobjects = resources
+        .get(b"XObject")
+        .and_then(|xobjects| document.dereference(xobjects))
+        .and_then(|(_, xobjects)| xobjects.as_dict())
+        .cloned()
+        .unwrap_or_default();
+    resources.set("XObject", xobjects);
+    resources
+}
+
+// The page's content streams as a list of references
+fn page_contents(document: &Document, page: &Dictionary) -> Vec<Object> {
+    match page.get(b"Contents") {
+        Ok(Object::Reference(id)) => match document.get_object(*id) {
+            Ok(Object::Array(items)) => items.clone(),
+            _ => vec![Object::Reference(*id)],
+        },
+        Ok(Object::Array(items)) => items.clone(),
+        _ => Vec::new(),
+    }
+}
+
+// Draws each visible annotation's appearance stream into the page content,
+// returning how many were drawn. The original content is wrapped in q/Q so
+// whatever graphics state it leaves behind can't shift the appearances.
+fn flatten_page_annotations(document: &mut Document, page_id: ObjectId) -> Result<usize, String> {
+    let page = document
+        .get_dictionary(page_id)
+        .map_err(|e| format!("Failed to read page: {}", e))?
+        .clone();
+    let Some(annots) = page
+        .get(b"Annots")
+        .and_then(|annots| document.dereference(annots))
+        .and_then(|(_, annots)| annots.as_array())
+        .ok()
+        .cloned()
+    else {
+        return Ok(0);
+    };
+
+    let mut draws = Vec::new();
+    for annot in &annots {
+        let Ok((_, Object::Dictionary(annot))) = document.dereference(annot) else {
+            continue;
+        };
+        let flags = annot.get(b"F").and_then(Object::as_i64).unwrap_or(0);
+        let popup = annot.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Popup");
+        if popup || flags & (ANNOT_FLAG_HIDDEN | ANNOT_FLAG_NO_VIEW) != 0 {
+            continue;
+        }
+        let Some(appearance_id) = normal_appearance(document, annot) else {
+            continue;
+        };
+        let Ok(Object::Stream(appearance)) = document.get_object(appearance_id) else {
+            continue;
+        };
+
+        let bbox = appearance
+            .dict
+            .get(b"BBox")
+            .ok()
+            .and_then(|bbox| number_array(document, bbox));
+        let matrix = appearance
+            .dict
+            .get(b"Matrix")
+            .ok()
+            .and_then(|matrix| number_array(document, matrix))
+            .unwrap_or_else(|| vec![1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        let rect = annot
+            .get(b"Rect")
+            .ok()
+            .and_then(|rect| number_array(document, rect));
+        if let Some(placement) = bbox
+            .zip(rect)
+            .and_then(|(bbox, rect)| appearance_matrix(&bbox, &matrix, &rect))
+        {
+            draws.push((appearance_id, placement));
+        }
+    }
+
+    if draws.is_empty() {
+        return Ok(0);
+    }
+
+    let mut resources = page_resources(document, page_id);
+    let mut xobjects = resources
+        .get(b"XObject")
+        .and_then(Object::as_dict)
+        .cloned()
+        .unwrap_or_default();
+    let mut operations = String::new();
+    let mut n = 0;
+    for (appearance_id, placement) in &draws {
+        // Appearance streams are form XObjects, but writers don't always say so
+        if let Ok(Object::Stream(appearance)) = document.get_object_mut(*appearance_id) {
+            appearance.dict.set("Type", "XObject");
+            appearance.dict.set("Subtype", "Form");
+        }
+
+        let name = loop {
+            n += 1;
+            let name = format!("FlatAnnot{}", n);
+            if !xobjects.has(name.as_bytes()) {
+                break name;
+            }
+        };
+        xobjects.set(name.as_bytes().to_vec(), Object::Reference(*appearance_id));
+        let [a, b, c, d, e, f] = placement;
+        operations.push_str(&format!(
+            "q {} {} {} {} {} {} cm /{} Do Q\n",
+            a, b, c, d, e, f, name
+        ));
+    }
+    resources.set("XObject", xobjects);
+
+    let mut contents = vec![Object::Reference(
+        document.add_object(lopdf::Stream::new(Dictionary::new(), b"q\n".to_vec())),
+    )];
+    contents.extend(page_contents(document, &page));
+    contents.push(Object::Reference(document.add_object(lopdf::Stream::new(
+        Dictionary::new(),
+        format!("Q\n{}", operations).into_bytes(),
+    ))));
+
+    let page = document
+        .get_dictionary_mut(page_id)
+        .map_err(|e| format!("Failed to read page: {}", e))?;
+    page.set("Resources", resources);
+    page.set("Contents", contents);
+    Ok(draws.len())
+}
+
+// Writes a copy of the PDF without annotations to output_path. mode "remove"
+// drops them; "flatten" first draws the visible ones into the page content
+// the way a viewer shows them, so highlights and ink stay but can no longer
+// be edited. Form fields go as well, since their widgets are annotations.
+// The Info dictionary and XMP metadata are kept as they are.
+#[tauri::command]
+pub async fn remove_annotations(
+    file_path: String,
+    output_path: String,
+    mode: String,
+) -> Result<(), String> {
+    if !matches!(mode.as_str(), "remove" | "flatten") {
+        return Err(crate::importer::invalid_argument(
+            "invalid_mode",
+            format!("Unsupported annotation mode: {}", mode),
+        ));
+    }
+
+    let path = &crate::os_path(Path::new(&file_path));
+    if !path.is_file() {
+        return Err(format!("Path is not a file: {}", file_path));
+    }
+    let output = &crate::os_path(Path::new(&output_path));
+    if output.is_dir() {
+        return Err(format!("Output path is a directory: {}", output_path));
+    }
+    if output.exists() {
+        return Err(format!("File already exists: {}", output_path));
+    }
+
+    // The whole document is loaded and written out again
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = &crate::os_path(Path::new(&file_path));
+        let mut document =
+            Document::load(path).map_err(|e| format!("Failed to load PDF: {}", e))?;
+        if document.is_encrypted() {
+            return Err("Encrypted PDFs can't be rewritten".to_string());
+        }
+
+        for page_id in document.get_pages().into_values() {
+            if mode == "flatten" {
+                flatten_page_annotations(&mut document, page_id)?;
+            }
+            if let Ok(page) = document.get_dictionary_mut(page_id) {
+                page.remove(b"Annots");
+            }
+        }
+        if let Ok(catalog) = document.catalog_mut() {
+            catalog.remove(b"AcroForm");
+        }
+        document.prune_objects();
+
+        let mut bytes = Vec::new();
+        document
+            .save_to(&mut bytes)
+            .map_err(|e| format!("Failed to serialize PDF: {}", e))?;
+        crate::atomic_write(&crate::os_path(Path::new(&output_path)), &bytes)
+            .map_err(|e| format!("Failed to write {}: {}", output_path, e))
+    })
+    .await
+    .map_err(|e| format!("Failed to remove annotations: {}", e))?
+}
+
+// Object numbers past this in a rebuilt table mean garbage, not a document
+const MAX_REBUILT_OBJECTS: u32 = 10_000_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfRepair {
+    pub output_path: String,
+    // false when the cross-reference table was already sound and the file
+    // was only rewritten
+    pub changed: bool,
+    pub page_count: u32,
+}
+
+// Whether the last startxref points at an xref table or an xref stream object
+fn xref_is_intact(bytes: &[u8]) -> bool {
+    let tail = &bytes[bytes.len().saturating_sub(TRAILER_SCAN_BYTES as usize)..];
+    let Ok(pattern) = Regex::new(r"startxref\s+([0-9]+)") else {
+        return false;
+    };
+    let Some(offset) = pattern.captures_iter(tail).last().and_then(|captures| {
+        std::str::from_utf8(captures.get(1)?.as_bytes())
+            .ok()?
+            .parse()
+            .ok()
+    }) else {
+        return false;
+    };
+    let Some(target) = bytes.get(offset..) else {
+        return false;
+    };
+    target.starts_with(b"xref")
+        || Regex::new(r"^[0-9]+\s+[0-9]+\s+obj\b")
+            .map(|object| object.is_match(target))
+            .unwrap_or(false)
+}
+
+fn object_reference(captures: regex::bytes::Captures) -> Option<(u32, u16)> {
+    let number = std::str::from_utf8(captures.get(1)?.as_bytes()).ok()?;
+    let generation = std::str::from_utf8(captures.get(2)?.as_bytes()).ok()?;
+    Some((number.parse().ok()?, generation.parse().ok()?))
+}
+
+// A copy of the file with a fresh xref table and trailer, built from where
+// "N G obj" actually occurs. Objects packed into object streams can't be
+// found this way, which is why lopdf's own loader is tried first.
+fn rebuild_xref(bytes: &[u8]) -> Option<Vec<u8>> {
+    let object_pattern = Regex::new(r"(?:^|\s)([0-9]{1,10})\s+([0-9]{1,5})\s+obj\b").ok()?;
+    // Later definitions win, as with incremental updates
+    let mut offsets = BTreeMap::new();
+    for captures in object_pattern.captures_iter(bytes) {
+        let start = captures.get(1)?.start();
+        if let Some((number, generation)) = object_reference(captures) {
+            offsets.insert(number, (start, generation));
+        }
+    }
+    let size = offsets.keys().next_back()? + 1;
+    if size > MAX_REBUILT_OBJECTS {
+        return None;
+    }
+
+    let last_reference = |key: &str| {
+        Regex::new(&format!(r"/{}\s+([0-9]+)\s+([0-9]+)\s+R", key))
+            .ok()?
+            .captures_iter(bytes)
+            .filter_map(object_reference)
+            .last()
+    };
+    // Without a trailer, the catalog is the object holding /Type /Catalog
+    let root = last_reference("Root").or_else(|| {
+        let catalog = Regex::new(r"/Type\s*/Catalog\b").ok()?.find(bytes)?.start();
+        offsets
+            .iter()
+            .filter(|(_, (offset, _))| *offset < catalog)
+            .max_by_key(|(_, (offset, _))| *offset)
+            .map(|(number, (_, generation))| (*number, *generation))
+    })?;
+
+    let body_end = bytes
+        .windows(6)
+        .rposition(|window| window == b"endobj")
+        .map(|position| position + 6)?;
+    let mut rebuilt = bytes[..body_end].to_vec();
+    rebuilt.push(b'\n');
+    let xref_offset = rebuilt.len();
+
+    let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", size);
+    for number in 1..size {
+        match offsets.get(&number) {
+            Some((offset, generation)) => {
+                table.push_str(&format!("{:010} {:05} n \n", offset, generation))
+            }
+            None => table.push_str("0000000000 00000 f \n"),
+        }
+    }
+    let info = last_reference("Info")
+        .filter(|(number, _)| offsets.contains_key(number))
+        .map(|(number, generation)| format!(" /Info {} {} R", number, generation))
+        .unwrap_or_default();
+    table.push_str(&format!(
+        "trailer\n<< /Size {} /Root {} {} R{} >>\nstartxref\n{}\n%%EOF\n",
+        size, root.0, root.1, info, xref_offset
+    ));
+    rebuilt.extend_from_slice(table.as_bytes());
+    Some(rebuilt)
+}
+
+// lopdf can panic on some malformed cross-reference tables instead of
+// returning an error
+fn load_tolerant(bytes: &[u8]) -> Option<Document> {
+    std::panic::catch_unwind(|| Document::load_mem(bytes).ok())
+        .ok()
+        .flatten()
+        .filter(|document| !document.get_pages().is_empty())
+}
+
+// Rewrites file_path to output_path with a clean cross-reference table. A
+// table that doesn't match the file is rebuilt from the objects found in it.
+// output_path must not exist yet; the original is only replaced when
+// output_path names it and force is set. Fails with not_a_pdf, encrypted or
+// parse_error (nothing left to recover).
+#[tauri::command]
+pub async fn repair_pdf(
+    file_path: String,
+    output_path: String,
+    force: Option<bool>,
+) -> Result<PdfRepair, String> {
+    let path = crate::os_path(Path::new(&file_path));
+    if !path.is_file() {
+        return Err(format!("Path is not a file: {}", file_path));
+    }
+    let output = crate::os_path(Path::new(&output_path));
+    if output.is_dir() {
+        return Err(format!("Output path is a directory: {}", output_path));
+    }
+    let in_place = output == path || crate::is_same_file(&output, &path);
+    if output.exists() && !(in_place && force.unwrap_or(false)) {
+        return Err(crate::importer::invalid_argument(
+            "file_exists",
+            if in_place {
+                format!("Refusing to replace {} without force", file_path)
+            } else {
+                format!("File already exists: {}", output_path)
+            },
+        ));
+    }
+
+    // The whole document is read, and rebuilt if it has to be
+    tauri::async_runtime::spawn_blocking(move || {
+        let bytes = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+        if !bytes.starts_with(b"%PDF-") {
+            return Err(crate::importer::invalid_argument(
+                "not_a_pdf",
+                format!("{} has no PDF header", file_path),
+            ));
+        }
+
+        let sound = xref_is_intact(&bytes) && tail_has_eof_marker(&bytes);
+        let (mut document, changed) = match load_tolerant(&bytes) {
+            Some(document) => (document, !sound),
+            None => {
+                let document = rebuild_xref(&bytes)
+                    .and_then(|rebuilt| load_tolerant(&rebuilt))
+                    .ok_or_else(|| {
+                        crate::importer::invalid_argument(
+                            "parse_error",
+                            format!("No readable document structure in {}", file_path),
+                        )
+                    })?;
+                (document, true)
+            }
+        };
+        if document.is_encrypted() {
+            return Err(crate::importer::invalid_argument(
+                "encrypted",
+                "Encrypted PDFs can't be rewritten".to_string(),
+            ));
+        }
+        let page_count = document.get_pages().len() as u32;
+
+        // Nothing to fix, and rewriting the original would change it for
+        // nothing
+        if !in_place || changed {
+            let mut repaired = Vec::new();
+            document
+                .save_to(&mut repaired)
+                .map_err(|e| format!("Failed to serialize PDF: {}", e))?;
+            crate::atomic_write(&output, &repaired)
+                .map_err(|e| format!("Failed to write {}: {}", output_path, e))?;
+        }
+
+        Ok(PdfRepair {
+            output_path,
+            changed,
+            page_count,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to repair PDF: {}", e))?
+}
+
+// The text of each page, in page order. lopdf decodes the common font
+// encodings only; a page it can't read comes back empty instead of failing
+// the whole document.
+pub fn extract_text(path: &Path) -> Result<Vec<String>, String> {
+    let document = Document::load(path).map_err(|e| format!("Failed to load PDF: {}", e))?;
+    if document.is_encrypted() {
+        return Err(format!("PDF is encrypted: {}", path.display()));
+    }
+
+    Ok(document
+        .get_pages()
+        .into_keys()
+        .map(|number| {
+            document.extract_text(&[number]).unwrap_or_else(|e| {
+                warn!("Failed to extract text from page {}: {}", number, e);
+                String::new()
+            })
+        })
+        .collect())
+}
+
+// Below this many words per page on average the PDF is taken to be scanned
+// images, whose few words are page numbers or an OCR-less cover
+const MIN_TEXT_WORDS_PER_PAGE: usize = 10;
+// What a page of a paper holds, for PDFs without a usable text layer
+const ASSUMED_WORDS_PER_PAGE: usize = 250;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadingEstimate {
+    pub word_count: usize,
+    pub pages: u32,
+    // Rounded up to whole minutes
+    pub minutes: u32,
+    // "words" when counted from the text, "pages" when the PDF had too little
+    // text and ASSUMED_WORDS_PER_PAGE stands in for it
+    pub estimated_from: String,
+}
+
+// Minutes to read file_path at `wpm` words per minute
+#[tauri::command]
+pub async fn estimate_reading_time(file_path: String, wpm: u32) -> Result<ReadingEstimate, String> {
+    if wpm == 0 {
+        return Err(crate::importer::invalid_argument(
+            "invalid_input",
+            "Words per minute must be at least 1".to_string(),
+        ));
+    }
+
+    let path = &crate::os_path(Path::new(&file_path));
+    if !path.is_file() {
+        return Err(format!("Path is not a file: {}", file_path));
+    }
+
+    // Extracting the text of every page is slow for long documents
+    let path = path.to_path_buf();
+    let pages = tauri::async_runtime::spawn_blocking(move || extract_text(&path))
+        .await
+        .map_err(|e| format!("Failed to extract text: {}", e))??;
+    // Tokens without a letter or digit are stray punctuation and bullets
+    let word_count = pages
+        .iter()
+        .flat_map(|text| text.split_whitespace())
+        .filter(|token| token.chars().any(char::is_alphanumeric))
+        .count();
+
+    let (words, estimated_from) = if word_count < pages.len() * MIN_TEXT_WORDS_PER_PAGE {
+        (pages.len() * ASSUMED_WORDS_PER_PAGE, "pages")
+    } else {
+        (word_count, "words")
+    };
+
+    Ok(ReadingEstimate {
+        word_count,
+        pages: pages.len() as u32,
+        minutes: words.div_ceil(wpm as usize) as u32,
+        estimated_from: estimated_from.to_string(),
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfInfo {
+    pub page_count: u32,
+    pub pdf_version: String,
+    pub encrypted: bool,
+    // From the Info dictionary; left empty for encrypted files, whose strings
+    // can't be read without the password
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    pub keywords: Option<String>,
+    pub creator: Option<String>,
+    pub producer: Option<String>,
+    // ISO 8601, with the offset when the file gives one
+    pub creation_date: Option<String>,
+    pub mod_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfInfoResult {
+    pub path: String,
+    pub info: Option<PdfInfo>,
+    pub error: Option<String>,
+}
+
+// "D:YYYYMMDDHHmmSSOHH'mm'" where everything after the year is optional and
+// O is Z, + or -. Some writers leave out the "D:" or the apostrophes.
+fn parse_pdf_date(text: &str) -> Option<String> {
+    let text = text.trim();
+    let text = text.strip_prefix("D:").unwrap_or(text);
+    let digits = text.bytes().take_while(u8::is_ascii_digit).count();
+    if digits < 4 || digits % 2 != 0 || digits > 14 {
+        return None;
+    }
+
+    let field = |start: usize, default: u32| -> Option<u32> {
+        if start < digits {
+            text[start..start + 2].parse().ok()
+        } else {
+            Some(default)
+        }
+    };
+    let year = text[..4].parse::<i32>().ok()?;
+    let (month, day) = (field(4, 1)?, field(6, 1)?);
+    let (hour, minute, second) = (field(8, 0)?, field(10, 0)?, field(12, 0)?);
+    let time = chrono::NaiveDate::from_ymd_opt(year, month, day)?
+        .and_hms_opt(hour, minute, second)?
+        .format("%Y-%m-%dT%H:%M:%S")
+        .to_string();
+
+    let zone = &text[digits..];
+    let offset = match zone.chars().next() {
+        None => String::new(),
+        Some('Z') => "Z".to_string(),
+        Some(sign @ ('+' | '-')) => {
+            let numbers = zone[1..]
+                .chars()
+                .filter(char::is_ascii_digit)
+                .collect::<String>();
+            let hours = numbers.get(..2)?.parse::<u32>().ok()?;
+            let minutes = numbers.get(2..4).map_or(Some(0), |m| m.parse().ok())?;
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            format!("{}{:02}:{:02}", sign, hours, minutes)
+        }
+        Some(_) => return None,
+    };
+    Some(format!("{}{}", time, offset))
+}
+
+fn info_string(document: &Document, info: &Dictionary, key: &[u8]) -> Option<String> {
+    let (_, value) = document.dereference(info.get(key).ok()?).ok()?;
+    let text = decode_text_string(value.as_str().ok()?);
+    let text = text.trim();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text.to_string())
+    }
+}
+
+// Whether the trailer names an encryption dictionary, for files lopdf
+// couldn't load
+fn mentions_encrypt(file: &mut File, len: u64) -> bool {
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::Start(len.saturating_sub(TRAILER_SCAN_BYTES)))
+        .and_then(|_| file.read_to_end(&mut tail))
+        .is_ok()
+        && tail.windows(8).any(|window| window == b"/Encrypt")
+}
+
+fn read_pdf_info(path: &Path) -> Result<PdfInfo, String> {
+    if !path.is_file() {
+        return Err(format!("Path is not a file: {}", path.display()));
+    }
+    let mut file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let len = file
+        .metadata()
+        .map_err(|e| format!("Failed to read file metadata: {}", e))?
+        .len();
+    if !has_pdf_header(&mut file).map_err(|e| format!("Failed to read file: {}", e))? {
+        return Err(crate::importer::invalid_argument(
+            "not_a_pdf",
+            format!("{} has no PDF header", path.display()),
+        ));
+    }
+
+    // lopdf can panic on some malformed cross-reference tables instead of
+    // returning an error
+    let loaded = std::panic::catch_unwind(|| Document::load(path))
+        .map_err(|_| "the parser gave up on malformed data".to_string())
+        .and_then(|loaded| loaded.map_err(|e| e.to_string()));
+    let document = match loaded {
+        Ok(document) => document,
+        Err(_) if mentions_encrypt(&mut file, len) => {
+            return Err(crate::importer::invalid_argument(
+                "encrypted",
+                format!("{} is encrypted and can't be opened", path.display()),
+            ))
+        }
+        Err(error) => {
+            return Err(crate::importer::invalid_argument(
+                "parse_error",
+                format!("Failed to parse {}: {}", path.display(), error),
+            ))
+        }
+    };
+
+    let encrypted = document.is_encrypted();
+    let info = document
+        .trailer
+        .get(b"Info")
+        .ok()
+        .and_then(|info| document.dereference(info).ok())
+        .and_then(|(_, info)| info.as_dict().ok())
+        .filter(|_| !encrypted);
+    let field = |key: &[u8]| info.and_then(|info| info_string(&document, info, key));
+
+    Ok(PdfInfo {
+        page_count: document.get_pages().len() as u32,
+        pdf_version: document.version.clone(),
+        encrypted,
+        title: field(b"Title"),
+        author: field(b"Author"),
+        subject: field(b"Subject"),
+        keywords: field(b"Keywords"),
+        creator: field(b"Creator"),
+        producer: field(b"Producer"),
+        creation_date: field(b"CreationDate").and_then(|date| parse_pdf_date(&date)),
+        mod_date: field(b"ModDate").and_then(|date| parse_pdf_date(&date)),
+    })
+}
+
+// Page count, version and Info dictionary of file_path. Files that aren't a
+// PDF, can't be parsed or can't be opened without a password fail with
+// not_a_pdf, parse_error or encrypted.
+#[tauri::command]
+pub async fn get_pdf_info(file_path: String) -> Result<PdfInfo, String> {
+    // The whole document is parsed
+    tauri::async_runtime::spawn_blocking(move || {
+        read_pdf_info(&crate::os_path(Path::new(&file_path)))
+    })
+    .await
+    .map_err(|e| format!("Failed to read PDF info: {}", e))?
+}
+
+// get_pdf_info for many files at once, spread over the local concurrency
+// budget, e.g. to backfill page counts for a library scanned before. Results
+// come back in input order with per-file errors, and each finished file emits
+// "pdf-info-progress".
+#[tauri::command]
+pub async fn get_pdf_info_batch(
+    app: AppHandle,
+    paths: Vec<String>,
+) -> Result<Vec<PdfInfoResult>, String> {
+    let permits = app
+        .state::<Concurrency>()
+        .acquire_up_to(Budget::Local, paths.len())
+        .await;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let next = AtomicUsize::new(0);
+        let done = AtomicUsize::new(0);
+        let results = Mutex::new(Vec::with_capacity(paths.len()));
+
+        std::thread::scope(|scope| {
+            for _ in 0..permits.num_permits().min(paths.len()) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(path) = paths.get(index) else {
+                        break;
+                    };
+                    let result = match read_pdf_info(&crate::os_path(Path::new(path))) {
+                        Ok(info) => PdfInfoResult {
+                            path: path.clone(),
+                            info: Some(info),
+                            error: None,
+                        },
+                        Err(error) => PdfInfoResult {
+                            path: path.clone(),
+                            info: None,
+                            error: Some(error),
+                        },
+                    };
+                    results.lock().unwrap().push((index, result));
+
+                    let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+                    let _ = app.emit(
+                        "pdf-info-progress",
+                        serde_json::json!({
+                            "done": done,
+                            "total": paths.len(),
+                            "path": path,
+                        }),
+                    );
+                });
+            }
+        });
+
+        let mut results = results.into_inner().unwrap();
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    })
+    .await
+    .map_err(|e| format!("Failed to read PDF info: {}", e))
+}
+
+// Renders stay well within what a webview can decode: 600 dpi is already an
+// ~5000px wide A4 page
+const MAX_RENDER_DPI: u32 = 600;
+
+// Prefers a pdfium shipped next to the executable over a system-wide one
+pub fn bind_pdfium() -> Result<Box<dyn PdfiumLibraryBindings>, String> {
+    let bundled = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .map(|dir| Pdfium::pdfium_platform_library_name_at_path(&dir));
+
+    if let Some(library) = bundled {
+        if let Ok(bindings) = Pdfium::bind_to_library(library) {
+            return Ok(bindings);
+        }
+    }
+
+    Pdfium::bind_to_system_library()
+        .map_err(|e| format!("Failed to load the pdfium library: {}", e))
+}
+
+// PNG bytes of one page (1-based) at `dpi`. Nothing is cached, so the frontend
+// can ask for full-size renders when zooming as well as small previews.
+#[tauri::command]
+pub async fn render_page(file_path: String, page: u32, dpi: u32) -> Result<Vec<u8>, String> {
+    if !crate::os_path(Path::new(&file_path)).is_file() {
+        return Err(format!("Path is not a file: {}", file_path));
+    }
+
+    if dpi == 0 || dpi > MAX_RENDER_DPI {
+        return Err(format!(
+            "Unsupported resolution: {} dpi (expected 1-{})",
+            dpi, MAX_RENDER_DPI
+        ));
+    }
+
+    // Rasterizing a page at high dpi takes long enough to stall the IPC thread
+    tauri::async_runtime::spawn_blocking(move || {
+        let pdfium = Pdfium::new(bind_pdfium()?);
+        let document = pdfium
+            .load_pdf_from_file(&crate::os_path(Path::new(&file_path)), None)
+            .map_err(|e| match e {
+                PdfiumError::PdfiumLibraryInternalError(PdfiumInternalError::PasswordError) => {
+                    format!("PDF is encrypted: {}", file_path)
+                }
+                e => format!("Failed to load PDF: {}", e),
+            })?;
+
+        // Page sizes are in points, 72 to the inch
+        let scale = dpi as f32 / 72.0;
+        let (png_bytes, _, _) = render_page_png(&document, page, |width, height| {
+            (
+                (width * scale).round().max(1.0) as i32,
+                (height * scale).round().max(1.0) as i32,
+            )
+        })?;
+        Ok(png_bytes)
+    })
+    .await
+    .map_err(|e| format!("Failed to render page: {}", e))?
+}
+
+// Page `page` (1-based) of an open document as PNG bytes, with the bitmap's
+// width and height. `target_size` turns the page size in points into pixels.
+pub fn render_page_png(
+    document: &PdfDocument,
+    page: u32,
+    target_size: impl FnOnce(f32, f32) -> (i32, i32),
+) -> Result<(Vec<u8>, u32, u32), String> {
+    let pages = document.pages();
+    let page_count = pages.len() as u32;
+    if page == 0 || page > page_count {
+        return Err(format!(
+            "Page {} is outside the document ({} pages)",
+            page, page_count
+        ));
+    }
+
+    let pdf_page = pages
+        .get((page - 1) as u16)
+        .map_err(|e| format!("Failed to read page {}: {}", page, e))?;
+
+    let (width, height) = target_size(pdf_page.width().value, pdf_page.height().value);
+    let config = PdfRenderConfig::new()
+        .set_target_width(width)
+        .set_target_height(height);
+    let bitmap = pdf_page
+        .render_with_config(&config)
+        .map_err(|e| format!("Failed to render page {}: {}", page, e))?;
+
+    let (width, height) = (bitmap.width() as u32, bitmap.height() as u32);
+    let mut png_bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png_bytes, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| format!("Failed to encode page {}: {}", page, e))?;
+    writer
+        .write_image_data(&bitmap.as_rgba_bytes())
+        .map_err(|e| format!("Failed to encode page {}: {}", page, e))?;
+    writer
+        .finish()
+        .map_err(|e| format!("Failed to encode page {}: {}", page, e))?;
+
+    Ok((png_bytes, width, height))
+}
@@ -0,0 +1,85 @@
+// Citation counts and venue from Semantic Scholar, looked up by arXiv id. An
+// optional extra for imports, so any failure just means no enrichment.
+
+use log::debug;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use url::Url;
+
+use crate::importer::non_empty_text;
+use crate::SidecarFields;
+
+const SEMANTIC_SCHOLAR_PAPER_URL: &str = "https://api.semanticscholar.org/graph/v1/paper";
+const ENRICHMENT_FIELDS: &str = "citationCount,influentialCitationCount,venue";
+// Much shorter than the importer timeout; the import doesn't wait on this
+// for long
+const ENRICHMENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Enrichment {
+    pub citation_count: Option<u64>,
+    pub influential_citation_count: Option<u64>,
+    pub venue: Option<String>,
+}
+
+impl Enrichment {
+    // Stored under the same names in the sidecar
+    pub fn sidecar_fields(&self) -> SidecarFields {
+        let mut fields = SidecarFields::new();
+        fields.insert(
+            "citation_count".to_string(),
+            serde_json::json!(self.citation_count),
+        );
+        fields.insert(
+            "influential_citation_count".to_string(),
+            serde_json::json!(self.influential_citation_count),
+        );
+        fields.insert("venue".to_string(), serde_json::json!(self.venue));
+        fields
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PaperResponse {
+    #[serde(default)]
+    citation_count: Option<u64>,
+    #[serde(default)]
+    influential_citation_count: Option<u64>,
+    #[serde(default)]
+    venue: Option<String>,
+}
+
+// `arxiv_id` without version; Semantic Scholar doesn't track versions
+pub async fn fetch_enrichment(client: &Client, arxiv_id: &str) -> Result<Enrichment, String> {
+    let url = Url::parse_with_params(
+        &format!("{}/arXiv:{}", SEMANTIC_SCHOLAR_PAPER_URL, arxiv_id),
+        [("fields", ENRICHMENT_FIELDS)],
+    )
+    .map_err(|e| format!("Failed to build Semantic Scholar query: {}", e))?;
+
+    debug!("GET {}", url);
+    let response = client
+        .get(url)
+        .timeout(ENRICHMENT_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to query Semantic Scholar: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Semantic Scholar returned {}", response.status()));
+    }
+
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read Semantic Scholar response: {}", e))?;
+    let paper = serde_json::from_str::<PaperResponse>(&body)
+        .map_err(|e| format!("Failed to parse Semantic Scholar response: {}", e))?;
+
+    Ok(Enrichment {
+        citation_count: paper.citation_count,
+        influential_citation_count: paper.influential_citation_count,
+        venue: non_empty_text(paper.venue),
+    })
+}
@@ -0,0 +1,367 @@
+// Reading and writing the per-paper metadata sidecar. It can be stored as
+// JSON, YAML, TOML or Markdown with YAML frontmatter; everything outside this
+// module works on SidecarFields and never looks at the file format.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::importer::invalid_argument;
+use crate::{atomic_write, SidecarFields};
+
+const FRONTMATTER_FENCE: &str = "---";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SidecarFormat {
+    Json,
+    Yaml,
+    Toml,
+    MarkdownFrontmatter,
+}
+
+const FORMATS: &[SidecarFormat] = &[
+    SidecarFormat::Json,
+    SidecarFormat::Yaml,
+    SidecarFormat::Toml,
+    SidecarFormat::MarkdownFrontmatter,
+];
+
+impl SidecarFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "yaml" => Ok(Self::Yaml),
+            "toml" => Ok(Self::Toml),
+            "markdown_frontmatter" => Ok(Self::MarkdownFrontmatter),
+            _ => Err(invalid_argument(
+                "invalid_sidecar_format",
+                format!("Unsupported sidecar format: {}", value),
+            )),
+        }
+    }
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Self::Json => ".metadata.json",
+            Self::Yaml => ".metadata.yaml",
+            Self::Toml => ".metadata.toml",
+            Self::MarkdownFrontmatter => ".metadata.md",
+        }
+    }
+
+    // Field stored as the document body rather than as a key, if any
+    pub fn body_key(self) -> Option<&'static str> {
+        match self {
+            Self::MarkdownFrontmatter => Some("summary"),
+            Self::Json | Self::Yaml | Self::Toml => None,
+        }
+    }
+
+    // Staged copies (see importer::part_path) count as the file they become
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy();
+        let name = name.strip_suffix(".part").unwrap_or(&name);
+        FORMATS
+            .iter()
+            .copied()
+            .find(|format| name.ends_with(format.suffix()))
+    }
+}
+
+pub fn metadata_suffixes() -> impl Iterator<Item = &'static str> {
+    FORMATS.iter().map(|format| format.suffix())
+}
+
+pub fn metadata_path(dir: &Path, stem: &str, format: SidecarFormat) -> PathBuf {
+    dir.join(format!("{}{}", stem, format.suffix()))
+}
+
+// The file stem a sidecar belongs to, or None if `path` isn't a sidecar
+pub fn sidecar_stem(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+    metadata_suffixes().find_map(|suffix| name.strip_suffix(suffix).map(|stem| stem.to_string()))
+}
+
+// The sidecar of a PDF in whichever format it was written, or a not yet
+// existing one in `format`
+pub fn sidecar_for_pdf(pdf_path: &Path, format: SidecarFormat) -> PathBuf {
+    let (dir, stem) = pdf_dir_and_stem(pdf_path);
+    existing(dir, &stem).unwrap_or_else(|| metadata_path(dir, &stem, format))
+}
+
+fn pdf_dir_and_stem(pdf_path: &Path) -> (&Path, String) {
+    let stem = pdf_path
+        .file_stem()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    (pdf_path.parent().unwrap_or_else(|| Path::new("")), stem)
+}
+
+// Every sidecar of a PDF merged into one, the most recently written winning
+// where they disagree. There is normally just one; a second is left behind
+// when the format setting changed after the first was written.
+pub fn read_for_pdf(pdf_path: &Path) -> Result<SidecarFields, String> {
+    let (dir, stem) = pdf_dir_and_stem(pdf_path);
+    let mut found = FORMATS
+        .iter()
+        .map(|format| metadata_path(dir, &stem, *format))
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).ok()?.modified().ok()?;
+            Some((modified, path))
+        })
+        .collect::<Vec<_>>();
+    found.sort();
+
+    let mut fields = SidecarFields::new();
+    for (_, path) in found {
+        fields.extend(read(&path)?);
+    }
+    Ok(fields)
+}
+
+// The sidecar for `stem` in `dir` if one exists in any format. Existence
+// checks go through this so a sidecar written before the format setting
+// changed still counts.
+pub fn existing(dir: &Path, stem: &str) -> Option<PathBuf> {
+    FORMATS
+        .iter()
+        .map(|format| metadata_path(dir, stem, *format))
+        .find(|path| path.is_file())
+}
+
+// TOML has no null, so absent values are left out instead
+fn without_nulls(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(object) => serde_json::Value::Object(
+            object
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, without_nulls(value)))
+                .collect(),
+        ),
+        serde_json::Value::Array(items) => serde_json::Value::Array(
+            items
+                .into_iter()
+                .filter(|value| !value.is_null())
+                .map(without_nulls)
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+// Only a string can become the body; anything else stays in the frontmatter
+fn take_body(fields: &mut SidecarFields, key: &str) -> String {
+    match fields.remove(key) {
+        Some(serde_json::Value::String(body)) => body,
+        Some(other) => {
+            fields.insert(key.to_string(), other);
+            String::new()
+        }
+        None => String::new(),
+    }
+}
+
+fn serialize(format: SidecarFormat, mut fields: SidecarFields) -> Result<String, String> {
+    match format {
+        SidecarFormat::Json => serde_json::to_string_pretty(&fields).map_err(|e| e.to_string()),
+        SidecarFormat::Yaml => serde_yaml::to_string(&fields).map_err(|e| e.to_string()),
+        SidecarFormat::Toml => {
+            toml::to_string_pretty(&without_nulls(serde_json::Value::Object(fields)))
+                .map_err(|e| e.to_string())
+        }
+        SidecarFormat::MarkdownFrontmatter => {
+            let body = format
+                .body_key()
+                .map(|key| take_body(&mut fields, key))
+                .unwrap_or_default();
+            let frontmatter = serde_yaml::to_string(&fields).map_err(|e| e.to_string())?;
+            Ok(format!(
+                "{fence}\n{}{fence}\n\n{}\n",
+                frontmatter,
+                body.trim(),
+                fence = FRONTMATTER_FENCE
+            ))
+        }
+    }
+}
+
+fn into_fields(value: serde_json::Value) -> Result<SidecarFields, String> {
+    match value {
+        serde_json::Value::Object(object) => Ok(object),
+        _ => Err("not a key/value document".to_string()),
+    }
+}
+
+fn deserialize(format: SidecarFormat, text: &str) -> Result<SidecarFields, String> {
+    match format {
+        SidecarFormat::Json => into_fields(serde_json::from_str(text).map_err(|e| e.to_string())?),
+        SidecarFormat::Yaml => into_fields(serde_yaml::from_str(text).map_err(|e| e.to_string())?),
+        SidecarFormat::Toml => into_fields(toml::from_str(text).map_err(|e| e.to_string())?),
+        SidecarFormat::MarkdownFrontmatter => {
+            let rest = text
+                .trim_start_matches('\u{feff}')
+                .strip_prefix(FRONTMATTER_FENCE)
+                .ok_or_else(|| "missing frontmatter".to_string())?;
+            let (frontmatter, body) = rest
+                .split_once(&format!("\n{}", FRONTMATTER_FENCE))
+                .ok_or_else(|| "unterminated frontmatter".to_string())?;
+
+            let mut fields =
+                into_fields(serde_yaml::from_str(frontmatter).map_err(|e| e.to_string())?)?;
+            // Whatever follows the closing fence on its line is not body text
+            let body = body.split_once('\n').map(|(_, body)| body).unwrap_or("");
+            let body = body.trim();
+            if let Some(body_key) = format.body_key().filter(|_| !body.is_empty()) {
+                fields.insert(
+                    body_key.to_string(),
+                    serde_json::Value::String(body.to_string()),
+                );
+            }
+            Ok(fields)
+        }
+    }
+}
+
+// A missing sidecar reads as empty so callers can merge into it
+pub fn read(path: &Path) -> Result<SidecarFields, String> {
+    if !path.exists() {
+        return Ok(SidecarFields::new());
+    }
+
+    let format = SidecarFormat::from_path(path)
+        .ok_or_else(|| format!("Not a metadata sidecar: {}", path.display()))?;
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read metadata file: {}", e))?;
+    deserialize(format, &text)
+        .map_err(|e| format!("Failed to parse metadata file {}: {}", path.display(), e))
+}
+
+pub fn write(path: &Path, fields: SidecarFields) -> Result<(), String> {
+    let format = SidecarFormat::from_path(path)
+        .ok_or_else(|| format!("Not a metadata sidecar: {}", path.display()))?;
+    let text =
+        serialize(format, fields).map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+    atomic_write(path, text.as_bytes()).map_err(|e| format!("Failed to write metadata file: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fields(value: serde_json::Value) -> SidecarFields {
+        into_fields(value).unwrap()
+    }
+
+    #[test]
+    fn every_format_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let written = fields(json!({
+            "title": "Anomalies and \"quotes\": a survey",
+            "authors": ["A. Author", "B. Author"],
+            "version": 2,
+            "summary": "First paragraph.\n\n---\n\nSecond paragraph after a rule.",
+            "links": { "pdf": "https://arxiv.org/pdf/2401.00001v2" },
+        }));
+
+        for format in FORMATS {
+            let path = metadata_path(dir.path(), "paper", *format);
+            write(&path, written.clone()).unwrap();
+            assert_eq!(SidecarFormat::from_path(&path), Some(*format));
+            assert_eq!(read(&path).unwrap(), written, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn toml_drops_nulls() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = metadata_path(dir.path(), "paper", SidecarFormat::Toml);
+        write(
+            &path,
+            fields(json!({ "title": "T", "doi": null, "authors": ["A", null] })),
+        )
+        .unwrap();
+        assert_eq!(
+            read(&path).unwrap(),
+            fields(json!({ "title": "T", "authors": ["A"] }))
+        );
+    }
+
+    #[test]
+    fn markdown_keeps_the_body_key_in_the_body() {
+        let format = SidecarFormat::MarkdownFrontmatter;
+        let text = serialize(
+            format,
+            fields(json!({ "title": "T", "summary": "Body text" })),
+        )
+        .unwrap();
+        assert_eq!(text, "---\ntitle: T\n---\n\nBody text\n");
+
+        // A summary that isn't a string can't be the body
+        let structured = fields(json!({ "title": "T", "summary": ["a", "b"] }));
+        let text = serialize(format, structured.clone()).unwrap();
+        assert_eq!(deserialize(format, &text).unwrap(), structured);
+
+        assert_eq!(
+            deserialize(format, "---\ntitle: T\n---\n").unwrap(),
+            fields(json!({ "title": "T" }))
+        );
+        for format in [
+            SidecarFormat::Json,
+            SidecarFormat::Yaml,
+            SidecarFormat::Toml,
+        ] {
+            assert_eq!(format.body_key(), None);
+        }
+    }
+
+    #[test]
+    fn read_for_pdf_prefers_the_newest_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        let pdf_path = dir.path().join("paper.pdf");
+        assert_eq!(read_for_pdf(&pdf_path).unwrap(), SidecarFields::new());
+
+        let written = |format, fields, age_secs| {
+            let path = metadata_path(dir.path(), "paper", format);
+            write(&path, fields).unwrap();
+            let modified = std::time::SystemTime::now() - std::time::Duration::from_secs(age_secs);
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        };
+        written(
+            SidecarFormat::Json,
+            fields(json!({ "title": "Old title", "doi": "10.1000/182" })),
+            3600,
+        );
+        written(
+            SidecarFormat::Toml,
+            fields(json!({ "title": "New title" })),
+            0,
+        );
+
+        assert_eq!(
+            read_for_pdf(&pdf_path).unwrap(),
+            fields(json!({ "title": "New title", "doi": "10.1000/182" }))
+        );
+    }
+
+    #[test]
+    fn existing_finds_a_sidecar_in_any_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let pdf_path = dir.path().join("paper.pdf");
+        assert_eq!(existing(dir.path(), "paper"), None);
+        assert_eq!(
+            sidecar_for_pdf(&pdf_path, SidecarFormat::Json),
+            metadata_path(dir.path(), "paper", SidecarFormat::Json)
+        );
+
+        let yaml = metadata_path(dir.path(), "paper", SidecarFormat::Yaml);
+        write(&yaml, fields(json!({ "title": "T" }))).unwrap();
+        assert_eq!(existing(dir.path(), "paper"), Some(yaml.clone()));
+        assert_eq!(sidecar_for_pdf(&pdf_path, SidecarFormat::Json), yaml);
+    }
+}
@@ -0,0 +1,278 @@
+// Page thumbnails for the library grid, rendered once and then served from
+// PNGs under the app data dir. The webview gets them as data URLs, since it
+// has no access to files there. A cached file's mtime is rewritten whenever it
+// is served, so the oldest mtime is the least recently used one.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use log::warn;
+use pdfium_render::prelude::{Pdfium, PdfiumError, PdfiumInternalError};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::checksum::sha256_hex;
+use crate::concurrency::{Budget, Concurrency};
+use crate::importer::invalid_argument;
+use crate::pdf;
+
+const THUMBNAIL_DIR: &str = "thumbnails";
+const MAX_THUMBNAIL_DIMENSION: u32 = 2048;
+// Least recently used thumbnails are dropped once the cache grows past this
+const MAX_THUMBNAIL_CACHE_BYTES: u64 = 256 * 1024 * 1024;
+// Enough of the path's hash to keep file names apart
+const PATH_HASH_CHARS: usize = 16;
+
+// Serializes eviction and invalidation so two renders don't both trim the
+// cache, or remove each other's fresh files
+static THUMBNAIL_CACHE_LOCK: Mutex<()> = Mutex::new(());
+// The cache dir, for commands that move or delete PDFs without an AppHandle;
+// set once from setup
+static CACHE_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Thumbnail {
+    pub path: String,
+    // "data:image/png;base64,...", for an <img> src
+    pub data_url: String,
+    pub width: u32,
+    pub height: u32,
+    // Served from the cache rather than rendered by this call
+    pub cached: bool,
+}
+
+fn thumbnail_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(THUMBNAIL_DIR))
+        .map_err(|e| format!("Failed to resolve data directory: {}", e))
+}
+
+pub fn init(app: &AppHandle) {
+    match thumbnail_dir(app) {
+        Ok(dir) => *CACHE_DIR.lock().unwrap() = Some(dir),
+        Err(error) => warn!("{}", error),
+    }
+}
+
+fn data_url(png_bytes: &[u8]) -> String {
+    format!("data:image/png;base64,{}", BASE64.encode(png_bytes))
+}
+
+// Every thumbnail of one PDF starts with this, whatever its page and size.
+// A PDF that is already gone is keyed by its resolved folder instead.
+fn path_prefix(file_path: &Path) -> String {
+    let resolved = crate::normalized_path(file_path).unwrap_or_else(|_| {
+        match (
+            file_path
+                .parent()
+                .and_then(|parent| crate::normalized_path(parent).ok()),
+            file_path.file_name(),
+        ) {
+            (Some(parent), Some(name)) => parent.join(name),
+            _ => file_path.to_path_buf(),
+        }
+    });
+    let hash = sha256_hex(resolved.to_string_lossy().as_bytes());
+    format!("{}_", &hash[..PATH_HASH_CHARS])
+}
+
+fn png_size(path: &Path) -> Option<(u32, u32)> {
+    let reader = png::Decoder::new(File::open(path).ok()?).read_info().ok()?;
+    let info = reader.info();
+    Some((info.width, info.height))
+}
+
+// PNG bytes with their width and height
+fn render_thumbnail(
+    file_path: &Path,
+    page: u32,
+    max_dimension: u32,
+) -> Result<(Vec<u8>, u32, u32), String> {
+    let pdfium = Pdfium::new(pdf::bind_pdfium()?);
+    let document = pdfium
+        .load_pdf_from_file(file_path, None)
+        .map_err(|e| match e {
+            PdfiumError::PdfiumLibraryInternalError(PdfiumInternalError::PasswordError) => {
+                invalid_argument(
+                    "encrypted",
+                    format!("PDF is encrypted: {}", file_path.display()),
+                )
+            }
+            e => invalid_argument(
+                "parse_error",
+                format!("Failed to load PDF {}: {}", file_path.display(), e),
+            ),
+        })?;
+
+    pdf::render_page_png(&document, page, |width, height| {
+        // The longer side becomes max_dimension
+        let scale = max_dimension as f32 / width.max(height).max(1.0);
+        (
+            (width * scale).round().max(1.0) as i32,
+            (height * scale).round().max(1.0) as i32,
+        )
+    })
+}
+
+// Drops least recently used thumbnails until the cache fits
+// MAX_THUMBNAIL_CACHE_BYTES again
+fn evict(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut files = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            let used = metadata.modified().unwrap_or(UNIX_EPOCH);
+            Some((used, metadata.len(), entry.path()))
+        })
+        .collect::<Vec<_>>();
+
+    let mut total = files.iter().map(|(_, len, _)| len).sum::<u64>();
+    if total <= MAX_THUMBNAIL_CACHE_BYTES {
+        return;
+    }
+    files.sort();
+    for (_, len, path) in files {
+        if total <= MAX_THUMBNAIL_CACHE_BYTES {
+            break;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => total -= len,
+            Err(error) => warn!("Failed to evict thumbnail {}: {:?}", path.display(), error),
+        }
+    }
+}
+
+// Renders page `page` (1-based) of file_path so its longer side is
+// max_dimension pixels and returns the cached PNG. The cache is keyed by
+// path, modification time, page and size, so an edited PDF gets fresh
+// thumbnails on its own. Encrypted and unreadable PDFs fail with "encrypted"
+// or "parse_error", for the grid to show a placeholder.
+#[tauri::command]
+pub async fn render_pdf_thumbnail(
+    app: AppHandle,
+    file_path: String,
+    page: u32,
+    max_dimension: u32,
+) -> Result<Thumbnail, String> {
+    if max_dimension == 0 || max_dimension > MAX_THUMBNAIL_DIMENSION {
+        return Err(invalid_argument(
+            "invalid_input",
+            format!(
+                "Unsupported thumbnail size: {} (expected 1-{})",
+                max_dimension, MAX_THUMBNAIL_DIMENSION
+            ),
+        ));
+    }
+    let dir = thumbnail_dir(&app)?;
+    let _permit = app.state::<Concurrency>().acquire(Budget::Local).await;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = &crate::os_path(Path::new(&file_path));
+        let modified = fs::metadata(path)
+            .ok()
+            .filter(|metadata| metadata.is_file())
+            .ok_or_else(|| format!("Path is not a file: {}", file_path))?
+            .modified()
+            .map_err(|e| format!("Failed to read modification time: {}", e))?;
+        let mtime = modified
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_millis())
+            .unwrap_or(0);
+        let cache_file = dir.join(format!(
+            "{}{}_{}_{}.png",
+            path_prefix(path),
+            mtime,
+            page,
+            max_dimension
+        ));
+
+        if let (Some((width, height)), Ok(png_bytes)) =
+            (png_size(&cache_file), fs::read(&cache_file))
+        {
+            if let Err(error) = File::options()
+                .write(true)
+                .open(&cache_file)
+                .and_then(|file| file.set_modified(SystemTime::now()))
+            {
+                warn!("Failed to mark thumbnail as used: {:?}", error);
+            }
+            return Ok(Thumbnail {
+                path: cache_file.to_string_lossy().to_string(),
+                data_url: data_url(&png_bytes),
+                width,
+                height,
+                cached: true,
+            });
+        }
+
+        let (png_bytes, width, height) = render_thumbnail(path, page, max_dimension)?;
+        let _guard = THUMBNAIL_CACHE_LOCK.lock().unwrap();
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create data directory: {}", e))?;
+        crate::atomic_write(&cache_file, &png_bytes)
+            .map_err(|e| format!("Failed to write thumbnail: {}", e))?;
+        evict(&dir);
+
+        Ok(Thumbnail {
+            path: cache_file.to_string_lossy().to_string(),
+            data_url: data_url(&png_bytes),
+            width,
+            height,
+            cached: false,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to render thumbnail: {}", e))?
+}
+
+// Removes every cached thumbnail of file_path, or the whole cache without
+// one. Returns how many files went.
+#[tauri::command]
+pub fn invalidate_thumbnail_cache(
+    app: AppHandle,
+    file_path: Option<String>,
+) -> Result<usize, String> {
+    let prefix = file_path
+        .as_deref()
+        .map(|path| path_prefix(Path::new(path)));
+    remove_thumbnails(&thumbnail_dir(&app)?, prefix.as_deref())
+}
+
+// Drops the thumbnails of a PDF that was renamed, moved or deleted, which
+// would otherwise sit in the cache until evicted
+pub fn forget(file_path: &Path) {
+    let Some(dir) = CACHE_DIR.lock().unwrap().clone() else {
+        return;
+    };
+    if let Err(error) = remove_thumbnails(&dir, Some(&path_prefix(file_path))) {
+        warn!("{}", error);
+    }
+}
+
+fn remove_thumbnails(dir: &Path, prefix: Option<&str>) -> Result<usize, String> {
+    let _guard = THUMBNAIL_CACHE_LOCK.lock().unwrap();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(error) => return Err(format!("Failed to read thumbnail cache: {}", error)),
+    };
+
+    let mut removed = 0;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if prefix.is_some_and(|prefix| !name.starts_with(prefix)) {
+            continue;
+        }
+        match fs::remove_file(entry.path()) {
+            Ok(()) => removed += 1,
+            Err(error) => warn!("Failed to remove thumbnail {}: {:?}", name, error),
+        }
+    }
+    Ok(removed)
+}
@@ -0,0 +1,291 @@
+use log::{error, warn};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::State;
+use url::Url;
+
+use crate::downloads::{DownloadInfo, DownloadManager, DownloadRequest, DownloadState};
+use crate::importer::{self, non_empty_text, sanitize_title_for_filename};
+use crate::pdf;
+use crate::sidecar;
+
+// The header may sit anywhere in the first KiB per the spec
+const PDF_HEADER_WINDOW: usize = 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlPaperMetadata {
+    pub title: Option<String>,
+    pub url: String,
+    // Where the redirects ended up, when different from `url`
+    pub resolved_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UrlImportResult {
+    // "downloaded" or "skipped"; skip reasons are invalid_link,
+    // too_many_redirects, access_denied, paper_not_found, network_error,
+    // html_landing_page, not_a_pdf, corrupt_download, file_exists and
+    // write_failed
+    pub status: String,
+    pub reason: Option<String>,
+    pub detail: Option<String>,
+    pub pdf_path: Option<String>,
+    pub pdf_size: Option<u64>,
+    pub metadata_path: Option<String>,
+    pub paper: Option<UrlPaperMetadata>,
+}
+
+fn skipped_result(reason: &str) -> UrlImportResult {
+    UrlImportResult {
+        status: "skipped".to_string(),
+        reason: Some(reason.to_string()),
+        ..Default::default()
+    }
+}
+
+fn looks_like_html(content_type: Option<&str>, body: &[u8]) -> bool {
+    if content_type
+        .map(|value| value.to_lowercase().contains("html"))
+        .unwrap_or(false)
+    {
+        return true;
+    }
+
+    let start = String::from_utf8_lossy(&body[..body.len().min(PDF_HEADER_WINDOW)])
+        .trim_start()
+        .to_lowercase();
+    start.starts_with("<!doctype html") || start.starts_with("<html")
+}
+
+fn has_pdf_header(body: &[u8]) -> bool {
+    body[..body.len().min(PDF_HEADER_WINDOW)]
+        .windows(5)
+        .any(|window| window == b"%PDF-")
+}
+
+// Prefers the RFC 5987 `filename*=UTF-8''...` form over plain `filename=`
+fn content_disposition_filename(value: &str) -> Option<String> {
+    let parameter = |name: &str| {
+        value.split(';').find_map(|part| {
+            let (key, value) = part.split_once('=')?;
+            if key.trim().eq_ignore_ascii_case(name) {
+                Some(value.trim().trim_matches('"').to_string())
+            } else {
+                None
+            }
+        })
+    };
+
+    if let Some(extended) = parameter("filename*") {
+        let encoded = extended.splitn(3, '\'').nth(2).unwrap_or(&extended);
+        let decoded = percent_decode_str(encoded).decode_utf8_lossy().to_string();
+        if let Some(name) = non_empty_text(Some(decoded)) {
+            return Some(name);
+        }
+    }
+
+    non_empty_text(parameter("filename"))
+}
+
+fn url_filename(url: &Url) -> Option<String> {
+    let segment = url
+        .path_segments()?
+        .rev()
+        .find(|segment| !segment.is_empty())?;
+    non_empty_text(Some(
+        percent_decode_str(segment).decode_utf8_lossy().to_string(),
+    ))
+}
+
+fn strip_pdf_extension(name: &str) -> &str {
+    match name.len().checked_sub(4) {
+        Some(split)
+            if name.is_char_boundary(split) && name[split..].eq_ignore_ascii_case(".pdf") =>
+        {
+            &name[..split]
+        }
+        _ => name,
+    }
+}
+
+#[tauri::command]
+pub async fn import_pdf_from_url(
+    downloads: State<'_, DownloadManager>,
+    url: String,
+    target_dir: String,
+    conflict_policy: String,
+    suggested_name: Option<String>,
+) -> Result<UrlImportResult, String> {
+    importer::check_conflict_policy(&conflict_policy)?;
+    importer::check_target_dir(&target_dir)?;
+
+    let parsed_url = match Url::parse(url.trim()) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => parsed,
+        _ => return Ok(skipped_result("invalid_link")),
+    };
+
+    let target = Path::new(&target_dir);
+    if !importer::ensure_target_dir(target) {
+        return Ok(skipped_result("write_failed"));
+    }
+
+    // The file is named after the response (Content-Disposition, the PDF's
+    // own title), so the body is downloaded under a hidden name first
+    let staging = target.join(format!(".{}.download", uuid::Uuid::new_v4()));
+    let job = serde_json::json!({ "importer": "url", "url": parsed_url.as_str(), "kind": "pdf" });
+    let info = downloads
+        .download(DownloadRequest {
+            url: parsed_url.to_string(),
+            destination: staging.clone(),
+            provenance: Some(job),
+            cancel: None,
+            expect_pdf: false,
+        })
+        .await;
+
+    let result = place_download(info, &staging, target, parsed_url, suggested_name);
+    if staging.exists() {
+        let _ = fs::remove_file(&staging);
+    }
+    Ok(result)
+}
+
+// Checks a finished download and moves it to its final name
+fn place_download(
+    info: DownloadInfo,
+    staging: &Path,
+    target: &Path,
+    parsed_url: Url,
+    suggested_name: Option<String>,
+) -> UrlImportResult {
+    match (info.state, info.reason.as_deref()) {
+        (DownloadState::Done, _) => {}
+        (_, Some("too_many_redirects")) => {
+            let mut result = skipped_result("too_many_redirects");
+            result.detail = info.error.filter(|url| !url.is_empty());
+            return result;
+        }
+        (_, Some("http_error")) => {
+            let status = info.http_status.unwrap_or_default();
+            let reason = match status {
+                401 | 403 => "access_denied",
+                404 | 410 => "paper_not_found",
+                _ => "network_error",
+            };
+            let mut result = skipped_result(reason);
+            result.detail = Some(format!("HTTP {}", status));
+            return result;
+        }
+        (_, Some("write_failed")) => {
+            error!("Failed to write downloaded PDF: {:?}", info.error);
+            return skipped_result("write_failed");
+        }
+        _ => {
+            warn!("Failed to download {}: {:?}", parsed_url, info.error);
+            return skipped_result("network_error");
+        }
+    }
+
+    let body = match fs::read(staging) {
+        Ok(body) => body,
+        Err(error) => {
+            error!("Failed to read downloaded PDF: {:?}", error);
+            return skipped_result("write_failed");
+        }
+    };
+
+    // Login walls and "download" pages often answer 200 with HTML, sometimes
+    // even labelled application/pdf
+    if !has_pdf_header(&body) {
+        let reason = if looks_like_html(info.content_type.as_deref(), &body) {
+            "html_landing_page"
+        } else {
+            "not_a_pdf"
+        };
+        let mut result = skipped_result(reason);
+        result.detail = Some(importer::body_preview(&body));
+        return result;
+    }
+
+    if !pdf::tail_has_eof_marker(&body) {
+        return skipped_result("corrupt_download");
+    }
+
+    let resolved_url = info
+        .resolved_url
+        .as_deref()
+        .and_then(|resolved| Url::parse(resolved).ok())
+        .unwrap_or_else(|| parsed_url.clone());
+    let disposition_name = info
+        .content_disposition
+        .as_deref()
+        .and_then(content_disposition_filename);
+    let title = pdf::info_title(&body);
+    let name = non_empty_text(suggested_name)
+        .or(disposition_name)
+        .or_else(|| url_filename(&resolved_url))
+        .or_else(|| title.clone())
+        .unwrap_or_default();
+    let file_stem = sanitize_title_for_filename(strip_pdf_extension(&name));
+    let pdf_path = target.join(format!("{}.pdf", file_stem));
+    let metadata_path = sidecar::sidecar_for_pdf(&pdf_path, crate::default_sidecar_format());
+
+    let paper = UrlPaperMetadata {
+        title,
+        url: parsed_url.to_string(),
+        resolved_url: if resolved_url != parsed_url {
+            Some(resolved_url.to_string())
+        } else {
+            None
+        },
+    };
+
+    if pdf_path.exists() {
+        return UrlImportResult {
+            status: "skipped".to_string(),
+            reason: Some("file_exists".to_string()),
+            pdf_path: Some(pdf_path.to_string_lossy().to_string()),
+            metadata_path: if metadata_path.exists() {
+                Some(metadata_path.to_string_lossy().to_string())
+            } else {
+                None
+            },
+            paper: Some(paper),
+            ..Default::default()
+        };
+    }
+
+    // The sidecar is staged too and both are renamed into place together, as
+    // for arXiv
+    let metadata_part = importer::part_path(&metadata_path);
+    importer::remove_stale_part(&metadata_part);
+    let sha256 = info.sha256.unwrap_or_default();
+    if let Err(error) = importer::commit_import(
+        Some((staging, &pdf_path)),
+        &metadata_part,
+        &metadata_path,
+        |part| {
+            importer::write_sidecar(
+                &paper,
+                "url",
+                Some(&pdf_path),
+                part,
+                importer::checksum_fields(&sha256),
+            )
+        },
+    ) {
+        error!("{}", error);
+        return skipped_result("write_failed");
+    }
+
+    UrlImportResult {
+        status: "downloaded".to_string(),
+        pdf_path: Some(pdf_path.to_string_lossy().to_string()),
+        pdf_size: Some(body.len() as u64),
+        metadata_path: Some(metadata_path.to_string_lossy().to_string()),
+        paper: Some(paper),
+        ..Default::default()
+    }
+}
@@ -158,6 +158,17 @@ export function ImportModal({
     return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
   };
 
+  // Symlinks and hard links to a file the scan also found share its data, so
+  // they're marked rather than counted as copies
+  const filesPerId = new Map<string, number>();
+  for (const file of scanResult?.files ?? []) {
+    if (file.file_id) {
+      filesPerId.set(file.file_id, (filesPerId.get(file.file_id) ?? 0) + 1);
+    }
+  }
+  const isLinked = (file: ScannedFile): boolean =>
+    Boolean(file.is_symlink) || (!!file.file_id && (filesPerId.get(file.file_id) ?? 0) > 1);
+
   if (!isOpen) return null;
 
   return (
@@ -323,6 +334,14 @@ export function ImportModal({
                           <span className="flex-1 text-sm text-slate-800 truncate">
                             {file.name}
                           </span>
+                          {isLinked(file) && (
+                            <span
+                              className="text-xs text-slate-400"
+                              title="Shares its data with another file in this scan"
+                            >
+                              linked
+                            </span>
+                          )}
                           <span className="text-xs text-slate-400">
                             {formatSize(file.size)}
                           </span>
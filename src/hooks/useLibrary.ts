@@ -148,7 +148,7 @@ export function useLibrary(): UseLibraryReturn {
           const scannedFile: ScannedFile = {
             name: event.filePath.split('/').pop() || 'Unknown',
             path: event.filePath,
-            size: 0,
+            size: event.size ?? 0,
           };
 
           const result = await libraryService.importFiles(
@@ -432,9 +432,9 @@ export function useLibrary(): UseLibraryReturn {
           file_exists: 'This paper already exists in your download folder. Skipped.',
           invalid_link: 'Invalid arXiv URL or ID.',
           paper_not_found: 'Paper not found on arXiv.',
+          pdf_unavailable: 'arXiv has this paper but its PDF could not be downloaded.',
           write_failed: 'Cannot write files to the selected folder.',
           network_error: 'Network error while downloading from arXiv.',
-          invalid_conflict_policy: 'Unsupported conflict policy.',
         };
 
         return {
@@ -476,7 +476,12 @@ export function useLibrary(): UseLibraryReturn {
       console.error('Error importing arXiv paper:', error);
       return {
         status: 'error',
-        message: error instanceof Error ? error.message : 'Failed to import from arXiv.',
+        message:
+          error instanceof Error
+            ? error.message
+            : typeof error === 'string'
+              ? error
+              : 'Failed to import from arXiv.',
       };
     } finally {
       setIsLoading(false);
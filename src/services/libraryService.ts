@@ -12,6 +12,10 @@ import type {
   ScannedFile,
   ImportResult,
   FolderChangedEvent,
+  FileChangedEvent,
+  WatcherErrorEvent,
+  FileStatus,
+  FilenameValidation,
 } from '../types/library';
 import type { ArxivImportRequest, ArxivImportResult } from '../types/arxiv';
 
@@ -27,13 +31,18 @@ function generateId(): string {
 export async function scanDirectory(
   dirPath: string,
   recursive: boolean = true,
-  maxDepth: number = 10
+  maxDepth: number = 10,
+  followSymlinks: boolean = false,
+  // Give up on slow or hung mounts and return what was found so far
+  timeoutSecs?: number
 ): Promise<ScanResult> {
   try {
     const result = await invoke<ScanResult>('scan_directory_for_pdfs', {
       dirPath,
       recursive,
       maxDepth,
+      followSymlinks,
+      timeoutSecs,
     });
     return result;
   } catch (error) {
@@ -214,6 +223,8 @@ export async function importFiles(
     importedItems: [],
   };
 
+  // Scans report a symlink under its own path (see ScannedFile), so a link
+  // next to the file it points to is imported as an item of its own
   const existingPaths = new Set(currentItems.map((item) => item.path));
 
   for (let i = 0; i < files.length; i++) {
@@ -249,12 +260,22 @@ export async function importFiles(
 // Start watching a folder
 export async function startWatchFolder(
   folderPath: string,
-  recursive: boolean = true
+  recursive: boolean = true,
+  replace: boolean = false,
+  settle?: { stableMs?: number; pollIntervalMs?: number },
+  // Also report the PDFs already there as 'created' events
+  emitInitial: boolean = false
 ): Promise<string> {
   try {
+    // Returns the existing watch id when this folder is already watched,
+    // unless replace asks for a fresh watcher
     const watchId = await invoke<string>('start_watch_folder', {
       folderPath,
       recursive,
+      replace,
+      stableMs: settle?.stableMs,
+      pollIntervalMs: settle?.pollIntervalMs,
+      emitInitial,
     });
     return watchId;
   } catch (error) {
@@ -263,6 +284,16 @@ export async function startWatchFolder(
   }
 }
 
+// Watch a single file, e.g. the open document; stop it with stopWatchFolder
+export async function startWatchFile(filePath: string): Promise<string> {
+  try {
+    return await invoke<string>('start_watch_file', { filePath });
+  } catch (error) {
+    console.error('Error starting file watch:', error);
+    throw error;
+  }
+}
+
 // Stop watching a folder
 export async function stopWatchFolder(watchId: string): Promise<void> {
   try {
@@ -284,6 +315,28 @@ export async function onFolderChanged(
   return unlisten;
 }
 
+// Listen for changes to files watched with startWatchFile
+export async function onFileChanged(
+  callback: (event: FileChangedEvent) => void
+): Promise<() => void> {
+  const unlisten = await listen<FileChangedEvent>('file-changed', (event) => {
+    callback(event.payload);
+  });
+
+  return unlisten;
+}
+
+// Listen for watchers that failed, e.g. because their folder disappeared
+export async function onWatcherError(
+  callback: (event: WatcherErrorEvent) => void
+): Promise<() => void> {
+  const unlisten = await listen<WatcherErrorEvent>('watcher-error', (event) => {
+    callback(event.payload);
+  });
+
+  return unlisten;
+}
+
 // Migrate old recentFiles to new library format
 export function migrateRecentFiles(): LibraryItem[] | null {
   try {
@@ -410,26 +463,52 @@ export async function verifyAndCleanLibrary(
   }
 }
 
+// Per-path status with the reason a file can't be opened, if any
+export async function verifyFilesDetailed(filePaths: string[]): Promise<FileStatus[]> {
+  return invoke<FileStatus[]>('verify_files_detailed', { filePaths });
+}
+
+// Checks a file name the way renamePdfFile will, for feedback while typing
+export async function validateFilename(
+  name: string,
+  maxBytes?: number
+): Promise<FilenameValidation> {
+  return invoke<FilenameValidation>('validate_filename', { name, maxBytes });
+}
+
 // Rename a PDF file in the filesystem
 export async function renamePdfFile(
   oldPath: string,
-  newName: string
+  newName: string,
+  onConflict: 'error' | 'auto_suffix' = 'error',
+  clearReadonly = false
 ): Promise<{ success: boolean; newPath?: string; error?: string }> {
   try {
     // Remove .pdf extension from newName if present (we'll preserve the original extension)
     const cleanName = newName.replace(/\.pdf$/i, '');
 
+    // With auto_suffix the backend picks the first free "<name>_N" and
+    // returns the path it actually used
     const newPath = await invoke<string>('rename_file', {
       oldPath,
       newName: cleanName,
+      onConflict,
+      clearReadonly,
     });
 
     return { success: true, newPath };
   } catch (error) {
     console.error('Error renaming file:', error);
+    // Command errors arrive as strings, e.g. "file_locked: ..." or
+    // "read_only: ..." for files the user can unlock and retry
     return {
       success: false,
-      error: error instanceof Error ? error.message : 'Unknown error',
+      error:
+        typeof error === 'string'
+          ? error
+          : error instanceof Error
+            ? error.message
+            : 'Unknown error',
     };
   }
 }
@@ -448,16 +527,22 @@ export async function importArxivPaper(
       inputUrlOrId: request.input_url_or_id,
       targetDir: request.target_dir,
       conflictPolicy: request.conflict_policy,
+      options: request.options,
+      importId: request.import_id,
     });
   } catch (error) {
+    // Rejections are caller mistakes ("<code>: <message>"); environmental
+    // failures come back as skipped results instead.
     console.error('Error importing arXiv paper:', error);
-    return {
-      status: 'skipped',
-      reason: 'network_error',
-    };
+    throw error;
   }
 }
 
+// Resolves false when the import already finished
+export async function cancelImport(importId: string): Promise<boolean> {
+  return invoke<boolean>('cancel_import', { importId });
+}
+
 export function createScannedFileFromArxivResult(
   result: ArxivImportResult
 ): ScannedFile | null {
@@ -1,30 +1,94 @@
+export interface ArxivAuthor {
+  name: string;
+  affiliation?: string;
+  orcid?: string;
+  orcid_confidence?: number;
+}
+
 export interface ArxivPaperMetadata {
   arxiv_id: string;
   version: number;
   title: string;
   authors: string[];
+  authors_detailed?: ArxivAuthor[];
   summary: string;
   published: string;
   updated: string;
+  published_year?: number;
+  published_month?: number;
   abs_url: string;
   pdf_url: string;
+  primary_category?: string;
+  categories: string[];
+  doi?: string;
+  journal_ref?: string;
+  comment?: string;
+  withdrawn?: boolean;
+}
+
+export interface ArxivImportOptions {
+  mode?: 'full' | 'metadata_only' | 'attach';
+  existing_pdf_path?: string;
+  include_source?: boolean;
+  api_base?: string;
+  pdf_base?: string;
+  strict?: boolean;
+  library_roots?: string[];
+  write_readme?: boolean;
+  write_abstract?: boolean;
+  metadata_only?: boolean;
+  filename_template?: string;
+  sidecar_format?: 'json' | 'yaml' | 'toml' | 'markdown_frontmatter';
+  enrich_orcid?: boolean;
+  // Citation counts and venue from Semantic Scholar
+  enrich?: boolean;
+  // 'source' fetches only the LaTeX e-print, as {file_stem}.tar.gz
+  format?: 'pdf' | 'source';
+}
+
+export interface ArxivEnrichment {
+  citation_count?: number;
+  influential_citation_count?: number;
+  venue?: string;
 }
 
 export interface ArxivImportRequest {
   input_url_or_id: string;
   target_dir: string;
-  conflict_policy: 'skip';
+  conflict_policy: 'skip' | 'rename';
+  options?: ArxivImportOptions;
+  // Lets cancelImport stop this import while it runs
+  import_id?: string;
 }
 
 export interface ArxivImportResult {
-  status: 'downloaded' | 'skipped';
+  status: 'downloaded' | 'metadata_only' | 'attached' | 'source_only' | 'skipped';
   reason?: string;
+  detail?: string;
   pdf_path?: string;
+  renamed_from?: string;
   pdf_size?: number;
+  resolved_pdf_url?: string;
+  http_status?: number;
+  sha256?: string;
   metadata_path?: string;
+  bibtex_path?: string;
+  source_path?: string;
+  source_size?: number;
+  readme_path?: string;
+  abstract_path?: string;
+  enrichment?: ArxivEnrichment;
+  warnings?: string[];
+  available_versions?: number[];
   paper?: ArxivPaperMetadata;
 }
 
+export interface ArxivVersion {
+  version: number;
+  submitted?: string;
+  size?: string;
+}
+
 export interface ArxivImportOutcome {
   status: 'downloaded' | 'skipped' | 'error';
   message: string;
@@ -0,0 +1,27 @@
+export type DownloadState = 'queued' | 'active' | 'paused' | 'done' | 'failed' | 'cancelled';
+
+// Payload of list_downloads and of each "download-state" event
+export interface DownloadInfo {
+  id: string;
+  url: string;
+  destination: string;
+  provenance?: Record<string, unknown> | null;
+  state: DownloadState;
+  bytes_downloaded: number;
+  total_bytes?: number | null;
+  http_status?: number | null;
+  resolved_url?: string | null;
+  content_type?: string | null;
+  content_disposition?: string | null;
+  reason?:
+    | 'network_error'
+    | 'too_many_redirects'
+    | 'http_error'
+    | 'not_a_pdf'
+    | 'write_failed'
+    | 'cancelled'
+    | null;
+  error?: string | null;
+  // Set once the download is done
+  sha256?: string | null;
+}
@@ -39,12 +39,17 @@ export interface ScanResult {
   totalCount: number;
   errorCount: number;
   errors: string[];
+  timed_out?: boolean;
 }
 
 export interface ScannedFile {
   name: string;
   path: string;
   size: number;
+  // For a symlink, path is the link itself and link_target the file it points to
+  is_symlink?: boolean;
+  link_target?: string | null;
+  file_id?: string | null;
 }
 
 export interface ImportProgress {
@@ -67,11 +72,64 @@ export interface ImportError {
   error: string;
 }
 
+export interface FileStatus {
+  path: string;
+  exists: boolean;
+  accessible: boolean;
+  reason?:
+    | 'not_found'
+    | 'permission_denied'
+    | 'parent_unreachable'
+    | 'is_directory'
+    | 'broken_symlink'
+    | null;
+  size?: number | null;
+  modified?: number | null;
+}
+
+export interface FilenameValidation {
+  valid: boolean;
+  code?:
+    | 'empty_name'
+    | 'invalid_character'
+    | 'reserved_name'
+    | 'trailing_dot_or_space'
+    | 'name_too_long'
+    | null;
+  message?: string | null;
+  suggestion?: string | null;
+}
+
 export interface FolderChangedEvent {
   watchId: string;
   folderPath: string;
-  eventType: 'created' | 'removed' | 'modified';
+  eventType: 'created' | 'removed' | 'modified' | 'folder_renamed' | 'folder_removed';
   filePath: string;
+  size?: number | null;
+  modified?: number | null;
+  // On 'removed': the app deleted or trashed the file itself
+  initiatedByApp?: boolean;
+  // On 'folder_renamed': where the folder at filePath went
+  newPath?: string | null;
+  // On folder events: the watcher was on that folder or inside it and has
+  // been stopped
+  watcherStopped?: boolean;
+}
+
+export interface FileChangedEvent {
+  watchId: string;
+  filePath: string;
+  eventType: 'modified' | 'deleted' | 'renamed';
+  // On 'renamed', when the platform reports it
+  newPath?: string | null;
+}
+
+export interface WatcherErrorEvent {
+  watchId: string;
+  folderPath: string;
+  message: string;
+  // The watcher was removed and has to be started again
+  fatal: boolean;
 }
 
 export interface LibraryState {